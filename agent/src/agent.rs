@@ -0,0 +1,227 @@
+//! Provisioning logic, independent of the chain connection
+
+use crate::backend::{Backend, Manifest};
+use pallet_kumulus::{ResourceId, UsageReport};
+use std::collections::BTreeMap;
+
+/// Kumulus events relevant to the provider
+#[derive(Debug)]
+pub enum ChainEvent {
+    Rented {
+        resource_id: ResourceId,
+    },
+    Ended {
+        resource_id: ResourceId,
+    },
+    DeploymentUpdated {
+        resource_id: ResourceId,
+        version: u32,
+        manifest: Manifest,
+    },
+}
+
+#[derive(Default)]
+struct Rental {
+    deployed: Option<u32>,   // Version of the running deployment
+    unreported: UsageReport, // Measured but not submitted yet
+}
+
+pub struct Agent<B> {
+    backend: B,
+    // Ongoing rentals of the provider
+    rentals: BTreeMap<ResourceId, Rental>,
+}
+
+impl<B: Backend> Agent<B> {
+    pub fn new(backend: B) -> Self {
+        Self {
+            backend,
+            rentals: BTreeMap::new(),
+        }
+    }
+
+    pub async fn handle(&mut self, event: ChainEvent) -> anyhow::Result<()> {
+        match event {
+            ChainEvent::Rented { resource_id } => {
+                log::info!("resource {resource_id} rented, waiting for its deployment");
+                self.rentals.insert(resource_id, Rental::default());
+            }
+            ChainEvent::Ended { resource_id } => {
+                if let Some(rental) = self.rentals.remove(&resource_id) {
+                    log::info!("rental of resource {resource_id} ended");
+                    if rental.deployed.is_some() {
+                        self.backend.stop(resource_id).await?;
+                    }
+                }
+            }
+            ChainEvent::DeploymentUpdated {
+                resource_id,
+                version,
+                manifest,
+            } => {
+                // Rentals of other providers
+                let Some(rental) = self.rentals.get_mut(&resource_id) else {
+                    return Ok(());
+                };
+
+                if rental.deployed.take().is_some() {
+                    self.backend.stop(resource_id).await?;
+                }
+                self.backend.deploy(resource_id, &manifest).await?;
+                rental.deployed = Some(version);
+
+                log::info!("resource {resource_id} runs deployment version {version}");
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn rentals(&self) -> Vec<ResourceId> {
+        self.rentals.keys().copied().collect()
+    }
+
+    /// Usage not reported yet of every rental, at most `max` per rental. Usage left over is
+    /// kept for the next reports.
+    pub async fn usage(&mut self, max: &UsageReport) -> Vec<(ResourceId, UsageReport)> {
+        let mut reports = Vec::new();
+
+        for (resource_id, rental) in &mut self.rentals {
+            if rental.deployed.is_some() {
+                match self.backend.usage(*resource_id).await {
+                    Ok(report) => {
+                        rental.unreported =
+                            combine(&rental.unreported, &report, u64::saturating_add)
+                    }
+                    Err(e) => log::warn!("no usage for resource {resource_id}: {e:?}"),
+                }
+            }
+
+            if rental.unreported != UsageReport::default() {
+                reports.push((*resource_id, combine(&rental.unreported, max, u64::min)));
+            }
+        }
+
+        reports
+    }
+
+    /// Forget usage once submitted
+    pub fn reported(&mut self, resource_id: ResourceId, report: &UsageReport) {
+        if let Some(rental) = self.rentals.get_mut(&resource_id) {
+            rental.unreported = combine(&rental.unreported, report, u64::saturating_sub);
+        }
+    }
+}
+
+fn combine(a: &UsageReport, b: &UsageReport, f: fn(u64, u64) -> u64) -> UsageReport {
+    UsageReport {
+        cpu_seconds: f(a.cpu_seconds, b.cpu_seconds),
+        egress_gb: f(a.egress_gb, b.egress_gb),
+        storage_gb_hours: f(a.storage_gb_hours, b.storage_gb_hours),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::LocalBackend;
+    use async_trait::async_trait;
+    use pallet_kumulus::DeploymentManifest;
+    use subxt::utils::H256;
+
+    fn agent() -> Agent<LocalBackend> {
+        Agent::new(LocalBackend::new("sleep".into(), vec!["30".into()]))
+    }
+
+    fn cpu(cpu_seconds: u64) -> UsageReport {
+        UsageReport {
+            cpu_seconds,
+            ..Default::default()
+        }
+    }
+
+    // Uses the same CPU time between two reports
+    struct Metered(u64);
+
+    #[async_trait]
+    impl Backend for Metered {
+        async fn deploy(&mut self, _: ResourceId, _: &Manifest) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        async fn stop(&mut self, _: ResourceId) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        async fn usage(&mut self, _: ResourceId) -> anyhow::Result<UsageReport> {
+            Ok(cpu(self.0))
+        }
+    }
+
+    #[tokio::test]
+    async fn workloads_follow_rentals() {
+        let mut agent = agent();
+        let manifest = DeploymentManifest::OffChain(H256::repeat_byte(1));
+
+        // Deployments of resources rented from other providers are ignored
+        agent
+            .handle(ChainEvent::DeploymentUpdated {
+                resource_id: 1,
+                version: 1,
+                manifest: manifest.clone(),
+            })
+            .await
+            .unwrap();
+        assert!(!agent.backend.is_running(1));
+
+        agent
+            .handle(ChainEvent::Rented { resource_id: 0 })
+            .await
+            .unwrap();
+        agent
+            .handle(ChainEvent::DeploymentUpdated {
+                resource_id: 0,
+                version: 1,
+                manifest,
+            })
+            .await
+            .unwrap();
+        assert!(agent.backend.is_running(0));
+        assert_eq!(agent.rentals.get(&0).unwrap().deployed, Some(1));
+
+        // An idle process uses no CPU time
+        assert!(agent.usage(&cpu(u64::MAX)).await.is_empty());
+
+        agent
+            .handle(ChainEvent::Ended { resource_id: 0 })
+            .await
+            .unwrap();
+        assert!(!agent.backend.is_running(0));
+        assert!(agent.rentals().is_empty());
+    }
+
+    #[tokio::test]
+    async fn usage_is_kept_until_reported() {
+        let mut agent = Agent::new(Metered(30));
+        agent
+            .handle(ChainEvent::Rented { resource_id: 0 })
+            .await
+            .unwrap();
+        agent
+            .handle(ChainEvent::DeploymentUpdated {
+                resource_id: 0,
+                version: 1,
+                manifest: DeploymentManifest::OffChain(H256::repeat_byte(1)),
+            })
+            .await
+            .unwrap();
+
+        // Capped per report, the rest waits for the next one
+        assert_eq!(agent.usage(&cpu(20)).await, vec![(0, cpu(20))]);
+        agent.reported(0, &cpu(20));
+        assert_eq!(agent.usage(&cpu(100)).await, vec![(0, cpu(40))]);
+
+        // Unsubmitted usage adds up
+        assert_eq!(agent.usage(&cpu(100)).await, vec![(0, cpu(70))]);
+    }
+}
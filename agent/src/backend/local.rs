@@ -0,0 +1,132 @@
+//! Runs each workload as a local process, for development and tests
+
+use super::{Backend, Manifest};
+use anyhow::{anyhow, Context};
+use async_trait::async_trait;
+use pallet_kumulus::{DeploymentManifest, ResourceId, UsageReport};
+use std::collections::BTreeMap;
+use tokio::process::{Child, Command};
+
+/// Clock ticks per second of the CPU times in `/proc`, fixed by the kernel ABI
+const CLOCK_TICKS: u64 = 100;
+
+struct Workload {
+    process: Child,
+    reported_ticks: u64, // CPU time already reported
+}
+
+pub struct LocalBackend {
+    program: String,
+    args: Vec<String>,
+    workloads: BTreeMap<ResourceId, Workload>,
+}
+
+impl LocalBackend {
+    pub fn new(program: String, args: Vec<String>) -> Self {
+        Self {
+            program,
+            args,
+            workloads: BTreeMap::new(),
+        }
+    }
+
+    #[cfg(test)]
+    pub fn is_running(&mut self, resource_id: ResourceId) -> bool {
+        self.workloads
+            .get_mut(&resource_id)
+            .is_some_and(|workload| matches!(workload.process.try_wait(), Ok(None)))
+    }
+}
+
+#[async_trait]
+impl Backend for LocalBackend {
+    async fn deploy(&mut self, resource_id: ResourceId, manifest: &Manifest) -> anyhow::Result<()> {
+        let mut command = Command::new(&self.program);
+        command
+            .args(&self.args)
+            .env("KUMULUS_RESOURCE_ID", resource_id.to_string())
+            .kill_on_drop(true);
+
+        match manifest {
+            DeploymentManifest::Container(container) => {
+                let ports: Vec<String> = container.ports.iter().map(u16::to_string).collect();
+                command
+                    .env(
+                        "KUMULUS_IMAGE",
+                        String::from_utf8_lossy(&container.image).as_ref(),
+                    )
+                    .env("KUMULUS_PORTS", ports.join(","))
+                    .env("KUMULUS_ENV_HASH", format!("{:?}", container.env_hash));
+            }
+            DeploymentManifest::OffChain(hash) => {
+                command.env("KUMULUS_MANIFEST", format!("{hash:?}"));
+            }
+        }
+
+        let process = command
+            .spawn()
+            .with_context(|| format!("cannot start the workload of resource {resource_id}"))?;
+
+        self.workloads.insert(
+            resource_id,
+            Workload {
+                process,
+                reported_ticks: 0,
+            },
+        );
+
+        Ok(())
+    }
+
+    async fn stop(&mut self, resource_id: ResourceId) -> anyhow::Result<()> {
+        if let Some(mut workload) = self.workloads.remove(&resource_id) {
+            workload.process.kill().await?;
+        }
+
+        Ok(())
+    }
+
+    async fn usage(&mut self, resource_id: ResourceId) -> anyhow::Result<UsageReport> {
+        let workload = self
+            .workloads
+            .get_mut(&resource_id)
+            .ok_or_else(|| anyhow!("no workload for resource {resource_id}"))?;
+
+        let pid = workload
+            .process
+            .id()
+            .ok_or_else(|| anyhow!("workload of resource {resource_id} exited"))?;
+        let ticks = cpu_ticks(pid)?;
+
+        // Whole seconds only, the remainder is reported next time
+        let cpu_seconds = ticks.saturating_sub(workload.reported_ticks) / CLOCK_TICKS;
+        workload.reported_ticks += cpu_seconds * CLOCK_TICKS;
+
+        // Egress and storage aren't metered for a local process
+        Ok(UsageReport {
+            cpu_seconds,
+            egress_gb: 0,
+            storage_gb_hours: 0,
+        })
+    }
+}
+
+/// CPU time used by a process and its waited-for children, in clock ticks
+fn cpu_ticks(pid: u32) -> anyhow::Result<u64> {
+    let stat = std::fs::read_to_string(format!("/proc/{pid}/stat"))
+        .with_context(|| format!("cannot read the CPU time of process {pid}"))?;
+
+    // The command name may contain spaces, the fields from the state on follow its parenthesis
+    let (_, fields) = stat
+        .rsplit_once(')')
+        .ok_or_else(|| anyhow!("malformed stat of process {pid}"))?;
+    let fields: Vec<&str> = fields.split_whitespace().collect();
+
+    // utime, stime, cutime and cstime
+    fields
+        .get(11..15)
+        .ok_or_else(|| anyhow!("malformed stat of process {pid}"))?
+        .iter()
+        .map(|field| Ok(field.parse::<u64>()?))
+        .sum()
+}
@@ -0,0 +1,22 @@
+//! Workload backends driven by the agent
+
+mod local;
+
+pub use local::LocalBackend;
+
+use async_trait::async_trait;
+use pallet_kumulus::{DeploymentManifest, ResourceId, UsageReport};
+use subxt::utils::H256;
+
+pub type Manifest = DeploymentManifest<H256>;
+
+#[async_trait]
+pub trait Backend: Send {
+    /// Start the workload of a rental, a running one is stopped first by the agent
+    async fn deploy(&mut self, resource_id: ResourceId, manifest: &Manifest) -> anyhow::Result<()>;
+
+    async fn stop(&mut self, resource_id: ResourceId) -> anyhow::Result<()>;
+
+    /// Resources consumed by the workload since the previous call
+    async fn usage(&mut self, resource_id: ResourceId) -> anyhow::Result<UsageReport>;
+}
@@ -0,0 +1,352 @@
+//! Chain access over RPC, with the dynamic subxt API decoded into the pallet types
+
+use crate::{
+    agent::{Agent, ChainEvent},
+    backend::{Backend, Manifest},
+};
+use anyhow::{anyhow, Context};
+use codec::{Compact, Decode};
+use pallet_kumulus::{Deployment, HostId, Resource, ResourceId, UsageReport};
+use std::str::FromStr;
+use subxt::{
+    dynamic::Value,
+    utils::{AccountId32, H256},
+    OnlineClient, PolkadotConfig,
+};
+use subxt_signer::{sr25519::Keypair, SecretUri};
+
+const PALLET: &str = "Kumulus";
+
+pub fn keypair(suri: &str) -> anyhow::Result<Keypair> {
+    let uri = SecretUri::from_str(suri).context("invalid secret URI")?;
+
+    Keypair::from_uri(&uri).map_err(|e| anyhow!("invalid provider key: {e}"))
+}
+
+pub struct Chain {
+    api: OnlineClient<PolkadotConfig>,
+    signer: Keypair,
+    provider: AccountId32,
+    proxied: bool,
+    max_pending_usage_reports: u32,
+    max_usage_per_report: UsageReport,
+}
+
+impl Chain {
+    /// Connect for the provider account of `signer`, or for `proxied` when `signer` is one of
+    /// its proxies
+    pub async fn connect(
+        url: &str,
+        signer: Keypair,
+        proxied: Option<AccountId32>,
+    ) -> anyhow::Result<Self> {
+        let api = OnlineClient::<PolkadotConfig>::from_url(url)
+            .await
+            .with_context(|| format!("cannot connect to {url}"))?;
+        let provider = proxied
+            .clone()
+            .unwrap_or_else(|| signer.public_key().to_account_id());
+
+        log::info!("following {url} for provider {provider}");
+
+        let constant = |name| {
+            api.constants()
+                .at(&subxt::dynamic::constant(PALLET, name))
+                .with_context(|| format!("no {name} constant"))
+        };
+        let max_pending_usage_reports =
+            u32::decode(&mut constant("MaxPendingUsageReports")?.encoded())?;
+        let max_usage_per_report =
+            UsageReport::decode(&mut constant("MaxUsagePerReport")?.encoded())?;
+
+        Ok(Self {
+            api,
+            signer,
+            provider,
+            proxied: proxied.is_some(),
+            max_pending_usage_reports,
+            max_usage_per_report,
+        })
+    }
+
+    /// Drive the agent from finalized blocks until the subscription ends, starting from the
+    /// rentals ongoing at the first block
+    pub async fn follow<B: Backend>(
+        &self,
+        agent: &mut Agent<B>,
+        heartbeat_interval: u32,
+        usage_interval: u32,
+    ) -> anyhow::Result<()> {
+        let mut blocks = self.api.blocks().subscribe_finalized().await?;
+
+        // The storage of the first block already includes its events
+        if let Some(block) = blocks.next().await {
+            self.resume(agent, block?.hash()).await?;
+        }
+
+        while let Some(block) = blocks.next().await {
+            let block = block?;
+            let (number, hash) = (block.number(), block.hash());
+
+            for event in block.events().await?.iter() {
+                let event = event?;
+                if event.pallet_name() != PALLET {
+                    continue;
+                }
+
+                let Some(event) = self
+                    .decode(hash, event.variant_name(), event.field_bytes())
+                    .await?
+                else {
+                    continue;
+                };
+
+                if let Err(e) = agent.handle(event).await {
+                    log::warn!("#{number}: {e:?}");
+                }
+            }
+
+            if number % heartbeat_interval == 0 {
+                self.submit("heartbeat", vec![]).await;
+            }
+
+            if number % usage_interval == 0 {
+                // Rentals can also end without an event of their own, e.g. after a dispute
+                for resource_id in agent.rentals() {
+                    if !self.is_rented(hash, resource_id).await? {
+                        let event = ChainEvent::Ended { resource_id };
+                        if let Err(e) = agent.handle(event).await {
+                            log::warn!("#{number}: {e:?}");
+                        }
+                    }
+                }
+
+                for (resource_id, report) in agent.usage(&self.max_usage_per_report).await {
+                    // Reports wait for their dispute window to be settled, usage is kept
+                    // until there is room for it
+                    if self.pending_usage_reports(hash, resource_id).await?
+                        >= self.max_pending_usage_reports
+                    {
+                        log::debug!("usage reports of resource {resource_id} are full");
+                        continue;
+                    }
+
+                    if self
+                        .submit(
+                            "submit_usage_report",
+                            vec![Value::u128(resource_id.into()), usage_value(&report)],
+                        )
+                        .await
+                    {
+                        agent.reported(resource_id, &report);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn decode(
+        &self,
+        at: H256,
+        variant: &str,
+        mut fields: &[u8],
+    ) -> anyhow::Result<Option<ChainEvent>> {
+        let event = match variant {
+            "ResourceRented" => {
+                let (resource_id, _renter) = <(ResourceId, AccountId32)>::decode(&mut fields)?;
+                if !self.owns(at, resource_id).await? {
+                    return Ok(None);
+                }
+                ChainEvent::Rented { resource_id }
+            }
+            "RentalCancelled" | "RentalExpired" => {
+                let (resource_id, _renter) = <(ResourceId, AccountId32)>::decode(&mut fields)?;
+                ChainEvent::Ended { resource_id }
+            }
+            "SliceReleased" => {
+                let (_host_id, resource_id) = <(HostId, ResourceId)>::decode(&mut fields)?;
+                ChainEvent::Ended { resource_id }
+            }
+            "DeploymentUpdated" => {
+                let (resource_id, version, manifest) =
+                    <(ResourceId, u32, Manifest)>::decode(&mut fields)?;
+                ChainEvent::DeploymentUpdated {
+                    resource_id,
+                    version,
+                    manifest,
+                }
+            }
+            _ => return Ok(None),
+        };
+
+        Ok(Some(event))
+    }
+
+    /// Hand the ongoing rentals of the provider and their deployments to the agent, the
+    /// slices rented from its hosts included
+    async fn resume<B: Backend>(&self, agent: &mut Agent<B>, at: H256) -> anyhow::Result<()> {
+        for resource_id in self
+            .second_keys(at, "ProviderResources", Value::from_bytes(self.provider.0))
+            .await?
+        {
+            self.resume_rental(agent, at, resource_id).await?;
+        }
+
+        for host_id in self
+            .second_keys(at, "ProviderHosts", Value::from_bytes(self.provider.0))
+            .await?
+        {
+            for resource_id in self
+                .second_keys(at, "HostSlices", Value::u128(host_id.into()))
+                .await?
+            {
+                self.resume_rental(agent, at, resource_id).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn resume_rental<B: Backend>(
+        &self,
+        agent: &mut Agent<B>,
+        at: H256,
+        resource_id: ResourceId,
+    ) -> anyhow::Result<()> {
+        if !self.is_rented(at, resource_id).await? {
+            return Ok(());
+        }
+        agent.handle(ChainEvent::Rented { resource_id }).await?;
+
+        let query =
+            subxt::dynamic::storage(PALLET, "Deployments", vec![Value::u128(resource_id.into())]);
+        if let Some(deployment) = self.api.storage().at(at).fetch(&query).await? {
+            let deployment = Deployment::<H256, u32>::decode(&mut deployment.encoded())?;
+            let event = ChainEvent::DeploymentUpdated {
+                resource_id,
+                version: deployment.version,
+                manifest: deployment.manifest,
+            };
+            if let Err(e) = agent.handle(event).await {
+                log::warn!("cannot resume resource {resource_id}: {e:?}");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Second keys of the entries of a double map under `first`, resource and host ids alike
+    async fn second_keys(&self, at: H256, map: &str, first: Value) -> anyhow::Result<Vec<u32>> {
+        let query = subxt::dynamic::storage(PALLET, map, vec![first]);
+        let mut entries = self.api.storage().at(at).iter(query).await?;
+
+        let mut keys = Vec::new();
+        while let Some(entry) = entries.next().await {
+            let key = entry?
+                .keys
+                .get(1)
+                .and_then(|key| key.as_u128())
+                .and_then(|id| u32::try_from(id).ok())
+                .ok_or_else(|| anyhow!("malformed {map} key"))?;
+            keys.push(key);
+        }
+
+        Ok(keys)
+    }
+
+    /// Whether the resource, or the host a slice is allocated from, is the provider's
+    async fn owns(&self, at: H256, resource_id: ResourceId) -> anyhow::Result<bool> {
+        let storage = self.api.storage().at(at);
+        let query =
+            subxt::dynamic::storage(PALLET, "Resources", vec![Value::u128(resource_id.into())]);
+
+        if let Some(resource) = storage.fetch(&query).await? {
+            let resource = Resource::<AccountId32>::decode(&mut resource.encoded())?;
+            return Ok(resource.provider == self.provider);
+        }
+
+        let query =
+            subxt::dynamic::storage(PALLET, "Allocations", vec![Value::u128(resource_id.into())]);
+        let Some(allocation) = storage.fetch(&query).await? else {
+            return Ok(false);
+        };
+        // Only the host of the allocation and the provider of the host are needed
+        let host_id = HostId::decode(&mut allocation.encoded())?;
+
+        let query = subxt::dynamic::storage(PALLET, "Hosts", vec![Value::u128(host_id.into())]);
+        let Some(host) = storage.fetch(&query).await? else {
+            return Ok(false);
+        };
+
+        Ok(AccountId32::decode(&mut host.encoded())? == self.provider)
+    }
+
+    async fn is_rented(&self, at: H256, resource_id: ResourceId) -> anyhow::Result<bool> {
+        let query =
+            subxt::dynamic::storage(PALLET, "Rentals", vec![Value::u128(resource_id.into())]);
+
+        Ok(self.api.storage().at(at).fetch(&query).await?.is_some())
+    }
+
+    async fn pending_usage_reports(
+        &self,
+        at: H256,
+        resource_id: ResourceId,
+    ) -> anyhow::Result<u32> {
+        let query = subxt::dynamic::storage(
+            PALLET,
+            "UsageReports",
+            vec![Value::u128(resource_id.into())],
+        );
+
+        let Some(reports) = self.api.storage().at(at).fetch(&query).await? else {
+            return Ok(0);
+        };
+
+        // Only the length prefix of the list is needed
+        Ok(Compact::<u32>::decode(&mut reports.encoded())?.0)
+    }
+
+    /// Submit a call of the pallet signed by the provider, or by its proxy, without waiting
+    /// for its inclusion. Returns whether the node accepted it.
+    async fn submit(&self, call: &str, fields: Vec<Value>) -> bool {
+        let mut tx = subxt::dynamic::tx(PALLET, call, fields);
+        if self.proxied {
+            tx = subxt::dynamic::tx(
+                "Proxy",
+                "proxy",
+                vec![
+                    Value::unnamed_variant("Id", [Value::from_bytes(self.provider.0)]),
+                    Value::unnamed_variant("None", []),
+                    tx.into_value(),
+                ],
+            );
+        }
+
+        match self
+            .api
+            .tx()
+            .sign_and_submit_default(&tx, &self.signer)
+            .await
+        {
+            Ok(_) => true,
+            Err(e) => {
+                log::warn!("cannot submit {call}: {e}");
+                false
+            }
+        }
+    }
+}
+
+fn usage_value(report: &UsageReport) -> Value {
+    Value::named_composite([
+        ("cpu_seconds", Value::u128(report.cpu_seconds.into())),
+        ("egress_gb", Value::u128(report.egress_gb.into())),
+        (
+            "storage_gb_hours",
+            Value::u128(report.storage_gb_hours.into()),
+        ),
+    ])
+}
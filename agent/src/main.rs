@@ -0,0 +1,59 @@
+//! Kumulus provider agent
+//!
+//! Follows finalized blocks of a Kollectyve node, provisions the workloads of the rentals of
+//! its provider account and reports heartbeats and usage back to the chain.
+
+mod agent;
+mod backend;
+mod chain;
+
+use clap::Parser;
+use subxt::utils::AccountId32;
+
+#[derive(Debug, Parser)]
+#[command(version, about)]
+struct Cli {
+    /// WebSocket endpoint of a Kollectyve node.
+    #[arg(long, default_value = "ws://127.0.0.1:9944")]
+    url: String,
+
+    /// Secret URI of the provider account, e.g. `//Alice`.
+    #[arg(long)]
+    suri: String,
+
+    /// Address of the provider account when `--suri` is one of its `KumulusProvider` proxies.
+    #[arg(long)]
+    proxied: Option<AccountId32>,
+
+    /// Program started for each workload by the local backend. The manifest is passed in
+    /// `KUMULUS_*` environment variables.
+    #[arg(long, default_value = "sleep")]
+    program: String,
+
+    /// Arguments of the workload program.
+    #[arg(long, default_values_t = ["infinity".to_string()])]
+    args: Vec<String>,
+
+    /// Blocks between two heartbeats.
+    #[arg(long, default_value_t = 10, value_parser = clap::value_parser!(u32).range(1..))]
+    heartbeat_interval: u32,
+
+    /// Blocks between two usage reports of every workload.
+    #[arg(long, default_value_t = 100, value_parser = clap::value_parser!(u32).range(1..))]
+    usage_interval: u32,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    env_logger::init();
+
+    let cli = Cli::parse();
+
+    let signer = chain::keypair(&cli.suri)?;
+    let chain = chain::Chain::connect(&cli.url, signer, cli.proxied).await?;
+    let mut agent = agent::Agent::new(backend::LocalBackend::new(cli.program, cli.args));
+
+    chain
+        .follow(&mut agent, cli.heartbeat_interval, cli.usage_interval)
+        .await
+}
@@ -77,6 +77,11 @@ pub struct Cli {
     #[arg(long)]
     pub no_hardware_benchmarks: bool,
 
+    /// Allow the offchain workers to make HTTP requests, e.g. the health probes of the
+    /// resources of the providers whose key is in the keystore.
+    #[arg(long)]
+    pub enable_offchain_http: bool,
+
     /// Relay chain arguments
     #[arg(raw = true)]
     pub relay_chain_args: Vec<String>,
@@ -270,6 +270,7 @@ pub fn run() -> Result<()> {
                     collator_options,
                     id,
                     hwbench,
+                    cli.enable_offchain_http,
                 )
                 .await
                 .map(|r| r.0)
@@ -249,6 +249,7 @@ pub async fn start_parachain_node(
     collator_options: CollatorOptions,
     para_id: ParaId,
     hwbench: Option<sc_sysinfo::HwBench>,
+    enable_offchain_http: bool,
 ) -> sc_service::error::Result<(TaskManager, Arc<ParachainClient>)> {
     let parachain_config = prepare_node_config(parachain_config);
 
@@ -310,7 +311,8 @@ pub async fn start_parachain_node(
                 )),
                 network_provider: Arc::new(network.clone()),
                 is_validator: parachain_config.role.is_authority(),
-                enable_http_requests: false,
+                // Health probes of the kumulus offchain worker
+                enable_http_requests: enable_offchain_http,
                 custom_extensions: move |_| vec![],
             })?;
         task_manager.spawn_handle().spawn(
@@ -1,50 +0,0 @@
-use crate as kumulus_bootstrap;
-use frame::{prelude::*, runtime::prelude::*, testing_prelude::*};
-
-type Block = frame_system::mocking::MockBlock<Test>;
-
-// Configure a mock runtime to test the pallet.
-#[frame_construct_runtime]
-mod runtime {
-    #[runtime::runtime]
-    #[runtime::derive(
-        RuntimeCall,
-        RuntimeEvent,
-        RuntimeError,
-        RuntimeOrigin,
-        RuntimeFreezeReason,
-        RuntimeHoldReason,
-        RuntimeSlashReason,
-        RuntimeLockId,
-        RuntimeTask
-    )]
-    pub struct Test;
-
-    #[runtime::pallet_index(0)]
-    pub type System = frame_system;
-
-    #[runtime::pallet_index(1)]
-    pub type KumulusBootstrap = kumulus_bootstrap;
-}
-
-// System pallet configuration
-#[derive_impl(frame_system::config_preludes::TestDefaultConfig)]
-impl frame_system::Config for Test {
-    type Block = Block;
-}
-
-// Custom pallet configuration
-parameter_types! {}
-
-impl kumulus_bootstrap::Config for Test {
-    type RuntimeEvent = RuntimeEvent;
-    type WeightInfo = kumulus_bootstrap::weights::SubstrateWeight<Test>;
-}
-
-// Test externalities initialization
-pub fn new_test_ext() -> TestExternalities {
-    frame_system::GenesisConfig::<Test>::default()
-        .build_storage()
-        .unwrap()
-        .into()
-}
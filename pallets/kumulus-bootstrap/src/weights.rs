@@ -1,67 +0,0 @@
-
-//! Autogenerated weights for `kumulus_bootstrap`
-//!
-//! THIS FILE WAS AUTO-GENERATED USING THE SUBSTRATE BENCHMARK CLI VERSION 47.0.0
-//! DATE: 2025-05-10, STEPS: `50`, REPEAT: `20`, LOW RANGE: `[]`, HIGH RANGE: `[]`
-//! WORST CASE MAP SIZE: `1000000`
-//! HOSTNAME: `GDAUST`, CPU: `12th Gen Intel(R) Core(TM) i5-1235U`
-//! WASM-EXECUTION: `Compiled`, CHAIN: `None`, DB CACHE: `1024`
-
-// Executed Command:
-// frame-omni-bencher
-// v1
-// benchmark
-// pallet
-// --runtime
-// target/release/wbuild/parachain-template-runtime/parachain_template_runtime.compact.compressed.wasm
-// --pallet
-// kumulus_bootstrap
-// --extrinsic
-// 
-// --template
-// ./pallets/benchmarking/frame-umbrella-weight-template.hbs
-// --output
-// ./pallets/kumulus-bootstrap/src/weights.rs
-
-#![cfg_attr(rustfmt, rustfmt_skip)]
-#![allow(unused_parens)]
-#![allow(unused_imports)]
-#![allow(missing_docs)]
-
-use frame::weights_prelude::*;
-
-/// Weight functions needed for `kumulus_bootstrap`.
-pub trait WeightInfo {
-	fn register_bootstrapper() -> Weight;
-}
-
-/// Weights for `kumulus_bootstrap` using the Substrate node and recommended hardware.
-pub struct SubstrateWeight<T>(PhantomData<T>);
-impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
-	/// Storage: `KumulusBootstrap::Bootstrappers` (r:1 w:1)
-	/// Proof: `KumulusBootstrap::Bootstrappers` (`max_values`: None, `max_size`: Some(49), added: 2524, mode: `MaxEncodedLen`)
-	fn register_bootstrapper() -> Weight {
-		// Proof Size summary in bytes:
-		//  Measured:  `6`
-		//  Estimated: `3514`
-		// Minimum execution time: 13_494_000 picoseconds.
-		Weight::from_parts(16_829_000, 3514)
-			.saturating_add(T::DbWeight::get().reads(1_u64))
-			.saturating_add(T::DbWeight::get().writes(1_u64))
-	}
-}
-
-// For backwards compatibility and tests.
-impl WeightInfo for () {
-	/// Storage: `KumulusBootstrap::Bootstrappers` (r:1 w:1)
-	/// Proof: `KumulusBootstrap::Bootstrappers` (`max_values`: None, `max_size`: Some(49), added: 2524, mode: `MaxEncodedLen`)
-	fn register_bootstrapper() -> Weight {
-		// Proof Size summary in bytes:
-		//  Measured:  `6`
-		//  Estimated: `3514`
-		// Minimum execution time: 13_494_000 picoseconds.
-		Weight::from_parts(16_829_000, 3514)
-			.saturating_add(RocksDbWeight::get().reads(1_u64))
-			.saturating_add(RocksDbWeight::get().writes(1_u64))
-	}
-}
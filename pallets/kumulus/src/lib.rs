@@ -1,37 +1,119 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
+extern crate alloc;
+
+pub mod migrations;
+pub mod offchain;
+pub mod runtime_api;
 mod types;
 
+pub use offchain::crypto;
 pub use pallet::*;
+pub use types::*;
+
+#[cfg(test)]
+mod mock;
 
+#[cfg(test)]
+mod tests;
+
+// Retired errors, e.g. `BlockNumberOverflow`, are deprecated in place rather than removed,
+// keeping the indices of the others
+#[allow(deprecated)]
 #[frame_support::pallet]
 pub mod pallet {
     use super::*;
+    use alloc::vec::Vec;
     use frame_support::pallet_prelude::*;
     use frame_support::{
-        sp_runtime::traits::AccountIdConversion,
-        traits::{Currency, ExistenceRequirement, Get},
+        sp_runtime::{
+            traits::{AccountIdConversion, Hash, One, Saturating, TrailingZeroInput, Zero},
+            Perbill, SaturatedConversion,
+        },
+        traits::{
+            BalanceStatus, Currency, ExistenceRequirement, Get, Randomness, ReservableCurrency,
+        },
         PalletId,
     };
-    use frame_system::pallet_prelude::*;
-    use sp_std::convert::TryInto;
-
-    use crate::types::*;
+    use frame_system::{
+        offchain::{AppCrypto, CreateSignedTransaction},
+        pallet_prelude::*,
+    };
 
     pub type BalanceOf<T> =
         <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
 
+    pub type ProviderInfoOf<T> =
+        ProviderInfo<<T as frame_system::Config>::AccountId, BlockNumberFor<T>>;
+
+    pub type RentalOf<T> = Rental<<T as frame_system::Config>::AccountId, BlockNumberFor<T>>;
+
+    pub type BillingPeriodOf<T> = BillingPeriod<BlockNumberFor<T>>;
+
+    pub type BootstrapperInfoOf<T> =
+        BootstrapperInfo<<T as frame_system::Config>::AccountId, BlockNumberFor<T>>;
+
+    pub type UsageRecordOf<T> = UsageRecord<BlockNumberFor<T>, BalanceOf<T>>;
+
+    pub type HeldUsageOf<T> =
+        HeldUsage<<T as frame_system::Config>::AccountId, BlockNumberFor<T>, BalanceOf<T>>;
+
+    pub type EndedRentalOf<T> =
+        EndedRental<<T as frame_system::Config>::AccountId, BlockNumberFor<T>>;
+
+    pub type DisputeOf<T> = Dispute<<T as frame_system::Config>::AccountId, BlockNumberFor<T>>;
+
+    pub type ComputeOrderOf<T> =
+        ComputeOrder<<T as frame_system::Config>::AccountId, BalanceOf<T>, BlockNumberFor<T>>;
+
+    pub type AuctionOf<T> = Auction<BalanceOf<T>, BlockNumberFor<T>>;
+
+    pub type SealedBidOf<T> = SealedBid<<T as frame_system::Config>::Hash, BalanceOf<T>>;
+
+    pub type CommitmentOf<T> =
+        Commitment<<T as frame_system::Config>::AccountId, BlockNumberFor<T>>;
+
+    pub type DeploymentManifestOf<T> = DeploymentManifest<<T as frame_system::Config>::Hash>;
+
+    pub type DeploymentOf<T> = Deployment<<T as frame_system::Config>::Hash, BlockNumberFor<T>>;
+
+    pub type AccessBundleOf<T> = AccessBundle<BlockNumberFor<T>>;
+
+    pub type WatcherOf<T> = Watcher<BalanceOf<T>>;
+
+    pub type OrganisationOf<T> = Organisation<<T as frame_system::Config>::AccountId, BalanceOf<T>>;
+
+    pub type BudgetOf<T> = Budget<BalanceOf<T>, BlockNumberFor<T>>;
+
+    pub type BudgetUsageOf<T> = BudgetUsage<BalanceOf<T>, BlockNumberFor<T>>;
+
+    pub type HostOf<T> = Host<<T as frame_system::Config>::AccountId, BalanceOf<T>>;
+
+    pub type HardwareAttestationOf<T> = HardwareAttestation<
+        <T as frame_system::Config>::AccountId,
+        <T as frame_system::Config>::Hash,
+        BlockNumberFor<T>,
+    >;
+
+    // Subject of the randomness drawn for the watcher assignments
+    const WATCHER_SUBJECT: &[u8] = b"kumulus/watchers";
+
+    /// The in-code storage version, see [`crate::migrations`]
+    const STORAGE_VERSION: StorageVersion = StorageVersion::new(0);
+
     #[pallet::pallet]
+    #[pallet::storage_version(STORAGE_VERSION)]
     pub struct Pallet<T>(_);
 
     #[pallet::config]
-    pub trait Config: frame_system::Config {
+    pub trait Config: frame_system::Config + CreateSignedTransaction<Call<Self>> {
         type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
 
-        type Currency: Currency<Self::AccountId>;
+        type Currency: ReservableCurrency<Self::AccountId>;
 
+        /// Length of a week in blocks, billing periods are expressed from it
         #[pallet::constant]
-        type BlocksPerWeek: Get<u32>;
+        type BlocksPerWeek: Get<BlockNumberFor<Self>>;
 
         /// The payment escrow's pallet id
         #[pallet::constant]
@@ -40,11 +122,153 @@ pub mod pallet {
         /// Minimum deposit required when renting a resource
         #[pallet::constant]
         type MinimumDeposit: Get<BalanceOf<Self>>;
+
+        /// Origin allowed to add and remove bootstrappers (root or governance)
+        type AdminOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+        /// Upper bound for the resource cap of a single bootstrapper
+        #[pallet::constant]
+        type MaxBootstrappedResources: Get<u32>;
+
+        /// Maximum number of bootstrappers expiring in the same block
+        #[pallet::constant]
+        type MaxExpiringBootstrappers: Get<u32>;
+
+        /// Maximum number of self-registration requests awaiting approval
+        #[pallet::constant]
+        type MaxPendingBootstrappers: Get<u32>;
+
+        /// Maximum number of unsettled usage reports per rental
+        #[pallet::constant]
+        type MaxPendingUsageReports: Get<u32>;
+
+        /// Blocks during which a renter can dispute a usage report
+        #[pallet::constant]
+        type UsageDisputeWindow: Get<BlockNumberFor<Self>>;
+
+        /// Upper bound for each counter of a single usage report
+        #[pallet::constant]
+        type MaxUsagePerReport: Get<UsageReport>;
+
+        /// Amount reserved from a provider on registration, slashable by disputes
+        #[pallet::constant]
+        type ProviderBond: Get<BalanceOf<Self>>;
+
+        /// Blocks after the end of a rental during which the renter can still open a dispute
+        #[pallet::constant]
+        type DisputeWindow: Get<BlockNumberFor<Self>>;
+
+        /// Maximum number of evidence hashes each party can submit for a dispute
+        #[pallet::constant]
+        type MaxDisputeEvidence: Get<u32>;
+
+        /// Origin resolving disputes (council, juror set, ...)
+        type ArbiterOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+        /// Maximum number of compute orders waiting to be matched
+        #[pallet::constant]
+        type MaxOpenOrders: Get<u32>;
+
+        /// Amount reserved from the renter of an open order
+        #[pallet::constant]
+        type OrderDeposit: Get<BalanceOf<Self>>;
+
+        /// Blocks an order stays open before it is dropped unmatched
+        #[pallet::constant]
+        type OrderLifetime: Get<BlockNumberFor<Self>>;
+
+        /// Amount reserved from each bidder until the auction is settled, forfeited to the
+        /// provider if the bid is never revealed
+        #[pallet::constant]
+        type AuctionBidDeposit: Get<BalanceOf<Self>>;
+
+        /// Maximum number of sealed bids in a single auction
+        #[pallet::constant]
+        type MaxAuctionBids: Get<u32>;
+
+        /// Maximum number of auctions settled in the same block
+        #[pallet::constant]
+        type MaxEndingAuctions: Get<u32>;
+
+        /// Share of the remaining commitment paid to the provider when it is terminated early
+        #[pallet::constant]
+        type EarlyTerminationPenalty: Get<Perbill>;
+
+        /// Provider keys used by the offchain worker to sign health reports
+        type AuthorityId: AppCrypto<Self::Public, Self::Signature>;
+
+        /// Blocks between two health probes of the offchain worker
+        #[pallet::constant]
+        type OffchainHeartbeatInterval: Get<BlockNumberFor<Self>>;
+
+        /// Maximum number of resources in a health report
+        #[pallet::constant]
+        type MaxHeartbeatResources: Get<u32>;
+
+        /// Source of the watcher assignments, the relay chain randomness on a parachain. An
+        /// output known before the block drawing the assignments postpones the epoch.
+        type Randomness: Randomness<Self::Hash, BlockNumberFor<Self>>;
+
+        /// Amount reserved from a watcher, slashable by contradicted attestations
+        #[pallet::constant]
+        type WatcherStake: Get<BalanceOf<Self>>;
+
+        /// Blocks per watcher epoch, attestations are aggregated per epoch
+        #[pallet::constant]
+        type WatcherEpoch: Get<BlockNumberFor<Self>>;
+
+        /// Number of watchers assigned to each rented resource
+        #[pallet::constant]
+        type WatchersPerResource: Get<u32>;
+
+        /// Minimum number of attestations for a liveness verdict
+        #[pallet::constant]
+        type WatcherQuorum: Get<u32>;
+
+        /// Number of watchers agreeing on a benchmark to attest hardware without an auditor
+        #[pallet::constant]
+        type HardwareQuorum: Get<u32>;
+
+        /// Maximum number of watchers
+        #[pallet::constant]
+        type MaxWatchers: Get<u32>;
+
+        /// Paid from the watcher pot to each watcher agreeing with the verdict
+        #[pallet::constant]
+        type WatcherReward: Get<BalanceOf<Self>>;
+
+        /// Share of the stake moved to the watcher pot for an attestation against the verdict
+        #[pallet::constant]
+        type WatcherSlash: Get<Perbill>;
+
+        /// Share of the payments to providers moved to the watcher pot
+        #[pallet::constant]
+        type WatcherFee: Get<Perbill>;
+
+        /// On-chain identities backing the verified provider status
+        type Identity: ProviderIdentity<Self::AccountId>;
+
+        /// Blocks between a deregistration request and the release of the provider bond, at
+        /// least `DisputeWindow` so that the last rentals can still be disputed
+        #[pallet::constant]
+        type ProviderUnbondingPeriod: Get<BlockNumberFor<Self>>;
+
+        /// Maximum number of provider suspensions expiring in the same block
+        #[pallet::constant]
+        type MaxExpiringSuspensions: Get<u32>;
+
+        /// Maximum number of members of an organisation
+        #[pallet::constant]
+        type MaxOrganisationMembers: Get<u32>;
+
+        /// Amount reserved from the creator of an organisation
+        #[pallet::constant]
+        type OrganisationDeposit: Get<BalanceOf<Self>>;
     }
 
     #[pallet::storage]
     pub(super) type Providers<T: Config> =
-        StorageMap<_, Blake2_128Concat, T::AccountId, ProviderInfo<T::AccountId>, OptionQuery>;
+        StorageMap<_, Blake2_128Concat, T::AccountId, ProviderInfoOf<T>, OptionQuery>;
 
     #[pallet::storage]
     pub(super) type Resources<T: Config> =
@@ -55,7 +279,7 @@ pub mod pallet {
 
     #[pallet::storage]
     pub(super) type Rentals<T: Config> =
-        StorageMap<_, Blake2_128Concat, ResourceId, Rental<T::AccountId>, OptionQuery>;
+        StorageMap<_, Blake2_128Concat, ResourceId, RentalOf<T>, OptionQuery>;
 
     // Track category counts per provider
     #[pallet::storage]
@@ -69,12 +293,19 @@ pub mod pallet {
         ValueQuery,
     >;
 
-    //TODO: Add Bootstrapers : resources that are almost always available and for devnet phase
+    // Bootstrappers: resources that are almost always available, for the devnet phase
 
     #[pallet::storage]
     pub(super) type Bootstrappers<T: Config> =
-        StorageMap<_, Blake2_128Concat, T::AccountId, BootstrapperInfo<T::AccountId>, OptionQuery>;
+        StorageMap<_, Blake2_128Concat, T::AccountId, BootstrapperInfoOf<T>, OptionQuery>;
+
+    // Self-registration requests awaiting governance approval, with the request block
+    #[pallet::storage]
+    pub(super) type PendingBootstrappers<T: Config> =
+        CountedStorageMap<_, Blake2_128Concat, T::AccountId, BlockNumberFor<T>, OptionQuery>;
 
+    // Bootstrapped resources live in `Resources` like any other resource, this only indexes
+    // them by bootstrapper so they can be cleaned up on removal or expiry
     #[pallet::storage]
     pub(super) type BootstrappedResources<T: Config> = StorageDoubleMap<
         _,
@@ -82,468 +313,5091 @@ pub mod pallet {
         T::AccountId, // Bootstrapper account
         Blake2_128Concat,
         ResourceId, // Resource ID
-        Resource<T::AccountId>,
+        (),
         OptionQuery,
     >;
 
+    // Bootstrappers to remove at a given block
     #[pallet::storage]
-    pub(super) type NextBootstrappedResourceId<T: Config> = StorageValue<_, ResourceId, ValueQuery>;
+    pub(super) type BootstrapperExpiries<T: Config> = StorageMap<
+        _,
+        Twox64Concat,
+        BlockNumberFor<T>,
+        BoundedVec<T::AccountId, T::MaxExpiringBootstrappers>,
+        ValueQuery,
+    >;
 
     #[pallet::storage]
     pub type Deposits<T: Config> =
         StorageMap<_, Blake2_128Concat, ResourceId, BalanceOf<T>, OptionQuery>;
 
-    #[pallet::event]
-    #[pallet::generate_deposit(pub(super) fn deposit_event)]
-    pub enum Event<T: Config> {
-        ProviderRegistered {
-            who: T::AccountId,
-        },
-        ProviderStatusChanged {
-            who: T::AccountId,
-            status: ProviderStatus,
-        },
-        ResourceRegistered {
-            resource_id: u32,
-            provider: T::AccountId,
-        },
-        ResourceRented {
-            resource_id: u32,
-            renter: T::AccountId,
-        },
-        RentalCancelled {
-            resource_id: u32,
-            renter: T::AccountId,
-        },
-        PaymentClaimed {
-            resource_id: u32,
-            provider: T::AccountId,
-            amount: BalanceOf<T>,
-        },
-        BootstrapperAdded {
-            who: T::AccountId,
-            bootstrap_type: BootstrapperType,
-        },
+    // Usage prices set by providers
+    #[pallet::storage]
+    pub(super) type RateCards<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, RateCard<BalanceOf<T>>, OptionQuery>;
 
-        BootstrapperRemoved {
-            who: T::AccountId,
-        },
-        BootstrappedResourceAdded {
-            bootstrapped_id: ResourceId,
-            bootstrapper: T::AccountId,
-        },
-    }
+    // Rate card of the provider when the rental started, used to price its usage
+    #[pallet::storage]
+    pub(super) type RentalRateCards<T: Config> =
+        StorageMap<_, Blake2_128Concat, ResourceId, RateCard<BalanceOf<T>>, OptionQuery>;
 
-    #[pallet::error]
-    pub enum Error<T> {
-        ProviderNameTooLong,
-        ProviderWebsiteTooLong,
-        ProviderAlreadyRegistered,
-        ProviderNotRegistered,
-        ResourceNotFound,
-        ResourceNotAvailable,
-        NotResourceOwner,
-        BlockNumberOverflow,
-        NotRenter,
-        RentalNotFound,
-        NoPaymentDue,
-        InsufficientDeposit,
-        ConversionError,
-        NotBootstrapper,
-        BootstrapperExpired,
-        UptimeTooLow,
-        ResourceIdOverflow,
-        BootstrapperAlreadyRegistered,
-    }
+    // Price per week agreed when the rental was matched with an ask, replaces the category price
+    #[pallet::storage]
+    pub(super) type MatchedPrices<T: Config> =
+        StorageMap<_, Blake2_128Concat, ResourceId, BalanceOf<T>, OptionQuery>;
 
-    #[pallet::call]
-    impl<T: Config> Pallet<T> {
-        #[pallet::call_index(0)]
-        #[pallet::weight(Weight::default())]
-        pub fn register_provider(
-            origin: OriginFor<T>,
-            name: ProviderName,
-            website: Option<Website>,
-        ) -> DispatchResult {
-            let who = ensure_signed(origin)?;
+    // Reserved-capacity commitments, the resource can't be rented by others until they end
+    #[pallet::storage]
+    pub(super) type Commitments<T: Config> =
+        StorageMap<_, Blake2_128Concat, ResourceId, CommitmentOf<T>, OptionQuery>;
 
-            ensure!(
-                !Providers::<T>::contains_key(&who),
-                Error::<T>::ProviderAlreadyRegistered
-            );
+    // Workload the renter wants to run on a rented resource
+    #[pallet::storage]
+    pub(super) type Deployments<T: Config> =
+        StorageMap<_, Blake2_128Concat, ResourceId, DeploymentOf<T>, OptionQuery>;
 
-            ensure!(name.len() <= 50, Error::<T>::ProviderNameTooLong);
+    // Public key of the renter used by the provider to encrypt access credentials
+    #[pallet::storage]
+    pub(super) type AccessKeys<T: Config> =
+        StorageMap<_, Blake2_128Concat, ResourceId, AccessKey, OptionQuery>;
 
-            ensure!(
-                website.as_ref().map_or(true, |w| w.len() <= 100),
-                Error::<T>::ProviderWebsiteTooLong
-            );
+    // Encrypted access credentials posted by the provider
+    #[pallet::storage]
+    pub(super) type AccessBundles<T: Config> =
+        StorageMap<_, Blake2_128Concat, ResourceId, AccessBundleOf<T>, OptionQuery>;
 
-            let current_block = frame_system::Pallet::<T>::block_number();
+    // Usage reports of a rental waiting to be settled
+    #[pallet::storage]
+    pub(super) type UsageReports<T: Config> = StorageMap<
+        _,
+        Blake2_128Concat,
+        ResourceId,
+        BoundedVec<UsageRecordOf<T>, T::MaxPendingUsageReports>,
+        ValueQuery,
+    >;
 
-            let current_block_u32: u32 = current_block
-                .try_into()
-                .map_err(|_| Error::<T>::BlockNumberOverflow)?;
+    #[pallet::storage]
+    pub(super) type NextUsageReportId<T: Config> = StorageValue<_, UsageReportId, ValueQuery>;
 
-            // Create provider info
-            let provider_info = ProviderInfo {
-                account: who.clone(),
-                name: name.clone(),
-                website,
-                total_resources: 0,
-                reputation_score: Default::default(),
-                registration_block: current_block_u32,
-                last_updated: current_block_u32,
-                status: ProviderStatus::Inactive,
-            };
+    // Usage of an ended rental held in escrow until its dispute window is over
+    #[pallet::storage]
+    pub(super) type HeldUsages<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        ResourceId,
+        Twox64Concat,
+        UsageReportId,
+        HeldUsageOf<T>,
+        OptionQuery,
+    >;
 
-            Providers::<T>::insert(&who, provider_info);
+    // Usage a renter couldn't pay out of its deposit, owed to the provider
+    #[pallet::storage]
+    pub(super) type UsageDebts<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        T::AccountId,
+        Blake2_128Concat,
+        T::AccountId,
+        BalanceOf<T>,
+        ValueQuery,
+    >;
 
-            Self::deposit_event(Event::ProviderRegistered { who: who.clone() });
+    // Last block a provider agent reported in
+    #[pallet::storage]
+    pub type Heartbeats<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, BlockNumberFor<T>, OptionQuery>;
 
-            Ok(())
-        }
+    // Last block a resource was reported healthy by its provider
+    #[pallet::storage]
+    pub type ResourceHeartbeats<T: Config> =
+        StorageMap<_, Blake2_128Concat, ResourceId, BlockNumberFor<T>, OptionQuery>;
 
-        #[pallet::call_index(1)]
-        #[pallet::weight(Weight::default())]
-        pub fn update_provider_status(
-            origin: OriginFor<T>,
-            status: ProviderStatus,
-        ) -> DispatchResult {
-            let who = ensure_signed(origin)?;
+    // Staked watchers, kept after leaving the set until their stake is withdrawn
+    #[pallet::storage]
+    pub(super) type Watchers<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, WatcherOf<T>, OptionQuery>;
 
-            Providers::<T>::try_mutate(&who, |maybe_provider| -> DispatchResult {
-                let provider = maybe_provider
-                    .as_mut()
-                    .ok_or(Error::<T>::ProviderNotRegistered)?;
+    // Watchers eligible for the next epochs
+    #[pallet::storage]
+    pub(super) type WatcherSet<T: Config> =
+        StorageValue<_, BoundedVec<T::AccountId, T::MaxWatchers>, ValueQuery>;
 
-                provider.status = status.clone();
+    // Current epoch with its random seed and watchers, assignments are drawn from them
+    #[pallet::storage]
+    pub type WatchEpoch<T: Config> = StorageValue<
+        _,
+        (
+            EpochIndex,
+            T::Hash,
+            BoundedVec<T::AccountId, T::MaxWatchers>,
+        ),
+        OptionQuery,
+    >;
 
-                let current_block = frame_system::Pallet::<T>::block_number();
+    // Liveness attested by each assigned watcher, until the epoch is finalized
+    #[pallet::storage]
+    pub(super) type Attestations<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        (EpochIndex, ResourceId),
+        Blake2_128Concat,
+        T::AccountId,
+        bool,
+        OptionQuery,
+    >;
 
-                let current_block_u32: u32 = current_block
-                    .try_into()
-                    .map_err(|_| Error::<T>::BlockNumberOverflow)?;
+    #[pallet::storage]
+    pub(super) type LivenessTallies<T: Config> = StorageDoubleMap<
+        _,
+        Twox64Concat,
+        EpochIndex,
+        Blake2_128Concat,
+        ResourceId,
+        LivenessTally,
+        ValueQuery,
+    >;
 
-                provider.last_updated = current_block_u32;
+    // Oldest epoch that may still have tallies, the ones nobody finalized are finalized on idle
+    #[pallet::storage]
+    pub(super) type OldestLivenessEpoch<T: Config> = StorageValue<_, EpochIndex, ValueQuery>;
 
-                Self::deposit_event(Event::ProviderStatusChanged {
+    // Last liveness verdict of the watchers for a resource
+    #[pallet::storage]
+    pub type Liveness<T: Config> =
+        StorageMap<_, Blake2_128Concat, ResourceId, (EpochIndex, bool), OptionQuery>;
+
+    // Available resources by normalized country code, for region filtered discovery
+    #[pallet::storage]
+    pub type AvailableByCountry<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        IsoCountry,
+        Blake2_128Concat,
+        ResourceId,
+        (),
+        OptionQuery,
+    >;
+
+    // Auditors appointed by governance to verify hardware, besides the active watchers
+    #[pallet::storage]
+    pub(super) type Auditors<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, (), OptionQuery>;
+
+    // Latest hardware attestation of a resource, unverified without one
+    #[pallet::storage]
+    pub type HardwareAttestations<T: Config> =
+        StorageMap<_, Blake2_128Concat, ResourceId, HardwareAttestationOf<T>, OptionQuery>;
+
+    // Latest hardware attestation of a host, covering the slices rented from it
+    #[pallet::storage]
+    pub type HostAttestations<T: Config> =
+        StorageMap<_, Blake2_128Concat, HostId, HardwareAttestationOf<T>, OptionQuery>;
+
+    // Verdicts of the watchers benchmarking some hardware, attested once they reach a quorum
+    #[pallet::storage]
+    pub(super) type HardwareVotes<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        AttestationTarget,
+        Blake2_128Concat,
+        T::AccountId,
+        bool,
+        OptionQuery,
+    >;
+
+    // Whether only verified resources can be rented
+    #[pallet::storage]
+    pub type VerificationRequired<T: Config> = StorageValue<_, bool, ValueQuery>;
+
+    // Resources of each provider or bootstrapper
+    #[pallet::storage]
+    pub(super) type ProviderResources<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        T::AccountId,
+        Blake2_128Concat,
+        ResourceId,
+        (),
+        OptionQuery,
+    >;
+
+    #[pallet::storage]
+    pub(super) type ProviderHosts<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        T::AccountId,
+        Blake2_128Concat,
+        HostId,
+        (),
+        OptionQuery,
+    >;
+
+    // Block from which a deregistering provider can get its bond back
+    #[pallet::storage]
+    pub(super) type ProviderUnbonding<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, BlockNumberFor<T>, OptionQuery>;
+
+    // Providers suspended by governance
+    #[pallet::storage]
+    pub type Suspensions<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, Suspension<BlockNumberFor<T>>, OptionQuery>;
+
+    // Suspensions to lift at a given block
+    #[pallet::storage]
+    pub(super) type SuspensionExpiries<T: Config> = StorageMap<
+        _,
+        Twox64Concat,
+        BlockNumberFor<T>,
+        BoundedVec<T::AccountId, T::MaxExpiringSuspensions>,
+        ValueQuery,
+    >;
+
+    #[pallet::storage]
+    pub(super) type NextOrganisationId<T: Config> = StorageValue<_, OrganisationId, ValueQuery>;
+
+    #[pallet::storage]
+    pub type Organisations<T: Config> =
+        StorageMap<_, Blake2_128Concat, OrganisationId, OrganisationOf<T>, OptionQuery>;
+
+    // Role of each member of an organisation
+    #[pallet::storage]
+    pub type OrganisationMembers<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        OrganisationId,
+        Blake2_128Concat,
+        T::AccountId,
+        OrgRole,
+        OptionQuery,
+    >;
+
+    // Organisation owning a treasury account
+    #[pallet::storage]
+    pub(super) type OrganisationAccounts<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, OrganisationId, OptionQuery>;
+
+    // Spending caps of renters, the caps of organisations being set on their treasury
+    #[pallet::storage]
+    pub type Budgets<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, BudgetOf<T>, OptionQuery>;
+
+    // Spent in the current budget period
+    #[pallet::storage]
+    pub type BudgetUsages<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, BudgetUsageOf<T>, OptionQuery>;
+
+    // Account setting the budget of a renter in its place, so the renter can't lift its own cap
+    #[pallet::storage]
+    pub type BudgetControllers<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, T::AccountId, OptionQuery>;
+
+    // Providers that linked their on-chain identity, since the given block. The badge is only
+    // shown while the identity is judged, see `is_verified_provider`.
+    #[pallet::storage]
+    pub type VerifiedProviders<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, BlockNumberFor<T>, OptionQuery>;
+
+    // Bond reserved from each provider
+    #[pallet::storage]
+    pub(super) type ProviderBonds<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, BalanceOf<T>, ValueQuery>;
+
+    // Last ended rental of a resource, disputable during `DisputeWindow`
+    #[pallet::storage]
+    pub(super) type EndedRentals<T: Config> =
+        StorageMap<_, Blake2_128Concat, ResourceId, EndedRentalOf<T>, OptionQuery>;
+
+    #[pallet::storage]
+    pub(super) type Disputes<T: Config> =
+        StorageMap<_, Blake2_128Concat, DisputeId, DisputeOf<T>, OptionQuery>;
+
+    #[pallet::storage]
+    pub(super) type NextDisputeId<T: Config> = StorageValue<_, DisputeId, ValueQuery>;
+
+    // Open dispute of the ongoing rental of a resource, its escrow is frozen until resolved.
+    // Disputes of ended rentals don't freeze anything.
+    #[pallet::storage]
+    pub(super) type ResourceDisputes<T: Config> =
+        StorageMap<_, Blake2_128Concat, ResourceId, DisputeId, OptionQuery>;
+
+    // Number of open disputes against a provider, its bond can't be released meanwhile
+    #[pallet::storage]
+    pub(super) type ProviderDisputes<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, u32, ValueQuery>;
+
+    #[pallet::storage]
+    pub(super) type Orders<T: Config> =
+        StorageMap<_, Blake2_128Concat, OrderId, ComputeOrderOf<T>, OptionQuery>;
+
+    #[pallet::storage]
+    pub(super) type NextOrderId<T: Config> = StorageValue<_, OrderId, ValueQuery>;
+
+    // Orders waiting to be matched, oldest first
+    #[pallet::storage]
+    pub(super) type OrderQueue<T: Config> =
+        StorageValue<_, BoundedVec<OrderId, T::MaxOpenOrders>, ValueQuery>;
+
+    // Price per week asked by providers for their available resources
+    #[pallet::storage]
+    pub(super) type Asks<T: Config> =
+        CountedStorageMap<_, Blake2_128Concat, ResourceId, BalanceOf<T>, OptionQuery>;
+
+    // Asks indexed by the category of their resource, orders only scan the fitting categories
+    #[pallet::storage]
+    pub(super) type CategoryAsks<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        ResourceCategory,
+        Twox64Concat,
+        ResourceId,
+        BalanceOf<T>,
+        OptionQuery,
+    >;
+
+    // Number of asks per category, categories without asks are removed
+    #[pallet::storage]
+    pub(super) type AskCategories<T: Config> =
+        StorageMap<_, Blake2_128Concat, ResourceCategory, u32, OptionQuery>;
+
+    // Sealed-bid auctions of GPU resources
+    #[pallet::storage]
+    pub(super) type Auctions<T: Config> =
+        StorageMap<_, Blake2_128Concat, ResourceId, AuctionOf<T>, OptionQuery>;
+
+    #[pallet::storage]
+    pub(super) type AuctionBids<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        ResourceId,
+        Blake2_128Concat,
+        T::AccountId,
+        SealedBidOf<T>,
+        OptionQuery,
+    >;
+
+    // Auctions to settle at the end of their reveal phase
+    #[pallet::storage]
+    pub(super) type AuctionEnds<T: Config> = StorageMap<
+        _,
+        Twox64Concat,
+        BlockNumberFor<T>,
+        BoundedVec<ResourceId, T::MaxEndingAuctions>,
+        ValueQuery,
+    >;
+
+    // Hosts whose capacity is rented in slices
+    #[pallet::storage]
+    pub(super) type Hosts<T: Config> =
+        StorageMap<_, Blake2_128Concat, HostId, HostOf<T>, OptionQuery>;
+
+    #[pallet::storage]
+    pub(super) type NextHostId<T: Config> = StorageValue<_, HostId, ValueQuery>;
+
+    // Rented slices, identified in the resource ids so that their rentals are kept alike.
+    // A slice only exists for the time of its rental and is never registered as a resource.
+    #[pallet::storage]
+    pub(super) type Allocations<T: Config> =
+        StorageMap<_, Blake2_128Concat, ResourceId, (HostId, HostCapacity), OptionQuery>;
+
+    // Slices rented from each host
+    #[pallet::storage]
+    pub(super) type HostSlices<T: Config> =
+        StorageDoubleMap<_, Blake2_128Concat, HostId, Twox64Concat, ResourceId, (), OptionQuery>;
+
+    #[pallet::storage]
+    pub(super) type DisputeEvidence<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        DisputeId,
+        Blake2_128Concat,
+        T::AccountId,
+        BoundedVec<T::Hash, T::MaxDisputeEvidence>,
+        ValueQuery,
+    >;
+
+    #[pallet::event]
+    #[pallet::generate_deposit(pub(super) fn deposit_event)]
+    pub enum Event<T: Config> {
+        ProviderRegistered {
+            who: T::AccountId,
+        },
+        ProviderStatusChanged {
+            who: T::AccountId,
+            status: ProviderStatus,
+        },
+        ProviderInfoUpdated {
+            who: T::AccountId,
+            name: ProviderName,
+            website: Option<Website>,
+        },
+        ProviderVerified {
+            who: T::AccountId,
+        },
+        ProviderVerificationRevoked {
+            who: T::AccountId,
+        },
+        // No new rentals, the bond is released from `unbonded_at`
+        ProviderDeregistering {
+            who: T::AccountId,
+            unbonded_at: BlockNumberFor<T>,
+            refunded_rentals: u32,
+        },
+        ProviderDeregistered {
+            who: T::AccountId,
+        },
+        ProviderSuspended {
+            who: T::AccountId,
+            reason: SanctionReason,
+            expires_at: Option<BlockNumberFor<T>>,
+            refunded_rentals: u32,
+        },
+        SuspensionLifted {
+            who: T::AccountId,
+        },
+        ProviderTerminated {
+            who: T::AccountId,
+            reason: SanctionReason,
+            refunded_rentals: u32,
+            forfeited: BalanceOf<T>,
+        },
+        // Whole deposit back from escrow, plus a compensation from the provider bond
+        RenterRefunded {
+            resource_id: ResourceId,
+            renter: T::AccountId,
+            deposit: BalanceOf<T>,
+            compensation: BalanceOf<T>,
+        },
+        ResourceRegistered {
+            resource_id: u32,
+            provider: T::AccountId,
+        },
+        ResourceRented {
+            resource_id: u32,
+            renter: T::AccountId,
+        },
+        RentalCancelled {
+            resource_id: u32,
+            renter: T::AccountId,
+        },
+        PaymentClaimed {
+            resource_id: u32,
+            provider: T::AccountId,
+            amount: BalanceOf<T>,
+        },
+        RentalToppedUp {
+            resource_id: ResourceId,
+            renter: T::AccountId,
+            amount: BalanceOf<T>,
+        },
+        // The rental deposit was used up
+        RentalExpired {
+            resource_id: ResourceId,
+            renter: T::AccountId,
+        },
+        RateCardSet {
+            provider: T::AccountId,
+            rate_card: RateCard<BalanceOf<T>>,
+        },
+        UsageReported {
+            resource_id: ResourceId,
+            report_id: UsageReportId,
+            report: UsageReport,
+            amount: BalanceOf<T>,
+        },
+        UsageReportDisputed {
+            resource_id: ResourceId,
+            report_id: UsageReportId,
+        },
+        // Undisputed usage paid together with a claim or cancellation
+        UsageSettled {
+            resource_id: ResourceId,
+            amount: BalanceOf<T>,
+        },
+        // Held usage of an ended rental, paid to the provider or refunded if disputed
+        HeldUsageSettled {
+            resource_id: ResourceId,
+            report_id: UsageReportId,
+            paid: BalanceOf<T>,
+            refunded: BalanceOf<T>,
+        },
+        UsageDebtIncurred {
+            renter: T::AccountId,
+            provider: T::AccountId,
+            amount: BalanceOf<T>,
+        },
+        UsageDebtPaid {
+            renter: T::AccountId,
+            provider: T::AccountId,
+            amount: BalanceOf<T>,
+        },
+        DisputeOpened {
+            dispute_id: DisputeId,
+            resource_id: ResourceId,
+            renter: T::AccountId,
+        },
+        EvidenceSubmitted {
+            dispute_id: DisputeId,
+            who: T::AccountId,
+            evidence: T::Hash,
+        },
+        DisputeResolved {
+            dispute_id: DisputeId,
+            renter_amount: BalanceOf<T>,
+            provider_amount: BalanceOf<T>,
+            slashed: BalanceOf<T>,
+        },
+        OrderPlaced {
+            order_id: OrderId,
+            renter: T::AccountId,
+        },
+        OrderCancelled {
+            order_id: OrderId,
+        },
+        OrderExpired {
+            order_id: OrderId,
+        },
+        AskPosted {
+            resource_id: ResourceId,
+            price: BalanceOf<T>,
+        },
+        AskWithdrawn {
+            resource_id: ResourceId,
+        },
+        OrderMatched {
+            order_id: OrderId,
+            resource_id: ResourceId,
+            price: BalanceOf<T>,
+        },
+        AuctionCreated {
+            resource_id: ResourceId,
+            kind: AuctionKind,
+            reveal_end: BlockNumberFor<T>,
+        },
+        BidCommitted {
+            resource_id: ResourceId,
+            bidder: T::AccountId,
+        },
+        BidRevealed {
+            resource_id: ResourceId,
+            bidder: T::AccountId,
+            bid: BalanceOf<T>,
+        },
+        // The winner rents the resource at the clearing price per week
+        AuctionSettled {
+            resource_id: ResourceId,
+            winner: T::AccountId,
+            price: BalanceOf<T>,
+        },
+        AuctionFailed {
+            resource_id: ResourceId,
+        },
+        // The deposit of a bid never revealed went to the provider
+        BidForfeited {
+            resource_id: ResourceId,
+            bidder: T::AccountId,
+            amount: BalanceOf<T>,
+        },
+        CommitmentStarted {
+            resource_id: ResourceId,
+            renter: T::AccountId,
+            term: CommitmentTerm,
+            ends_at: BlockNumberFor<T>,
+        },
+        // The renter ended its commitment early and paid the penalty to the provider
+        CommitmentTerminated {
+            resource_id: ResourceId,
+            penalty: BalanceOf<T>,
+        },
+        // Provider agents (re)deploy the workload of the rental
+        DeploymentUpdated {
+            resource_id: ResourceId,
+            version: u32,
+            manifest: DeploymentManifestOf<T>,
+        },
+        // The provider must post the access bundle encrypted to the new key
+        AccessKeySet {
+            resource_id: ResourceId,
+            key: AccessKey,
+        },
+        AccessBundlePosted {
+            resource_id: ResourceId,
+            version: u32,
+        },
+        HealthReported {
+            provider: T::AccountId,
+            healthy: u32,
+        },
+        WatcherJoined {
+            who: T::AccountId,
+        },
+        // The stake can be withdrawn once the next epoch is over and its attestations are
+        // finalized. Drained watchers are removed from the set.
+        WatcherLeft {
+            who: T::AccountId,
+            epoch: EpochIndex,
+        },
+        WatcherStakeWithdrawn {
+            who: T::AccountId,
+            amount: BalanceOf<T>,
+        },
+        WatchEpochStarted {
+            epoch: EpochIndex,
+            watchers: u32,
+        },
+        LivenessAttested {
+            epoch: EpochIndex,
+            resource_id: ResourceId,
+            watcher: T::AccountId,
+            alive: bool,
+        },
+        // No verdict without a quorum or on a tie
+        LivenessFinalized {
+            epoch: EpochIndex,
+            resource_id: ResourceId,
+            alive: Option<bool>,
+        },
+        WatcherRewarded {
+            who: T::AccountId,
+            amount: BalanceOf<T>,
+        },
+        WatcherSlashed {
+            who: T::AccountId,
+            amount: BalanceOf<T>,
+        },
+        AuditorAdded {
+            who: T::AccountId,
+        },
+        AuditorRemoved {
+            who: T::AccountId,
+        },
+        HardwareAttested {
+            resource_id: ResourceId,
+            verifier: T::AccountId,
+            benchmark: T::Hash,
+            verified: bool,
+        },
+        HostAttested {
+            host_id: HostId,
+            verifier: T::AccountId,
+            benchmark: T::Hash,
+            verified: bool,
+        },
+        // A watcher benchmarked some hardware, attested once enough watchers agree
+        HardwareVoted {
+            target: AttestationTarget,
+            watcher: T::AccountId,
+            verified: bool,
+        },
+        VerificationRequirementSet {
+            required: bool,
+        },
+        HostRegistered {
+            host_id: HostId,
+            provider: T::AccountId,
+        },
+        // A slice of the host is rented as `resource_id`
+        SliceAllocated {
+            host_id: HostId,
+            resource_id: ResourceId,
+        },
+        SliceReleased {
+            host_id: HostId,
+            resource_id: ResourceId,
+        },
+        HostUpdated {
+            host_id: HostId,
+        },
+        HostDeregistered {
+            host_id: HostId,
+            provider: T::AccountId,
+        },
+        BootstrapperAdded {
+            who: T::AccountId,
+            bootstrap_type: BootstrapperType,
+            expires_at: BlockNumberFor<T>,
+        },
+
+        BootstrapperRemoved {
+            who: T::AccountId,
+        },
+        BootstrapperRequested {
+            who: T::AccountId,
+        },
+        BootstrapperRequestRejected {
+            who: T::AccountId,
+        },
+        BootstrapperExpired {
+            who: T::AccountId,
+        },
+        // The expired bootstrapper is kept until an admin removes it
+        BootstrapperRemovalFailed {
+            who: T::AccountId,
+            error: DispatchError,
+        },
+        BootstrappedResourceAdded {
+            resource_id: ResourceId,
+            bootstrapper: T::AccountId,
+        },
+        OrganisationCreated {
+            org_id: OrganisationId,
+            account: T::AccountId,
+            admin: T::AccountId,
+        },
+        MemberRoleSet {
+            org_id: OrganisationId,
+            member: T::AccountId,
+            role: OrgRole,
+        },
+        MemberRemoved {
+            org_id: OrganisationId,
+            member: T::AccountId,
+        },
+        OrganisationFundsWithdrawn {
+            org_id: OrganisationId,
+            beneficiary: T::AccountId,
+            amount: BalanceOf<T>,
+        },
+        BudgetSet {
+            who: T::AccountId,
+            budget: Option<BudgetOf<T>>,
+        },
+        // Spending of the period crossed the alert threshold of the budget
+        BudgetThresholdReached {
+            who: T::AccountId,
+            spent: BalanceOf<T>,
+            cap: BalanceOf<T>,
+        },
+        BudgetControllerSet {
+            who: T::AccountId,
+            controller: Option<T::AccountId>,
+        },
+    }
+
+    #[pallet::error]
+    pub enum Error<T> {
+        #[deprecated(
+            note = "names are bounded by their type and checked by `InvalidProviderName`"
+        )]
+        ProviderNameTooLong,
+        #[deprecated(note = "websites are bounded by their type and checked by `InvalidWebsite`")]
+        ProviderWebsiteTooLong,
+        ProviderAlreadyRegistered,
+        ProviderNotRegistered,
+        ResourceNotFound,
+        ResourceNotAvailable,
+        NotResourceOwner,
+        #[deprecated(note = "block numbers are no longer converted from u32")]
+        BlockNumberOverflow,
+        NotRenter,
+        RentalNotFound,
+        NoPaymentDue,
+        InsufficientDeposit,
+        ConversionError,
+        NotBootstrapper,
+        BootstrapperExpired,
+        UptimeTooLow,
+        ResourceIdOverflow,
+        BootstrapperAlreadyRegistered,
+        InvalidBootstrapperExpiry,
+        BootstrapperResourceCapTooHigh,
+        BootstrapperResourceCapReached,
+        TooManyExpiringBootstrappers,
+        BootstrapperRequestPending,
+        BootstrapperRequestNotFound,
+        TooManyPendingBootstrappers,
+        InvalidBillingPeriod,
+        NoRateCard,
+        UsageReportTooLarge,
+        TooManyUsageReports,
+        UsageReportNotFound,
+        DisputeWindowClosed,
+        UsageDisputeWindowOpen,
+        OutstandingUsageDebt,
+        NoUsageDebt,
+        EscrowFrozen,
+        DisputeAlreadyOpen,
+        DisputeNotFound,
+        NotDisputeParty,
+        TooMuchEvidence,
+        OrderNotFound,
+        NotOrderOwner,
+        TooManyOpenOrders,
+        InvalidOrderDuration,
+        OrderExpired,
+        AskNotFound,
+        OrderMismatch,
+        NotGpuResource,
+        AuctionAlreadyExists,
+        AuctionNotFound,
+        ResourceInAuction,
+        InvalidAuctionPeriod,
+        TooManyEndingAuctions,
+        TooManyBids,
+        BidAlreadyCommitted,
+        BiddingClosed,
+        NotRevealPhase,
+        BidNotFound,
+        InvalidBidReveal,
+        #[deprecated(note = "bids are no longer bounded by their deposit")]
+        BidAboveReserved,
+        OwnAuction,
+        BidBelowReservePrice,
+        HostNotFound,
+        InvalidCapacity,
+        InsufficientCapacity,
+        HostInUse,
+        ResourceCommitted,
+        InvalidManifest,
+        ManifestExceedsResource,
+        AccessKeyNotSet,
+        EmptyAccessBundle,
+        WatcherAlreadyRegistered,
+        NotWatcher,
+        TooManyWatchers,
+        WatcherLeaving,
+        WatcherStillBonded,
+        EpochNotStarted,
+        NotAssignedWatcher,
+        AlreadyAttested,
+        EpochNotOver,
+        NoAttestations,
+        AuditorAlreadyAdded,
+        AuditorNotFound,
+        NotVerifier,
+        VerificationFailed,
+        ResourceUnverified,
+        InvalidCountryCode,
+        InvalidProviderName,
+        InvalidWebsite,
+        IdentityNotVerified,
+        ProviderNotVerified,
+        IdentityStillVerified,
+        StatusReservedForGovernance,
+        ProviderNotAcceptingRentals,
+        ProviderDeregistering,
+        ProviderStillUnbonding,
+        ProviderNotDeregistering,
+        ProviderTerminated,
+        ProviderNotSuspended,
+        InvalidSuspensionExpiry,
+        TooManyExpiringSuspensions,
+        OrganisationNotFound,
+        NotOrganisationMember,
+        MissingOrganisationRole,
+        TooManyOrganisationMembers,
+        LastOrganisationAdmin,
+        OrganisationIdOverflow,
+        InvalidBudget,
+        BudgetExceeded,
+        NotBudgetController,
+    }
+
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+        fn on_initialize(n: BlockNumberFor<T>) -> Weight {
+            // Including the watch epoch rotation of `on_finalize`
+            let mut weight = T::DbWeight::get().reads_writes(4, 1);
+
+            for resource_id in AuctionEnds::<T>::take(n) {
+                let (bids, attempts) = Self::settle_auction(resource_id);
+
+                // Auction, then each bid released, then each rental attempted down the bids
+                weight.saturating_accrue(T::DbWeight::get().reads_writes(
+                    2 + 2 * bids as u64 + 4 * attempts as u64,
+                    1 + 2 * bids as u64 + 8 * attempts as u64,
+                ));
+            }
+
+            for who in SuspensionExpiries::<T>::take(n) {
+                // Lifted or extended by governance in the meantime
+                if Suspensions::<T>::get(&who).and_then(|s| s.expires_at) != Some(n) {
+                    weight.saturating_accrue(T::DbWeight::get().reads(1));
+                    continue;
+                }
+                let relisted = Self::do_lift_suspension(&who);
+
+                // Suspension and provider, plus resource and index entry per resource
+                weight.saturating_accrue(
+                    T::DbWeight::get().reads_writes(2 + relisted as u64, 3 + relisted as u64),
+                );
+
+                Self::deposit_event(Event::SuspensionLifted { who });
+            }
+
+            let expiring = BootstrapperExpiries::<T>::take(n);
+            if expiring.is_empty() {
+                return weight;
+            }
+            weight.saturating_accrue(T::DbWeight::get().writes(1));
+
+            for who in expiring {
+                // A failed refund leaves the bootstrapper and its resources untouched
+                let removed = match frame_support::storage::with_storage_layer(|| {
+                    Self::do_remove_bootstrapper(&who)
+                }) {
+                    Ok(removed) => {
+                        Self::deposit_event(Event::BootstrapperExpired { who });
+                        removed
+                    }
+                    Err(error) => {
+                        Self::deposit_event(Event::BootstrapperRemovalFailed { who, error });
+                        0
+                    }
+                };
+
+                // Bootstrapper entry, plus index entry, resource, rental and deposit per resource
+                weight.saturating_accrue(
+                    T::DbWeight::get().reads_writes(1 + 3 * removed as u64, 1 + 4 * removed as u64),
+                );
+            }
+
+            weight
+        }
+
+        fn on_idle(n: BlockNumberFor<T>, remaining_weight: Weight) -> Weight {
+            let mut weight = T::DbWeight::get().reads(1);
+            if remaining_weight.any_lt(weight) {
+                return Weight::zero();
+            }
+
+            let queue = OrderQueue::<T>::get();
+
+            // Scanning the fitting categories at worst, then renting the best ask
+            let per_order = T::DbWeight::get()
+                .reads_writes(3u64.saturating_add(3 * Asks::<T>::count() as u64), 10);
+
+            for order_id in queue {
+                if remaining_weight.any_lt(weight.saturating_add(per_order)) {
+                    break;
+                }
+                weight.saturating_accrue(per_order);
+
+                let Some(order) = Orders::<T>::get(order_id) else {
+                    continue;
+                };
+                if n >= order.expires_at {
+                    Self::close_order(order_id, &order);
+                    Self::deposit_event(Event::OrderExpired { order_id });
+                    continue;
+                }
+                let Some(resource_id) = Self::best_ask(&order) else {
+                    continue;
+                };
+
+                // A failed escrow transfer leaves the order queued
+                let _ = frame_support::storage::with_storage_layer(|| {
+                    Self::do_match_order(order_id, order, resource_id)
+                });
+            }
+
+            // Then the epochs nobody finalized, with what is left
+            weight.saturating_add(Self::finalize_stale_liveness(
+                n,
+                remaining_weight.saturating_sub(weight),
+            ))
+        }
+
+        fn on_finalize(n: BlockNumberFor<T>) {
+            // Assignments of the next block are drawn once the randomness of this block is
+            // known, i.e. after the parachain inherent
+            let epoch = Self::epoch_at(n.saturating_add(One::one()));
+            if WatchEpoch::<T>::get().is_none_or(|(current, ..)| current != epoch) {
+                Self::start_watch_epoch(epoch, n);
+            }
+        }
+
+        fn offchain_worker(n: BlockNumberFor<T>) {
+            Self::probe_resources(n);
+        }
+
+        fn integrity_test() {
+            assert!(
+                !T::BlocksPerWeek::get().is_zero(),
+                "BlocksPerWeek must not be zero"
+            );
+            assert!(
+                !T::OffchainHeartbeatInterval::get().is_zero(),
+                "OffchainHeartbeatInterval must not be zero"
+            );
+            assert!(
+                !T::WatcherEpoch::get().is_zero(),
+                "WatcherEpoch must not be zero"
+            );
+            assert!(
+                T::WatcherQuorum::get() > 0
+                    && T::WatcherQuorum::get() <= T::WatchersPerResource::get(),
+                "WatcherQuorum must be within 1..=WatchersPerResource"
+            );
+            assert!(
+                T::ProviderUnbondingPeriod::get() >= T::DisputeWindow::get(),
+                "ProviderUnbondingPeriod must cover the DisputeWindow"
+            );
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn try_state(
+            _n: BlockNumberFor<T>,
+        ) -> Result<(), frame_support::sp_runtime::TryRuntimeError> {
+            Self::do_try_state()
+        }
+    }
+
+    #[pallet::call]
+    impl<T: Config> Pallet<T> {
+        #[pallet::call_index(0)]
+        #[pallet::weight(Weight::default())]
+        pub fn register_provider(
+            origin: OriginFor<T>,
+            name: ProviderName,
+            website: Option<Website>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            ensure!(
+                !Providers::<T>::contains_key(&who),
+                Error::<T>::ProviderAlreadyRegistered
+            );
+
+            Self::ensure_valid_info(&name, &website)?;
+
+            let current_block = frame_system::Pallet::<T>::block_number();
+
+            // Create provider info
+            let provider_info = ProviderInfo {
+                account: who.clone(),
+                name: name.clone(),
+                website,
+                total_resources: 0,
+                reputation_score: Default::default(),
+                registration_block: current_block,
+                last_updated: current_block,
+                status: ProviderStatus::Inactive,
+            };
+
+            let bond = T::ProviderBond::get();
+            T::Currency::reserve(&who, bond)?;
+            ProviderBonds::<T>::insert(&who, bond);
+
+            Providers::<T>::insert(&who, provider_info);
+
+            Self::deposit_event(Event::ProviderRegistered { who: who.clone() });
+
+            Ok(())
+        }
+
+        #[pallet::call_index(1)]
+        #[pallet::weight(Weight::default())]
+        pub fn update_provider_status(
+            origin: OriginFor<T>,
+            status: ProviderStatus,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            // Providers only switch between active and inactive, other statuses are left to
+            // governance and to the deregistration flow
+            ensure!(
+                status.accepts_rentals(),
+                Error::<T>::StatusReservedForGovernance
+            );
+
+            Providers::<T>::try_mutate(&who, |maybe_provider| -> DispatchResult {
+                let provider = maybe_provider
+                    .as_mut()
+                    .ok_or(Error::<T>::ProviderNotRegistered)?;
+                ensure!(
+                    provider.status.accepts_rentals(),
+                    Error::<T>::StatusReservedForGovernance
+                );
+
+                provider.status = status.clone();
+
+                let current_block = frame_system::Pallet::<T>::block_number();
+
+                provider.last_updated = current_block;
+
+                Self::deposit_event(Event::ProviderStatusChanged {
+                    who: who.clone(),
+                    status,
+                });
+
+                Ok(())
+            })
+        }
+
+        #[pallet::call_index(3)]
+        #[pallet::weight(Weight::default())]
+        pub fn register_resource(
+            origin: OriginFor<T>,
+            resource: Resource<T::AccountId>,
+        ) -> DispatchResult {
+            let provider = ensure_signed(origin)?;
+            ensure!(
+                Providers::<T>::contains_key(&provider),
+                Error::<T>::ProviderNotRegistered
+            );
+            ensure!(
+                Self::accepts_rentals(&provider),
+                Error::<T>::ProviderNotAcceptingRentals
+            );
+
+            let location = resource
+                .location
+                .clone()
+                .normalized()
+                .ok_or(Error::<T>::InvalidCountryCode)?;
+
+            let resource_id = NextResourceId::<T>::get();
+
+            let next_resource_id = resource_id
+                .checked_add(1)
+                .ok_or(Error::<T>::ResourceIdOverflow)?;
+
+            ProviderResourceCount::<T>::mutate(
+                provider.clone(),
+                resource.category.clone(),
+                |count| *count += 1,
+            );
+
+            // The registering account is always the provider of the resource
+            let resource = Resource {
+                provider: provider.clone(),
+                location,
+                ..resource
+            };
+            Self::index_resource(resource_id, &resource);
+            ProviderResources::<T>::insert(&provider, resource_id, ());
+            Resources::<T>::insert(resource_id, resource);
+
+            NextResourceId::<T>::put(next_resource_id);
+
+            Self::deposit_event(Event::ResourceRegistered {
+                resource_id,
+                provider,
+            });
+
+            Ok(())
+        }
+
+        #[pallet::call_index(4)]
+        #[pallet::weight(Weight::default())]
+        pub fn rent_resource(
+            origin: OriginFor<T>,
+            resource_id: ResourceId,
+            billing_period: BillingPeriodOf<T>,
+            commitment: Option<CommitmentTerm>,
+        ) -> DispatchResult {
+            let renter = ensure_signed(origin)?;
+
+            Self::do_rent_committed(renter, resource_id, billing_period, commitment)
+        }
+
+        #[pallet::call_index(5)]
+        #[pallet::weight(Weight::default())]
+        pub fn cancel_rental(origin: OriginFor<T>, resource_id: ResourceId) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let rental = Rentals::<T>::get(resource_id).ok_or(Error::<T>::RentalNotFound)?;
+            Self::ensure_acting_for(&who, &rental.renter, OrgRole::Billing)?;
+            let renter = rental.renter.clone();
+
+            ensure!(
+                !ResourceDisputes::<T>::contains_key(resource_id),
+                Error::<T>::EscrowFrozen
+            );
+
+            Self::do_cancel_rental(resource_id, &rental)?;
+
+            Self::terminate_commitment(resource_id, &renter)?;
+
+            Self::deposit_event(Event::RentalCancelled {
+                resource_id,
+                renter,
+            });
+
+            Ok(())
+        }
+
+        #[pallet::call_index(6)]
+        #[pallet::weight(Weight::default())]
+        pub fn claim_payment(origin: OriginFor<T>, resource_id: ResourceId) -> DispatchResult {
+            let provider = ensure_signed(origin)?;
+
+            // Get the resource and verify ownership
+            let resource = Self::resource(resource_id).ok_or(Error::<T>::ResourceNotFound)?;
+            ensure!(resource.provider == provider, Error::<T>::NotResourceOwner);
+
+            // Get the rental information
+            let rental = Rentals::<T>::get(resource_id).ok_or(Error::<T>::RentalNotFound)?;
+            ensure!(rental.is_active, Error::<T>::RentalNotFound);
+
+            ensure!(
+                !ResourceDisputes::<T>::contains_key(resource_id),
+                Error::<T>::EscrowFrozen
+            );
+
+            let current_block = frame_system::Pallet::<T>::block_number();
+
+            // Calculate period length in blocks
+            let period_length = rental.billing_period.duration(T::BlocksPerWeek::get());
+
+            // Calculate blocks since last payment
+            let blocks_since_last_payment = current_block.saturating_sub(rental.last_paid_block);
+
+            // Calculate complete periods since last payment, time is billed per full period
+            let complete_periods = blocks_since_last_payment / period_length;
+
+            // Calculate the payment amount
+            let period_cost =
+                Self::period_price(resource_id, &resource, &rental.billing_period, None);
+
+            let payment_amount: BalanceOf<T> = (period_cost
+                .saturating_mul(complete_periods.saturated_into::<u128>()))
+            .try_into()
+            .map_err(|_| Error::<T>::ConversionError)?;
+
+            // Usage reports past their dispute window are settled along
+            let usage_amount = Self::take_settled_usage(resource_id, current_block);
+
+            // The provider can't be paid more than what is left of the deposit, usage beyond it
+            // is owed by the renter
+            let deposit = Deposits::<T>::get(resource_id).unwrap_or_default();
+            let amount_due = payment_amount.saturating_add(usage_amount);
+            let payment_amount = amount_due.min(deposit);
+            let unpaid_usage = amount_due.saturating_sub(deposit).min(usage_amount);
+
+            ensure!(!payment_amount.is_zero(), Error::<T>::NoPaymentDue);
+
+            // Update the last paid block
+            let new_last_paid_block = rental
+                .last_paid_block
+                .saturating_add(complete_periods.saturating_mul(period_length));
+
+            Rentals::<T>::try_mutate(resource_id, |maybe_rental| -> DispatchResult {
+                let rental = maybe_rental.as_mut().ok_or(Error::<T>::RentalNotFound)?;
+                rental.last_paid_block = new_last_paid_block;
+                Ok(())
+            })?;
+
+            let remaining_deposit = deposit.saturating_sub(payment_amount);
+            Deposits::<T>::insert(resource_id, remaining_deposit);
+
+            // Transfer payment directly from escrow to provider
+            Self::pay_provider(&provider, payment_amount)?;
+
+            if !usage_amount.is_zero() {
+                Self::deposit_event(Event::UsageSettled {
+                    resource_id,
+                    amount: usage_amount,
+                });
+            }
+
+            Self::deposit_event(Event::PaymentClaimed {
+                resource_id,
+                provider: provider.clone(),
+                amount: payment_amount,
+            });
+
+            Self::incur_usage_debt(&rental.renter, &provider, unpaid_usage);
+
+            // A rental lasts as long as it is funded
+            if remaining_deposit.is_zero() {
+                // Its commitment ends along, the penalty is waived if the renter can't pay it
+                let penalty = frame_support::storage::with_storage_layer(|| {
+                    Self::terminate_commitment(resource_id, &rental.renter)
+                });
+                if penalty.is_err() {
+                    Commitments::<T>::remove(resource_id);
+                }
+                Self::release_rental(resource_id);
+
+                Self::deposit_event(Event::RentalExpired {
+                    resource_id,
+                    renter: rental.renter,
+                });
+            }
+
+            Ok(())
+        }
+
+        #[pallet::call_index(7)]
+        #[pallet::weight(Weight::default())]
+        pub fn add_bootstrapper(
+            origin: OriginFor<T>,
+            account: T::AccountId,
+            bootstrap_type: BootstrapperType,
+            expires_at: BlockNumberFor<T>,
+            max_resources: u32,
+        ) -> DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
+
+            Self::do_add_bootstrapper(account, bootstrap_type, expires_at, max_resources)
+        }
+
+        #[pallet::call_index(8)]
+        #[pallet::weight(Weight::default())]
+        pub fn register_bootstrapped_resource(
+            origin: OriginFor<T>,
+            resource: Resource<T::AccountId>,
+        ) -> DispatchResult {
+            let bootstrapper = ensure_signed(origin)?;
+
+            // Check if bootstrapper exists and is valid
+            let mut bootstrapper_info =
+                Bootstrappers::<T>::get(&bootstrapper).ok_or(Error::<T>::NotBootstrapper)?;
+
+            let current_block = frame_system::Pallet::<T>::block_number();
+
+            ensure!(
+                current_block < bootstrapper_info.expires_at,
+                Error::<T>::BootstrapperExpired
+            );
+
+            ensure!(
+                bootstrapper_info.resource_count < bootstrapper_info.max_resources,
+                Error::<T>::BootstrapperResourceCapReached
+            );
+
+            let location = resource
+                .location
+                .clone()
+                .normalized()
+                .ok_or(Error::<T>::InvalidCountryCode)?;
+
+            // Bootstrapped resources share the regular resource ids so they go through the
+            // same rental flow
+            let resource_id = NextResourceId::<T>::get();
+
+            let next_resource_id = resource_id
+                .checked_add(1)
+                .ok_or(Error::<T>::ResourceIdOverflow)?;
+
+            let bootstrapped = Resource {
+                provider: bootstrapper.clone(),
+                location,
+                ..resource.clone()
+            };
+            Self::index_resource(resource_id, &bootstrapped);
+            ProviderResources::<T>::insert(&bootstrapper, resource_id, ());
+            Resources::<T>::insert(resource_id, bootstrapped);
+
+            BootstrappedResources::<T>::insert(&bootstrapper, resource_id, ());
+
+            ProviderResourceCount::<T>::mutate(
+                bootstrapper.clone(),
+                resource.category.clone(),
+                |count| *count += 1,
+            );
+
+            NextResourceId::<T>::put(next_resource_id);
+
+            bootstrapper_info.resource_count += 1;
+            Bootstrappers::<T>::insert(&bootstrapper, bootstrapper_info);
+
+            Self::deposit_event(Event::BootstrappedResourceAdded {
+                resource_id,
+                bootstrapper,
+            });
+
+            Ok(())
+        }
+
+        #[pallet::call_index(9)]
+        #[pallet::weight(Weight::default())]
+        pub fn remove_bootstrapper(origin: OriginFor<T>, account: T::AccountId) -> DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
+
+            let bootstrapper_info =
+                Bootstrappers::<T>::get(&account).ok_or(Error::<T>::NotBootstrapper)?;
+
+            // Drop the scheduled expiry
+            BootstrapperExpiries::<T>::mutate(bootstrapper_info.expires_at, |expiring| {
+                expiring.retain(|who| who != &account)
+            });
+
+            Self::do_remove_bootstrapper(&account)?;
+
+            Self::deposit_event(Event::BootstrapperRemoved { who: account });
+
+            Ok(())
+        }
+
+        /// Ask to become a bootstrapper. The request waits for governance approval.
+        #[pallet::call_index(10)]
+        #[pallet::weight(Weight::default())]
+        pub fn request_bootstrapper(origin: OriginFor<T>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            ensure!(
+                !Bootstrappers::<T>::contains_key(&who),
+                Error::<T>::BootstrapperAlreadyRegistered
+            );
+
+            ensure!(
+                !PendingBootstrappers::<T>::contains_key(&who),
+                Error::<T>::BootstrapperRequestPending
+            );
+
+            ensure!(
+                PendingBootstrappers::<T>::count() < T::MaxPendingBootstrappers::get(),
+                Error::<T>::TooManyPendingBootstrappers
+            );
+
+            let current_block = frame_system::Pallet::<T>::block_number();
+
+            PendingBootstrappers::<T>::insert(&who, current_block);
+
+            Self::deposit_event(Event::BootstrapperRequested { who });
+
+            Ok(())
+        }
+
+        #[pallet::call_index(11)]
+        #[pallet::weight(Weight::default())]
+        pub fn approve_bootstrapper(
+            origin: OriginFor<T>,
+            account: T::AccountId,
+            bootstrap_type: BootstrapperType,
+            expires_at: BlockNumberFor<T>,
+            max_resources: u32,
+        ) -> DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
+
+            ensure!(
+                PendingBootstrappers::<T>::contains_key(&account),
+                Error::<T>::BootstrapperRequestNotFound
+            );
+
+            Self::do_add_bootstrapper(account, bootstrap_type, expires_at, max_resources)
+        }
+
+        #[pallet::call_index(12)]
+        #[pallet::weight(Weight::default())]
+        pub fn reject_bootstrapper(origin: OriginFor<T>, account: T::AccountId) -> DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
+
+            PendingBootstrappers::<T>::take(&account)
+                .ok_or(Error::<T>::BootstrapperRequestNotFound)?;
+
+            Self::deposit_event(Event::BootstrapperRequestRejected { who: account });
+
+            Ok(())
+        }
+
+        /// Add funds to the deposit of a rental, extending how long it stays funded
+        #[pallet::call_index(13)]
+        #[pallet::weight(Weight::default())]
+        pub fn top_up_rental(
+            origin: OriginFor<T>,
+            resource_id: ResourceId,
+            amount: BalanceOf<T>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let rental = Rentals::<T>::get(resource_id).ok_or(Error::<T>::RentalNotFound)?;
+            Self::ensure_acting_for(&who, &rental.renter, OrgRole::Billing)?;
+            // Organisation rentals are topped up from the treasury
+            let renter = rental.renter;
+
+            ensure!(!amount.is_zero(), Error::<T>::InsufficientDeposit);
+            Self::charge_budget(&renter, amount)?;
+
+            T::Currency::transfer(
+                &renter,
+                &Self::escrow_account(),
+                amount,
+                ExistenceRequirement::KeepAlive,
+            )?;
+
+            Deposits::<T>::mutate(resource_id, |deposit| {
+                *deposit = Some(deposit.unwrap_or_default().saturating_add(amount));
+            });
+
+            Self::deposit_event(Event::RentalToppedUp {
+                resource_id,
+                renter,
+                amount,
+            });
+
+            Ok(())
+        }
+
+        /// Set the prices charged for metered usage on new rentals
+        #[pallet::call_index(14)]
+        #[pallet::weight(Weight::default())]
+        pub fn set_rate_card(
+            origin: OriginFor<T>,
+            rate_card: RateCard<BalanceOf<T>>,
+        ) -> DispatchResult {
+            let provider = ensure_signed(origin)?;
+
+            ensure!(
+                Providers::<T>::contains_key(&provider)
+                    || Bootstrappers::<T>::contains_key(&provider),
+                Error::<T>::ProviderNotRegistered
+            );
+
+            RateCards::<T>::insert(&provider, rate_card.clone());
+
+            Self::deposit_event(Event::RateCardSet {
+                provider,
+                rate_card,
+            });
+
+            Ok(())
+        }
+
+        /// Report the usage of a rental. It is paid with the next claim once the dispute window
+        /// is over, unless the renter disputes it.
+        #[pallet::call_index(15)]
+        #[pallet::weight(Weight::default())]
+        pub fn submit_usage_report(
+            origin: OriginFor<T>,
+            resource_id: ResourceId,
+            report: UsageReport,
+        ) -> DispatchResult {
+            let provider = ensure_signed(origin)?;
+
+            let resource = Self::resource(resource_id).ok_or(Error::<T>::ResourceNotFound)?;
+            ensure!(resource.provider == provider, Error::<T>::NotResourceOwner);
+
+            ensure!(
+                Rentals::<T>::contains_key(resource_id),
+                Error::<T>::RentalNotFound
+            );
+
+            ensure!(
+                report.fits_within(&T::MaxUsagePerReport::get()),
+                Error::<T>::UsageReportTooLarge
+            );
+
+            let rate_card = RentalRateCards::<T>::get(resource_id).ok_or(Error::<T>::NoRateCard)?;
+            let amount = rate_card.price(&report);
+
+            let report_id = NextUsageReportId::<T>::get();
+
+            UsageReports::<T>::try_mutate(resource_id, |records| {
+                records.try_push(UsageRecord {
+                    id: report_id,
+                    report: report.clone(),
+                    amount,
+                    submitted_at: frame_system::Pallet::<T>::block_number(),
+                    disputed: false,
+                })
+            })
+            .map_err(|_| Error::<T>::TooManyUsageReports)?;
+
+            NextUsageReportId::<T>::put(report_id.saturating_add(1));
+
+            Self::deposit_event(Event::UsageReported {
+                resource_id,
+                report_id,
+                report,
+                amount,
+            });
+
+            Ok(())
+        }
+
+        /// Reject a usage report while its dispute window is open, it will not be paid
+        #[pallet::call_index(16)]
+        #[pallet::weight(Weight::default())]
+        pub fn dispute_usage_report(
+            origin: OriginFor<T>,
+            resource_id: ResourceId,
+            report_id: UsageReportId,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let current_block = frame_system::Pallet::<T>::block_number();
+            let window = T::UsageDisputeWindow::get();
+
+            // Usage of an ended rental is disputed while held in escrow
+            if let Some(mut held) = HeldUsages::<T>::get(resource_id, report_id) {
+                Self::ensure_acting_for(&who, &held.renter, OrgRole::Billing)?;
+                ensure!(
+                    current_block < held.record.submitted_at.saturating_add(window),
+                    Error::<T>::DisputeWindowClosed
+                );
+                held.record.disputed = true;
+                HeldUsages::<T>::insert(resource_id, report_id, held);
+
+                Self::deposit_event(Event::UsageReportDisputed {
+                    resource_id,
+                    report_id,
+                });
+
+                return Ok(());
+            }
+
+            let rental = Rentals::<T>::get(resource_id).ok_or(Error::<T>::RentalNotFound)?;
+            Self::ensure_acting_for(&who, &rental.renter, OrgRole::Billing)?;
+
+            UsageReports::<T>::try_mutate(resource_id, |records| -> DispatchResult {
+                let record = records
+                    .iter_mut()
+                    .find(|record| record.id == report_id)
+                    .ok_or(Error::<T>::UsageReportNotFound)?;
+
+                ensure!(
+                    current_block < record.submitted_at.saturating_add(window),
+                    Error::<T>::DisputeWindowClosed
+                );
+
+                record.disputed = true;
+
+                Ok(())
+            })?;
+
+            Self::deposit_event(Event::UsageReportDisputed {
+                resource_id,
+                report_id,
+            });
+
+            Ok(())
+        }
+
+        /// Dispute an ongoing or recently ended rental. The escrow of an ongoing rental is
+        /// frozen until an arbiter resolves the dispute. The last renter of a resource can
+        /// dispute its ended rental while someone else rents the resource.
+        #[pallet::call_index(17)]
+        #[pallet::weight(Weight::default())]
+        pub fn open_dispute(
+            origin: OriginFor<T>,
+            resource_id: ResourceId,
+            evidence: T::Hash,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let current_block = frame_system::Pallet::<T>::block_number();
+
+            let rental = Rentals::<T>::get(resource_id);
+            let ended = EndedRentals::<T>::get(resource_id);
+            // The ongoing rental first, unless `who` only acts for the ended one
+            let dispute_ongoing = match (&rental, &ended) {
+                (Some(rental), Some(ended)) => {
+                    Self::ensure_acting_for(&who, &rental.renter, OrgRole::Billing).is_ok()
+                        || Self::ensure_acting_for(&who, &ended.renter, OrgRole::Billing).is_err()
+                }
+                (rental, _) => rental.is_some(),
+            };
+
+            let (renter, provider, rental_active) = match (rental, ended) {
+                (Some(rental), _) if dispute_ongoing => {
+                    Self::ensure_acting_for(&who, &rental.renter, OrgRole::Billing)?;
+                    ensure!(
+                        !ResourceDisputes::<T>::contains_key(resource_id),
+                        Error::<T>::DisputeAlreadyOpen
+                    );
+                    let resource =
+                        Self::resource(resource_id).ok_or(Error::<T>::ResourceNotFound)?;
+                    (rental.renter, resource.provider, true)
+                }
+                (_, ended) => {
+                    let ended = ended.ok_or(Error::<T>::RentalNotFound)?;
+                    Self::ensure_acting_for(&who, &ended.renter, OrgRole::Billing)?;
+                    ensure!(
+                        current_block < ended.ended_at.saturating_add(T::DisputeWindow::get()),
+                        Error::<T>::DisputeWindowClosed
+                    );
+                    // An ended rental can only be disputed once
+                    EndedRentals::<T>::remove(resource_id);
+                    (ended.renter, ended.provider, false)
+                }
+            };
+
+            let dispute_id = NextDisputeId::<T>::get();
+            NextDisputeId::<T>::put(dispute_id.saturating_add(1));
+
+            if rental_active {
+                ResourceDisputes::<T>::insert(resource_id, dispute_id);
+            }
+            ProviderDisputes::<T>::mutate(&provider, |count| *count = count.saturating_add(1));
+
+            Disputes::<T>::insert(
+                dispute_id,
+                Dispute {
+                    resource_id,
+                    renter: renter.clone(),
+                    provider,
+                    opened_at: current_block,
+                    rental_active,
+                },
+            );
+
+            Self::deposit_event(Event::DisputeOpened {
+                dispute_id,
+                resource_id,
+                renter,
+            });
+
+            Self::do_submit_evidence(dispute_id, who, evidence)
+        }
+
+        /// Add an evidence hash to a dispute, for the renter or the provider
+        #[pallet::call_index(18)]
+        #[pallet::weight(Weight::default())]
+        pub fn submit_evidence(
+            origin: OriginFor<T>,
+            dispute_id: DisputeId,
+            evidence: T::Hash,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let dispute = Disputes::<T>::get(dispute_id).ok_or(Error::<T>::DisputeNotFound)?;
+            ensure!(
+                who == dispute.provider
+                    || Self::ensure_acting_for(&who, &dispute.renter, OrgRole::Billing).is_ok(),
+                Error::<T>::NotDisputeParty
+            );
+
+            Self::do_submit_evidence(dispute_id, who, evidence)
+        }
+
+        /// Close a dispute: the frozen escrow is split between the parties and part of the
+        /// provider bond can be slashed to the renter
+        #[pallet::call_index(19)]
+        #[pallet::weight(Weight::default())]
+        pub fn resolve_dispute(
+            origin: OriginFor<T>,
+            dispute_id: DisputeId,
+            renter_share: Perbill,
+            slash: BalanceOf<T>,
+        ) -> DispatchResult {
+            T::ArbiterOrigin::ensure_origin(origin)?;
+
+            let dispute = Disputes::<T>::take(dispute_id).ok_or(Error::<T>::DisputeNotFound)?;
+            if dispute.rental_active {
+                ResourceDisputes::<T>::remove(dispute.resource_id);
+            }
+            ProviderDisputes::<T>::mutate_exists(&dispute.provider, |count| {
+                *count = count.map(|c| c.saturating_sub(1)).filter(|c| *c > 0);
+            });
+            let _ = DisputeEvidence::<T>::clear_prefix(dispute_id, u32::MAX, None);
+
+            let escrow_account = Self::escrow_account();
+
+            // Split what is left of the deposit and end the rental
+            let (renter_amount, provider_amount) = if dispute.rental_active {
+                let deposit = Deposits::<T>::get(dispute.resource_id).unwrap_or_default();
+                let renter_amount = renter_share.mul_floor(deposit);
+                let provider_amount = deposit.saturating_sub(renter_amount);
+
+                T::Currency::transfer(
+                    &escrow_account,
+                    &dispute.renter,
+                    renter_amount,
+                    ExistenceRequirement::AllowDeath,
+                )?;
+                T::Currency::transfer(
+                    &escrow_account,
+                    &dispute.provider,
+                    provider_amount,
+                    ExistenceRequirement::AllowDeath,
+                )?;
+
+                // The arbiter ends the rental together with its commitment
+                Commitments::<T>::remove(dispute.resource_id);
+                Self::release_rental(dispute.resource_id);
+                // The rental was just disputed, it can't be disputed again once ended
+                EndedRentals::<T>::remove(dispute.resource_id);
+
+                (renter_amount, provider_amount)
+            } else {
+                (Zero::zero(), Zero::zero())
+            };
+
+            // Compensate the renter from the provider bond
+            let slashed = Self::slash_bond(&dispute.provider, &dispute.renter, slash);
+
+            Self::deposit_event(Event::DisputeResolved {
+                dispute_id,
+                renter_amount,
+                provider_amount,
+                slashed,
+            });
+
+            Ok(())
+        }
+
+        /// Request compute by specs instead of a resource id, for `duration` billing periods.
+        /// `OrderDeposit` is reserved until the order is matched, cancelled or expires after
+        /// `OrderLifetime` blocks. The rental deposit is escrowed when the order is matched
+        /// with an ask and prepays the whole duration.
+        #[pallet::call_index(20)]
+        #[pallet::weight(Weight::default())]
+        pub fn place_order(
+            origin: OriginFor<T>,
+            min_specs: ComputeSpecs,
+            country_code: Option<CountryCode>,
+            max_price: BalanceOf<T>,
+            billing_period: BillingPeriodOf<T>,
+            duration: u32,
+        ) -> DispatchResult {
+            let renter = ensure_signed(origin)?;
+
+            ensure!(
+                !billing_period.duration(T::BlocksPerWeek::get()).is_zero(),
+                Error::<T>::InvalidBillingPeriod
+            );
+            ensure!(duration > 0, Error::<T>::InvalidOrderDuration);
+            // Compared with the normalized codes of the resources
+            let country_code = country_code
+                .map(|code| {
+                    iso_country(&code)
+                        .map(|code| CountryCode::truncate_from(code.to_vec()))
+                        .ok_or(Error::<T>::InvalidCountryCode)
+                })
+                .transpose()?;
+
+            let order_id = NextOrderId::<T>::get();
+
+            OrderQueue::<T>::try_mutate(|queue| queue.try_push(order_id))
+                .map_err(|_| Error::<T>::TooManyOpenOrders)?;
+
+            let deposit = T::OrderDeposit::get();
+            T::Currency::reserve(&renter, deposit)?;
+
+            let expires_at =
+                frame_system::Pallet::<T>::block_number().saturating_add(T::OrderLifetime::get());
+            Orders::<T>::insert(
+                order_id,
+                ComputeOrder {
+                    renter: renter.clone(),
+                    min_specs,
+                    country_code,
+                    max_price,
+                    billing_period,
+                    duration,
+                    deposit,
+                    expires_at,
+                },
+            );
+            NextOrderId::<T>::put(order_id.saturating_add(1));
+
+            Self::deposit_event(Event::OrderPlaced { order_id, renter });
+
+            Ok(())
+        }
+
+        #[pallet::call_index(21)]
+        #[pallet::weight(Weight::default())]
+        pub fn cancel_order(origin: OriginFor<T>, order_id: OrderId) -> DispatchResult {
+            let renter = ensure_signed(origin)?;
+
+            let order = Orders::<T>::get(order_id).ok_or(Error::<T>::OrderNotFound)?;
+            ensure!(order.renter == renter, Error::<T>::NotOrderOwner);
+
+            Self::close_order(order_id, &order);
+
+            Self::deposit_event(Event::OrderCancelled { order_id });
+
+            Ok(())
+        }
+
+        /// Offer an available resource to the order book at a price per week
+        #[pallet::call_index(22)]
+        #[pallet::weight(Weight::default())]
+        pub fn post_ask(
+            origin: OriginFor<T>,
+            resource_id: ResourceId,
+            price: BalanceOf<T>,
+        ) -> DispatchResult {
+            let provider = ensure_signed(origin)?;
+
+            let resource = Resources::<T>::get(resource_id).ok_or(Error::<T>::ResourceNotFound)?;
+            ensure!(resource.provider == provider, Error::<T>::NotResourceOwner);
+            ensure!(resource.is_available, Error::<T>::ResourceNotAvailable);
+            ensure!(
+                Self::accepts_rentals(&provider),
+                Error::<T>::ProviderNotAcceptingRentals
+            );
+            ensure!(
+                !Auctions::<T>::contains_key(resource_id),
+                Error::<T>::ResourceInAuction
+            );
+            // Committed capacity can't be relisted
+            ensure!(
+                Self::active_commitment(resource_id, frame_system::Pallet::<T>::block_number())
+                    .is_none(),
+                Error::<T>::ResourceCommitted
+            );
+
+            Self::insert_ask(resource_id, &resource.category, price);
+
+            Self::deposit_event(Event::AskPosted { resource_id, price });
+
+            Ok(())
+        }
+
+        #[pallet::call_index(23)]
+        #[pallet::weight(Weight::default())]
+        pub fn withdraw_ask(origin: OriginFor<T>, resource_id: ResourceId) -> DispatchResult {
+            let provider = ensure_signed(origin)?;
+
+            let resource = Resources::<T>::get(resource_id).ok_or(Error::<T>::ResourceNotFound)?;
+            ensure!(resource.provider == provider, Error::<T>::NotResourceOwner);
+
+            ensure!(Self::remove_ask(resource_id), Error::<T>::AskNotFound);
+
+            Self::deposit_event(Event::AskWithdrawn { resource_id });
+
+            Ok(())
+        }
+
+        /// Pair an order with an ask, anyone can submit a match. Orders are also matched with
+        /// the cheapest fitting ask in `on_idle`.
+        #[pallet::call_index(24)]
+        #[pallet::weight(Weight::default())]
+        pub fn match_order(
+            origin: OriginFor<T>,
+            order_id: OrderId,
+            resource_id: ResourceId,
+        ) -> DispatchResult {
+            ensure_signed(origin)?;
+
+            let order = Orders::<T>::get(order_id).ok_or(Error::<T>::OrderNotFound)?;
+
+            Self::do_match_order(order_id, order, resource_id)
+        }
+
+        /// List an available GPU resource into a sealed-bid auction. Bids are committed until
+        /// `commit_period` blocks, then revealed during `reveal_period` blocks.
+        #[pallet::call_index(25)]
+        #[pallet::weight(Weight::default())]
+        pub fn create_auction(
+            origin: OriginFor<T>,
+            resource_id: ResourceId,
+            kind: AuctionKind,
+            reserve_price: BalanceOf<T>,
+            billing_period: BillingPeriodOf<T>,
+            commit_period: BlockNumberFor<T>,
+            reveal_period: BlockNumberFor<T>,
+        ) -> DispatchResult {
+            let provider = ensure_signed(origin)?;
+
+            let resource = Resources::<T>::get(resource_id).ok_or(Error::<T>::ResourceNotFound)?;
+            ensure!(resource.provider == provider, Error::<T>::NotResourceOwner);
+            ensure!(resource.is_available, Error::<T>::ResourceNotAvailable);
+            ensure!(resource.category.is_gpu(), Error::<T>::NotGpuResource);
+            ensure!(
+                Self::accepts_rentals(&provider),
+                Error::<T>::ProviderNotAcceptingRentals
+            );
+            ensure!(
+                !Auctions::<T>::contains_key(resource_id),
+                Error::<T>::AuctionAlreadyExists
+            );
+            ensure!(
+                Self::active_commitment(resource_id, frame_system::Pallet::<T>::block_number())
+                    .is_none(),
+                Error::<T>::ResourceCommitted
+            );
+
+            ensure!(
+                !billing_period.duration(T::BlocksPerWeek::get()).is_zero(),
+                Error::<T>::InvalidBillingPeriod
+            );
+            ensure!(
+                !commit_period.is_zero() && !reveal_period.is_zero(),
+                Error::<T>::InvalidAuctionPeriod
+            );
+
+            let current_block = frame_system::Pallet::<T>::block_number();
+            let commit_end = current_block.saturating_add(commit_period);
+            let reveal_end = commit_end.saturating_add(reveal_period);
+
+            AuctionEnds::<T>::try_mutate(reveal_end, |ending| ending.try_push(resource_id))
+                .map_err(|_| Error::<T>::TooManyEndingAuctions)?;
+
+            // The resource is only rented through the auction
+            Self::remove_ask(resource_id);
+
+            Auctions::<T>::insert(
+                resource_id,
+                Auction {
+                    kind,
+                    reserve_price,
+                    billing_period,
+                    commit_end,
+                    reveal_end,
+                    bid_count: 0,
+                    reveal_count: 0,
+                },
+            );
+
+            Self::deposit_event(Event::AuctionCreated {
+                resource_id,
+                kind,
+                reveal_end,
+            });
+
+            Ok(())
+        }
+
+        /// Commit to a bid with the hash of `(bid, salt)`. `AuctionBidDeposit` is held until
+        /// the auction is settled, the bid itself stays hidden until revealed.
+        #[pallet::call_index(26)]
+        #[pallet::weight(Weight::default())]
+        pub fn commit_bid(
+            origin: OriginFor<T>,
+            resource_id: ResourceId,
+            commitment: T::Hash,
+        ) -> DispatchResult {
+            let bidder = ensure_signed(origin)?;
+
+            let mut auction = Auctions::<T>::get(resource_id).ok_or(Error::<T>::AuctionNotFound)?;
+            let resource = Resources::<T>::get(resource_id).ok_or(Error::<T>::ResourceNotFound)?;
+            ensure!(resource.provider != bidder, Error::<T>::OwnAuction);
+            ensure!(
+                frame_system::Pallet::<T>::block_number() < auction.commit_end,
+                Error::<T>::BiddingClosed
+            );
+            ensure!(
+                !AuctionBids::<T>::contains_key(resource_id, &bidder),
+                Error::<T>::BidAlreadyCommitted
+            );
+            ensure!(
+                auction.bid_count < T::MaxAuctionBids::get(),
+                Error::<T>::TooManyBids
+            );
+
+            let deposit = T::AuctionBidDeposit::get();
+            T::Currency::reserve(&bidder, deposit)?;
+
+            AuctionBids::<T>::insert(
+                resource_id,
+                &bidder,
+                SealedBid {
+                    commitment,
+                    deposit,
+                    revealed: None,
+                },
+            );
+            auction.bid_count.saturating_inc();
+            Auctions::<T>::insert(resource_id, auction);
+
+            Self::deposit_event(Event::BidCommitted {
+                resource_id,
+                bidder,
+            });
+
+            Ok(())
+        }
+
+        /// Open a committed bid, only valid during the reveal phase
+        #[pallet::call_index(27)]
+        #[pallet::weight(Weight::default())]
+        pub fn reveal_bid(
+            origin: OriginFor<T>,
+            resource_id: ResourceId,
+            bid: BalanceOf<T>,
+            salt: T::Hash,
+        ) -> DispatchResult {
+            let bidder = ensure_signed(origin)?;
+
+            let mut auction = Auctions::<T>::get(resource_id).ok_or(Error::<T>::AuctionNotFound)?;
+            let current_block = frame_system::Pallet::<T>::block_number();
+            ensure!(
+                auction.commit_end <= current_block && current_block < auction.reveal_end,
+                Error::<T>::NotRevealPhase
+            );
+
+            let mut sealed =
+                AuctionBids::<T>::get(resource_id, &bidder).ok_or(Error::<T>::BidNotFound)?;
+            ensure!(
+                sealed.revealed.is_none() && sealed.commitment == T::Hashing::hash_of(&(bid, salt)),
+                Error::<T>::InvalidBidReveal
+            );
+            ensure!(
+                bid >= auction.reserve_price,
+                Error::<T>::BidBelowReservePrice
+            );
+
+            sealed.revealed = Some((bid, auction.reveal_count));
+            auction.reveal_count.saturating_inc();
+            AuctionBids::<T>::insert(resource_id, &bidder, sealed);
+            Auctions::<T>::insert(resource_id, auction);
+
+            Self::deposit_event(Event::BidRevealed {
+                resource_id,
+                bidder,
+                bid,
+            });
+
+            Ok(())
+        }
+
+        /// Register the total capacity of a host, rented in slices with `rent_slice` at the
+        /// weekly `prices` of each unit of capacity
+        #[pallet::call_index(28)]
+        #[pallet::weight(Weight::default())]
+        pub fn register_host(
+            origin: OriginFor<T>,
+            location: Region,
+            uptime_guarantee: u8,
+            capacity: HostCapacity,
+            gpu: Option<GPUSpecs>,
+            prices: SlicePrices<BalanceOf<T>>,
+        ) -> DispatchResult {
+            let provider = ensure_signed(origin)?;
+            ensure!(
+                Providers::<T>::contains_key(&provider),
+                Error::<T>::ProviderNotRegistered
+            );
+
+            ensure!(
+                Self::accepts_rentals(&provider),
+                Error::<T>::ProviderNotAcceptingRentals
+            );
+            ensure!(
+                capacity.vcpu > 0 && (capacity.gpu_count == 0 || gpu.is_some()),
+                Error::<T>::InvalidCapacity
+            );
+            let location = location
+                .normalized()
+                .ok_or(Error::<T>::InvalidCountryCode)?;
+
+            let host_id = NextHostId::<T>::get();
+            NextHostId::<T>::put(host_id.saturating_add(1));
+
+            Hosts::<T>::insert(
+                host_id,
+                Host {
+                    provider: provider.clone(),
+                    location,
+                    uptime_guarantee,
+                    gpu,
+                    capacity: capacity.clone(),
+                    remaining: capacity,
+                    prices,
+                },
+            );
+
+            ProviderHosts::<T>::insert(&provider, host_id, ());
+
+            Self::deposit_event(Event::HostRegistered { host_id, provider });
+
+            Ok(())
+        }
+
+        /// Rent a slice of a host, priced by its capacity. The capacity is allocated to the
+        /// rental and returned to the host when the rental ends.
+        #[pallet::call_index(29)]
+        #[pallet::weight(Weight::default())]
+        pub fn rent_slice(
+            origin: OriginFor<T>,
+            host_id: HostId,
+            slice: HostCapacity,
+            billing_period: BillingPeriodOf<T>,
+        ) -> DispatchResult {
+            let renter = ensure_signed(origin)?;
+
+            let mut host = Hosts::<T>::get(host_id).ok_or(Error::<T>::HostNotFound)?;
+            ensure!(
+                slice.vcpu > 0 && !slice.is_empty(),
+                Error::<T>::InvalidCapacity
+            );
+            ensure!(
+                host.remaining.contains(&slice),
+                Error::<T>::InsufficientCapacity
+            );
+            ensure!(host.slice(&slice).is_some(), Error::<T>::InvalidCapacity);
+
+            // The allocation takes a resource id, the rental being kept like any other
+            let resource_id = NextResourceId::<T>::get();
+            let next_resource_id = resource_id
+                .checked_add(1)
+                .ok_or(Error::<T>::ResourceIdOverflow)?;
+            NextResourceId::<T>::put(next_resource_id);
+
+            let weekly_price = host.prices.price(&slice);
+            host.remaining = host.remaining.saturating_sub(&slice);
+            Hosts::<T>::insert(host_id, host);
+            Allocations::<T>::insert(resource_id, (host_id, slice));
+            HostSlices::<T>::insert(host_id, resource_id, ());
+
+            Self::deposit_event(Event::SliceAllocated {
+                host_id,
+                resource_id,
+            });
+
+            Self::do_rent_resource(renter, resource_id, billing_period, Some(weekly_price))
+        }
+
+        /// Attach a deployment manifest to a rental, or replace it with a new version
+        #[pallet::call_index(30)]
+        #[pallet::weight(Weight::default())]
+        pub fn update_deployment(
+            origin: OriginFor<T>,
+            resource_id: ResourceId,
+            manifest: DeploymentManifestOf<T>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let rental = Rentals::<T>::get(resource_id).ok_or(Error::<T>::RentalNotFound)?;
+            Self::ensure_acting_for(&who, &rental.renter, OrgRole::Deployer)?;
+
+            if let DeploymentManifest::Container(container) = &manifest {
+                ensure!(!container.image.is_empty(), Error::<T>::InvalidManifest);
+
+                let resource = Self::resource(resource_id).ok_or(Error::<T>::ResourceNotFound)?;
+                ensure!(
+                    resource.category.satisfies(&container.requests),
+                    Error::<T>::ManifestExceedsResource
+                );
+            }
+
+            let version = Deployments::<T>::get(resource_id)
+                .map_or(1, |deployment| deployment.version.saturating_add(1));
+
+            Deployments::<T>::insert(
+                resource_id,
+                Deployment {
+                    manifest: manifest.clone(),
+                    version,
+                    updated_at: frame_system::Pallet::<T>::block_number(),
+                },
+            );
+
+            Self::deposit_event(Event::DeploymentUpdated {
+                resource_id,
+                version,
+                manifest,
+            });
+
+            Ok(())
+        }
+
+        /// Register the key access credentials are encrypted to. After a rotation the posted
+        /// bundle stays encrypted to its own `key` until the provider posts a new one.
+        #[pallet::call_index(31)]
+        #[pallet::weight(Weight::default())]
+        pub fn set_access_key(
+            origin: OriginFor<T>,
+            resource_id: ResourceId,
+            key: AccessKey,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let rental = Rentals::<T>::get(resource_id).ok_or(Error::<T>::RentalNotFound)?;
+            Self::ensure_acting_for(&who, &rental.renter, OrgRole::Deployer)?;
+
+            AccessKeys::<T>::insert(resource_id, key.clone());
+
+            Self::deposit_event(Event::AccessKeySet { resource_id, key });
+
+            Ok(())
+        }
+
+        /// Post or rotate the access credentials of a rental, encrypted to the renter key
+        #[pallet::call_index(32)]
+        #[pallet::weight(Weight::default())]
+        pub fn post_access_bundle(
+            origin: OriginFor<T>,
+            resource_id: ResourceId,
+            ciphertext: EncryptedBundle,
+        ) -> DispatchResult {
+            let provider = ensure_signed(origin)?;
+
+            let resource = Self::resource(resource_id).ok_or(Error::<T>::ResourceNotFound)?;
+            ensure!(resource.provider == provider, Error::<T>::NotResourceOwner);
+            ensure!(
+                Rentals::<T>::contains_key(resource_id),
+                Error::<T>::RentalNotFound
+            );
+            ensure!(!ciphertext.is_empty(), Error::<T>::EmptyAccessBundle);
+
+            let key = AccessKeys::<T>::get(resource_id).ok_or(Error::<T>::AccessKeyNotSet)?;
+            let version = AccessBundles::<T>::get(resource_id)
+                .map_or(1, |bundle| bundle.version.saturating_add(1));
+
+            AccessBundles::<T>::insert(
+                resource_id,
+                AccessBundle {
+                    key,
+                    ciphertext,
+                    version,
+                    posted_at: frame_system::Pallet::<T>::block_number(),
+                },
+            );
+
+            Self::deposit_event(Event::AccessBundlePosted {
+                resource_id,
+                version,
+            });
+
+            Ok(())
+        }
+
+        /// Liveness signal of the provider agent
+        #[pallet::call_index(33)]
+        #[pallet::weight(Weight::default())]
+        pub fn heartbeat(origin: OriginFor<T>) -> DispatchResult {
+            let provider = ensure_signed(origin)?;
+            ensure!(
+                Providers::<T>::contains_key(&provider),
+                Error::<T>::ProviderNotRegistered
+            );
+
+            Heartbeats::<T>::insert(&provider, frame_system::Pallet::<T>::block_number());
+
+            Ok(())
+        }
+
+        /// Resources found healthy by the provider, submitted by the offchain worker
+        #[pallet::call_index(34)]
+        #[pallet::weight(Weight::default())]
+        pub fn report_health(
+            origin: OriginFor<T>,
+            resource_ids: BoundedVec<ResourceId, T::MaxHeartbeatResources>,
+        ) -> DispatchResult {
+            let provider = ensure_signed(origin)?;
+            ensure!(
+                Providers::<T>::contains_key(&provider),
+                Error::<T>::ProviderNotRegistered
+            );
+
+            let current_block = frame_system::Pallet::<T>::block_number();
+
+            for resource_id in &resource_ids {
+                let resource = Self::resource(*resource_id).ok_or(Error::<T>::ResourceNotFound)?;
+                ensure!(resource.provider == provider, Error::<T>::NotResourceOwner);
+
+                ResourceHeartbeats::<T>::insert(resource_id, current_block);
+            }
+            Heartbeats::<T>::insert(&provider, current_block);
+
+            Self::deposit_event(Event::HealthReported {
+                provider,
+                healthy: resource_ids.len() as u32,
+            });
+
+            Ok(())
+        }
+
+        /// Stake `WatcherStake` to be assigned rented resources to probe from the next epoch
+        #[pallet::call_index(35)]
+        #[pallet::weight(Weight::default())]
+        pub fn join_watchers(origin: OriginFor<T>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(
+                !Watchers::<T>::contains_key(&who),
+                Error::<T>::WatcherAlreadyRegistered
+            );
+
+            WatcherSet::<T>::try_append(&who).map_err(|_| Error::<T>::TooManyWatchers)?;
+
+            let stake = T::WatcherStake::get();
+            T::Currency::reserve(&who, stake)?;
+            Watchers::<T>::insert(
+                &who,
+                Watcher {
+                    stake,
+                    leaving: None,
+                    pending: 0,
+                },
+            );
+
+            Self::deposit_event(Event::WatcherJoined { who });
+
+            Ok(())
+        }
+
+        /// Leave the watcher set, the current assignments must still be attested
+        #[pallet::call_index(36)]
+        #[pallet::weight(Weight::default())]
+        pub fn leave_watchers(origin: OriginFor<T>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            let epoch = Self::epoch_at(frame_system::Pallet::<T>::block_number());
+
+            Watchers::<T>::try_mutate(&who, |watcher| -> DispatchResult {
+                let watcher = watcher.as_mut().ok_or(Error::<T>::NotWatcher)?;
+                ensure!(watcher.leaving.is_none(), Error::<T>::WatcherLeaving);
+                watcher.leaving = Some(epoch);
+                Ok(())
+            })?;
+            WatcherSet::<T>::mutate(|watchers| watchers.retain(|w| w != &who));
+
+            Self::deposit_event(Event::WatcherLeft { who, epoch });
+
+            Ok(())
+        }
+
+        /// Unreserve what is left of the stake, a full epoch after leaving and once the last
+        /// attestations are finalized
+        #[pallet::call_index(37)]
+        #[pallet::weight(Weight::default())]
+        pub fn withdraw_watcher_stake(origin: OriginFor<T>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            let watcher = Watchers::<T>::get(&who).ok_or(Error::<T>::NotWatcher)?;
+            let leaving = watcher.leaving.ok_or(Error::<T>::WatcherStillBonded)?;
+            ensure!(
+                Self::epoch_at(frame_system::Pallet::<T>::block_number())
+                    >= leaving.saturating_add(2),
+                Error::<T>::WatcherStillBonded
+            );
+            ensure!(watcher.pending == 0, Error::<T>::WatcherStillBonded);
+
+            T::Currency::unreserve(&who, watcher.stake);
+            Watchers::<T>::remove(&who);
+
+            Self::deposit_event(Event::WatcherStakeWithdrawn {
+                who,
+                amount: watcher.stake,
+            });
+
+            Ok(())
+        }
+
+        /// Liveness of the public endpoint of a rented resource, probed by one of the
+        /// watchers assigned to it for the current epoch
+        #[pallet::call_index(38)]
+        #[pallet::weight(Weight::default())]
+        pub fn attest_liveness(
+            origin: OriginFor<T>,
+            resource_id: ResourceId,
+            alive: bool,
+        ) -> DispatchResult {
+            let watcher = ensure_signed(origin)?;
+            ensure!(
+                Rentals::<T>::contains_key(resource_id),
+                Error::<T>::RentalNotFound
+            );
+
+            let epoch = Self::epoch_at(frame_system::Pallet::<T>::block_number());
+            ensure!(
+                WatchEpoch::<T>::get().is_some_and(|(current, ..)| current == epoch),
+                Error::<T>::EpochNotStarted
+            );
+            ensure!(
+                Self::assigned_watchers(resource_id).contains(&watcher),
+                Error::<T>::NotAssignedWatcher
+            );
+            ensure!(
+                !Attestations::<T>::contains_key((epoch, resource_id), &watcher),
+                Error::<T>::AlreadyAttested
+            );
+
+            Attestations::<T>::insert((epoch, resource_id), &watcher, alive);
+            Watchers::<T>::mutate(&watcher, |w| {
+                if let Some(w) = w {
+                    w.pending.saturating_inc();
+                }
+            });
+            LivenessTallies::<T>::mutate(epoch, resource_id, |tally| {
+                if alive {
+                    tally.alive.saturating_inc();
+                } else {
+                    tally.down.saturating_inc();
+                }
+            });
+
+            Self::deposit_event(Event::LivenessAttested {
+                epoch,
+                resource_id,
+                watcher,
+                alive,
+            });
+
+            Ok(())
+        }
+
+        /// Aggregate the attestations of an epoch once it is over. Watchers agreeing with the
+        /// majority are rewarded from the watcher pot, the others are slashed into it.
+        /// Epochs nobody finalizes are finalized on idle after another full epoch.
+        #[pallet::call_index(39)]
+        #[pallet::weight(Weight::default())]
+        pub fn finalize_liveness(
+            origin: OriginFor<T>,
+            epoch: EpochIndex,
+            resource_id: ResourceId,
+        ) -> DispatchResult {
+            ensure_signed(origin)?;
+            ensure!(
+                epoch < Self::epoch_at(frame_system::Pallet::<T>::block_number()),
+                Error::<T>::EpochNotOver
+            );
+            ensure!(
+                LivenessTallies::<T>::contains_key(epoch, resource_id),
+                Error::<T>::NoAttestations
+            );
+
+            Self::do_finalize_liveness(epoch, resource_id);
+
+            Ok(())
+        }
+
+        #[pallet::call_index(40)]
+        #[pallet::weight(Weight::default())]
+        pub fn add_auditor(origin: OriginFor<T>, who: T::AccountId) -> DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
+            ensure!(
+                !Auditors::<T>::contains_key(&who),
+                Error::<T>::AuditorAlreadyAdded
+            );
+
+            Auditors::<T>::insert(&who, ());
+
+            Self::deposit_event(Event::AuditorAdded { who });
+
+            Ok(())
+        }
+
+        #[pallet::call_index(41)]
+        #[pallet::weight(Weight::default())]
+        pub fn remove_auditor(origin: OriginFor<T>, who: T::AccountId) -> DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
+            Auditors::<T>::take(&who).ok_or(Error::<T>::AuditorNotFound)?;
+
+            Self::deposit_event(Event::AuditorRemoved { who });
+
+            Ok(())
+        }
+
+        /// Benchmark result of a resource, submitted by an auditor or an active watcher. The
+        /// resource is verified when the measured specs back the claimed category. Watchers
+        /// only attest once `HardwareQuorum` of them agree, and a failed result is final.
+        #[pallet::call_index(42)]
+        #[pallet::weight(Weight::default())]
+        pub fn attest_hardware(
+            origin: OriginFor<T>,
+            resource_id: ResourceId,
+            benchmark: T::Hash,
+            measured: CustomResourceSpecs,
+        ) -> DispatchResult {
+            let verifier = ensure_signed(origin)?;
+            let resource = Resources::<T>::get(resource_id).ok_or(Error::<T>::ResourceNotFound)?;
+            ensure!(resource.provider != verifier, Error::<T>::NotVerifier);
+
+            let verified = resource.category.is_backed_by(&measured);
+            let Some(verifier) = Self::vote_hardware(
+                AttestationTarget::Resource(resource_id),
+                HardwareAttestations::<T>::get(resource_id),
+                verifier,
+                verified,
+            )?
+            else {
+                return Ok(());
+            };
+
+            HardwareAttestations::<T>::insert(
+                resource_id,
+                HardwareAttestation {
+                    verifier: verifier.clone(),
+                    benchmark,
+                    measured,
+                    verified,
+                    attested_at: frame_system::Pallet::<T>::block_number(),
+                },
+            );
+
+            Self::deposit_event(Event::HardwareAttested {
+                resource_id,
+                verifier,
+                benchmark,
+                verified,
+            });
+
+            Ok(())
+        }
+
+        /// Allow or disallow rentals of unverified resources, slices of unverified hosts included
+        #[pallet::call_index(43)]
+        #[pallet::weight(Weight::default())]
+        pub fn set_verification_required(origin: OriginFor<T>, required: bool) -> DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
+
+            VerificationRequired::<T>::put(required);
+
+            Self::deposit_event(Event::VerificationRequirementSet { required });
+
+            Ok(())
+        }
+
+        /// Replace the name and website of the provider
+        #[pallet::call_index(44)]
+        #[pallet::weight(Weight::default())]
+        pub fn update_provider_info(
+            origin: OriginFor<T>,
+            name: ProviderName,
+            website: Option<Website>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            Self::ensure_valid_info(&name, &website)?;
+
+            Providers::<T>::try_mutate(&who, |maybe_provider| -> DispatchResult {
+                let provider = maybe_provider
+                    .as_mut()
+                    .ok_or(Error::<T>::ProviderNotRegistered)?;
+
+                provider.name = name.clone();
+                provider.website = website.clone();
+                provider.last_updated = frame_system::Pallet::<T>::block_number();
+
+                Ok(())
+            })?;
+
+            Self::deposit_event(Event::ProviderInfoUpdated { who, name, website });
+
+            Ok(())
+        }
+
+        /// Get the verified provider status, backed by the judged on-chain identity of the
+        /// provider account
+        #[pallet::call_index(45)]
+        #[pallet::weight(Weight::default())]
+        pub fn link_identity(origin: OriginFor<T>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(
+                Providers::<T>::contains_key(&who),
+                Error::<T>::ProviderNotRegistered
+            );
+            ensure!(
+                T::Identity::is_verified(&who),
+                Error::<T>::IdentityNotVerified
+            );
+
+            VerifiedProviders::<T>::insert(&who, frame_system::Pallet::<T>::block_number());
+
+            Self::deposit_event(Event::ProviderVerified { who });
+
+            Ok(())
+        }
+
+        /// Drop the verified status of a provider, by the provider itself or by anyone once
+        /// its identity lost its judgement
+        #[pallet::call_index(46)]
+        #[pallet::weight(Weight::default())]
+        pub fn revoke_provider_verification(
+            origin: OriginFor<T>,
+            provider: T::AccountId,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(
+                VerifiedProviders::<T>::contains_key(&provider),
+                Error::<T>::ProviderNotVerified
+            );
+            ensure!(
+                who == provider || !T::Identity::is_verified(&provider),
+                Error::<T>::IdentityStillVerified
+            );
+
+            VerifiedProviders::<T>::remove(&provider);
+
+            Self::deposit_event(Event::ProviderVerificationRevoked { who: provider });
+
+            Ok(())
+        }
+
+        /// Stop taking new rentals and start unbonding. Ongoing rentals are ended and settled
+        /// pro rata, the bond stays reserved until they can't be disputed anymore.
+        #[pallet::call_index(47)]
+        #[pallet::weight(Weight::default())]
+        pub fn deregister_provider(origin: OriginFor<T>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            let current_block = frame_system::Pallet::<T>::block_number();
+
+            Providers::<T>::try_mutate(&who, |maybe_provider| -> DispatchResult {
+                let provider = maybe_provider
+                    .as_mut()
+                    .ok_or(Error::<T>::ProviderNotRegistered)?;
+                match provider.status {
+                    ProviderStatus::Deregistering => {
+                        return Err(Error::<T>::ProviderDeregistering.into())
+                    }
+                    ProviderStatus::Suspended | ProviderStatus::Terminated => {
+                        return Err(Error::<T>::StatusReservedForGovernance.into())
+                    }
+                    ProviderStatus::Active | ProviderStatus::Inactive => {}
+                }
+
+                provider.status = ProviderStatus::Deregistering;
+                provider.last_updated = current_block;
+
+                Ok(())
+            })?;
+
+            let mut refunded_rentals = 0u32;
+            for resource_id in Self::rentable_ids(&who) {
+                if let Some(rental) = Rentals::<T>::get(resource_id) {
+                    ensure!(
+                        !ResourceDisputes::<T>::contains_key(resource_id),
+                        Error::<T>::EscrowFrozen
+                    );
+                    // The provider is leaving, the renter owes no early termination penalty
+                    Commitments::<T>::remove(resource_id);
+                    Self::do_cancel_rental(resource_id, &rental)?;
+
+                    Self::deposit_event(Event::RentalCancelled {
+                        resource_id,
+                        renter: rental.renter,
+                    });
+                    refunded_rentals += 1;
+                }
+
+                // Delisted from the asks and the country index
+                Self::remove_ask(resource_id);
+                if let Some(resource) = Resources::<T>::get(resource_id) {
+                    Self::unindex_resource(resource_id, &resource);
+                }
+            }
+
+            // The rentals just ended stay disputable until the bond is released
+            let unbonded_at = current_block
+                .saturating_add(T::ProviderUnbondingPeriod::get().max(T::DisputeWindow::get()));
+            ProviderUnbonding::<T>::insert(&who, unbonded_at);
+
+            Self::deposit_event(Event::ProviderDeregistering {
+                who,
+                unbonded_at,
+                refunded_rentals,
+            });
+
+            Ok(())
+        }
+
+        /// Once unbonded and without open disputes, delete the resources and hosts of the
+        /// provider and release its bond
+        #[pallet::call_index(48)]
+        #[pallet::weight(Weight::default())]
+        pub fn finalize_deregistration(origin: OriginFor<T>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            let unbonded_at =
+                ProviderUnbonding::<T>::get(&who).ok_or(Error::<T>::ProviderNotDeregistering)?;
+            ensure!(
+                frame_system::Pallet::<T>::block_number() >= unbonded_at,
+                Error::<T>::ProviderStillUnbonding
+            );
+
+            // The arbiter may still slash the bond
+            ensure!(
+                ProviderDisputes::<T>::get(&who) == 0,
+                Error::<T>::EscrowFrozen
+            );
+
+            let resource_ids: Vec<_> = ProviderResources::<T>::iter_key_prefix(&who).collect();
+            for resource_id in resource_ids {
+                Self::purge_resource(resource_id)?;
+            }
+            let host_ids: Vec<_> = ProviderHosts::<T>::iter_key_prefix(&who).collect();
+            for host_id in host_ids {
+                Self::purge_host(host_id)?;
+            }
+
+            T::Currency::unreserve(&who, ProviderBonds::<T>::take(&who));
+            Providers::<T>::remove(&who);
+            ProviderUnbonding::<T>::remove(&who);
+            VerifiedProviders::<T>::remove(&who);
+            Heartbeats::<T>::remove(&who);
+            RateCards::<T>::remove(&who);
+
+            Self::deposit_event(Event::ProviderDeregistered { who });
+
+            Ok(())
+        }
+
+        /// Suspend a provider until `expires_at`, or until lifted if `None`. Its rentals are
+        /// ended and refunded in full, each renter is also compensated with one billing period
+        /// from the bond. A pending deregistration is cancelled.
+        #[pallet::call_index(49)]
+        #[pallet::weight(Weight::default())]
+        pub fn suspend_provider(
+            origin: OriginFor<T>,
+            provider: T::AccountId,
+            reason: SanctionReason,
+            expires_at: Option<BlockNumberFor<T>>,
+        ) -> DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
+            let current_block = frame_system::Pallet::<T>::block_number();
+
+            if let Some(expires_at) = expires_at {
+                ensure!(
+                    expires_at > current_block,
+                    Error::<T>::InvalidSuspensionExpiry
+                );
+                SuspensionExpiries::<T>::try_mutate(expires_at, |expiring| {
+                    expiring.try_push(provider.clone())
+                })
+                .map_err(|_| Error::<T>::TooManyExpiringSuspensions)?;
+            }
+
+            Providers::<T>::try_mutate(&provider, |maybe_provider| -> DispatchResult {
+                let info = maybe_provider
+                    .as_mut()
+                    .ok_or(Error::<T>::ProviderNotRegistered)?;
+                ensure!(
+                    info.status != ProviderStatus::Terminated,
+                    Error::<T>::ProviderTerminated
+                );
+
+                info.status = ProviderStatus::Suspended;
+                info.last_updated = current_block;
+
+                Ok(())
+            })?;
+
+            // Replaces a previous suspension, its expiry is then skipped
+            Suspensions::<T>::insert(
+                &provider,
+                Suspension {
+                    reason,
+                    suspended_at: current_block,
+                    expires_at,
+                },
+            );
+            ProviderUnbonding::<T>::remove(&provider);
+
+            let refunded_rentals = Self::refund_renters(&provider);
+            for resource_id in ProviderResources::<T>::iter_key_prefix(&provider) {
+                Self::remove_ask(resource_id);
+                if let Some(resource) = Resources::<T>::get(resource_id) {
+                    Self::unindex_resource(resource_id, &resource);
+                }
+            }
+
+            Self::deposit_event(Event::ProviderSuspended {
+                who: provider,
+                reason,
+                expires_at,
+                refunded_rentals,
+            });
+
+            Ok(())
+        }
+
+        /// Lift a suspension before its expiry. The provider comes back as inactive.
+        #[pallet::call_index(50)]
+        #[pallet::weight(Weight::default())]
+        pub fn lift_suspension(origin: OriginFor<T>, provider: T::AccountId) -> DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
+
+            let suspension =
+                Suspensions::<T>::get(&provider).ok_or(Error::<T>::ProviderNotSuspended)?;
+
+            // Drop the scheduled expiry
+            if let Some(expires_at) = suspension.expires_at {
+                SuspensionExpiries::<T>::mutate(expires_at, |expiring| {
+                    expiring.retain(|who| who != &provider)
+                });
+            }
+
+            Self::do_lift_suspension(&provider);
+
+            Self::deposit_event(Event::SuspensionLifted { who: provider });
+
+            Ok(())
+        }
+
+        /// Permanently remove a provider. Its rentals are refunded as for a suspension, its
+        /// resources and hosts are deleted and the rest of its bond is slashed. The account
+        /// stays registered as terminated so that it can't register again. Open disputes on its
+        /// rentals have to be resolved first.
+        #[pallet::call_index(51)]
+        #[pallet::weight(Weight::default())]
+        pub fn terminate_provider(
+            origin: OriginFor<T>,
+            provider: T::AccountId,
+            reason: SanctionReason,
+        ) -> DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
+            let current_block = frame_system::Pallet::<T>::block_number();
+
+            // The arbiter may still slash the bond
+            ensure!(
+                ProviderDisputes::<T>::get(&provider) == 0,
+                Error::<T>::EscrowFrozen
+            );
+
+            Providers::<T>::try_mutate(&provider, |maybe_provider| -> DispatchResult {
+                let info = maybe_provider
+                    .as_mut()
+                    .ok_or(Error::<T>::ProviderNotRegistered)?;
+                ensure!(
+                    info.status != ProviderStatus::Terminated,
+                    Error::<T>::ProviderTerminated
+                );
+
+                info.status = ProviderStatus::Terminated;
+                info.last_updated = current_block;
+
+                Ok(())
+            })?;
+
+            // An expiry still scheduled is skipped once the suspension is gone
+            Suspensions::<T>::remove(&provider);
+            ProviderUnbonding::<T>::remove(&provider);
+
+            let refunded_rentals = Self::refund_renters(&provider);
+            let resource_ids: Vec<_> = ProviderResources::<T>::iter_key_prefix(&provider).collect();
+            for resource_id in resource_ids {
+                Self::purge_resource(resource_id)?;
+            }
+            let host_ids: Vec<_> = ProviderHosts::<T>::iter_key_prefix(&provider).collect();
+            for host_id in host_ids {
+                Self::purge_host(host_id)?;
+            }
+
+            let bond = ProviderBonds::<T>::take(&provider);
+            let (_, unslashed) = T::Currency::slash_reserved(&provider, bond);
+            let forfeited = bond.saturating_sub(unslashed);
+            VerifiedProviders::<T>::remove(&provider);
+            Heartbeats::<T>::remove(&provider);
+            RateCards::<T>::remove(&provider);
+
+            Self::deposit_event(Event::ProviderTerminated {
+                who: provider,
+                reason,
+                refunded_rentals,
+                forfeited,
+            });
+
+            Ok(())
+        }
+
+        /// Create an organisation administered by the caller, reserving `OrganisationDeposit`.
+        /// Its treasury account is funded with plain transfers and pays for the rentals of the
+        /// organisation.
+        #[pallet::call_index(52)]
+        #[pallet::weight(Weight::default())]
+        pub fn create_organisation(origin: OriginFor<T>) -> DispatchResult {
+            let admin = ensure_signed(origin)?;
+
+            let org_id = NextOrganisationId::<T>::get();
+            let next_org_id = org_id
+                .checked_add(1)
+                .ok_or(Error::<T>::OrganisationIdOverflow)?;
+
+            let deposit = T::OrganisationDeposit::get();
+            T::Currency::reserve(&admin, deposit)?;
+            NextOrganisationId::<T>::put(next_org_id);
+
+            let account = Self::organisation_account(org_id);
+            Organisations::<T>::insert(
+                org_id,
+                Organisation {
+                    account: account.clone(),
+                    members: 1,
+                    depositor: admin.clone(),
+                    deposit,
+                },
+            );
+            OrganisationMembers::<T>::insert(org_id, &admin, OrgRole::Admin);
+            OrganisationAccounts::<T>::insert(&account, org_id);
+
+            Self::deposit_event(Event::OrganisationCreated {
+                org_id,
+                account,
+                admin,
+            });
+
+            Ok(())
+        }
+
+        /// Add a member to an organisation or change its role
+        #[pallet::call_index(53)]
+        #[pallet::weight(Weight::default())]
+        pub fn set_member_role(
+            origin: OriginFor<T>,
+            org_id: OrganisationId,
+            member: T::AccountId,
+            role: OrgRole,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            Self::ensure_org_role(org_id, &who, OrgRole::Admin)?;
+
+            match OrganisationMembers::<T>::get(org_id, &member) {
+                Some(current) => {
+                    if current == OrgRole::Admin && role != OrgRole::Admin {
+                        Self::ensure_other_admin(org_id, &member)?;
+                    }
+                }
+                None => Organisations::<T>::try_mutate(org_id, |maybe_org| -> DispatchResult {
+                    let org = maybe_org.as_mut().ok_or(Error::<T>::OrganisationNotFound)?;
+                    ensure!(
+                        org.members < T::MaxOrganisationMembers::get(),
+                        Error::<T>::TooManyOrganisationMembers
+                    );
+                    org.members += 1;
+                    Ok(())
+                })?,
+            }
+
+            OrganisationMembers::<T>::insert(org_id, &member, role);
+
+            Self::deposit_event(Event::MemberRoleSet {
+                org_id,
+                member,
+                role,
+            });
+
+            Ok(())
+        }
+
+        /// Remove a member from an organisation, by an admin or the member itself. The last
+        /// admin can't leave.
+        #[pallet::call_index(54)]
+        #[pallet::weight(Weight::default())]
+        pub fn remove_member(
+            origin: OriginFor<T>,
+            org_id: OrganisationId,
+            member: T::AccountId,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            if who != member {
+                Self::ensure_org_role(org_id, &who, OrgRole::Admin)?;
+            }
+
+            let role = OrganisationMembers::<T>::get(org_id, &member)
+                .ok_or(Error::<T>::NotOrganisationMember)?;
+            if role == OrgRole::Admin {
+                Self::ensure_other_admin(org_id, &member)?;
+            }
+
+            OrganisationMembers::<T>::remove(org_id, &member);
+            Organisations::<T>::mutate(org_id, |maybe_org| {
+                if let Some(org) = maybe_org {
+                    org.members = org.members.saturating_sub(1);
+                }
+            });
+
+            Self::deposit_event(Event::MemberRemoved { org_id, member });
+
+            Ok(())
+        }
+
+        /// Rent a resource paid by the treasury of an organisation. The rental is owned by the
+        /// organisation and managed by its members according to their role.
+        #[pallet::call_index(55)]
+        #[pallet::weight(Weight::default())]
+        pub fn rent_for_organisation(
+            origin: OriginFor<T>,
+            org_id: OrganisationId,
+            resource_id: ResourceId,
+            billing_period: BillingPeriodOf<T>,
+            commitment: Option<CommitmentTerm>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            Self::ensure_org_role(org_id, &who, OrgRole::Billing)?;
+
+            let org = Organisations::<T>::get(org_id).ok_or(Error::<T>::OrganisationNotFound)?;
+
+            Self::do_rent_committed(org.account, resource_id, billing_period, commitment)
+        }
+
+        /// Move funds out of the treasury of an organisation, admins only
+        #[pallet::call_index(56)]
+        #[pallet::weight(Weight::default())]
+        pub fn withdraw_organisation_funds(
+            origin: OriginFor<T>,
+            org_id: OrganisationId,
+            beneficiary: T::AccountId,
+            amount: BalanceOf<T>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            Self::ensure_org_role(org_id, &who, OrgRole::Admin)?;
+
+            let org = Organisations::<T>::get(org_id).ok_or(Error::<T>::OrganisationNotFound)?;
+
+            T::Currency::transfer(
+                &org.account,
+                &beneficiary,
+                amount,
+                ExistenceRequirement::KeepAlive,
+            )?;
+
+            Self::deposit_event(Event::OrganisationFundsWithdrawn {
+                org_id,
+                beneficiary,
+                amount,
+            });
+
+            Ok(())
+        }
+
+        /// Cap what the caller spends on rentals and top-ups per period, or remove the cap.
+        /// What was spent in the current period still counts against a new cap. Only allowed
+        /// while the caller has no budget controller.
+        #[pallet::call_index(57)]
+        #[pallet::weight(Weight::default())]
+        pub fn set_budget(origin: OriginFor<T>, budget: Option<BudgetOf<T>>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            Self::ensure_budget_controller(&who, &who)?;
+
+            Self::do_set_budget(who, budget)
+        }
+
+        /// Cap what the treasury of an organisation spends per period, by one of its admins
+        #[pallet::call_index(58)]
+        #[pallet::weight(Weight::default())]
+        pub fn set_organisation_budget(
+            origin: OriginFor<T>,
+            org_id: OrganisationId,
+            budget: Option<BudgetOf<T>>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            Self::ensure_org_role(org_id, &who, OrgRole::Admin)?;
+
+            let org = Organisations::<T>::get(org_id).ok_or(Error::<T>::OrganisationNotFound)?;
+
+            Self::do_set_budget(org.account, budget)
+        }
+
+        /// Hand the budget of `who` over to `controller`, or back to `who`. Called by `who`
+        /// itself, or by its current controller once there is one.
+        #[pallet::call_index(59)]
+        #[pallet::weight(Weight::default())]
+        pub fn set_budget_controller(
+            origin: OriginFor<T>,
+            who: T::AccountId,
+            controller: Option<T::AccountId>,
+        ) -> DispatchResult {
+            let caller = ensure_signed(origin)?;
+            Self::ensure_budget_controller(&caller, &who)?;
+
+            BudgetControllers::<T>::set(&who, controller.clone());
+
+            Self::deposit_event(Event::BudgetControllerSet { who, controller });
+
+            Ok(())
+        }
+
+        /// Cap what `who` spends per period, by its budget controller
+        #[pallet::call_index(60)]
+        #[pallet::weight(Weight::default())]
+        pub fn set_controlled_budget(
+            origin: OriginFor<T>,
+            who: T::AccountId,
+            budget: Option<BudgetOf<T>>,
+        ) -> DispatchResult {
+            let controller = ensure_signed(origin)?;
+            ensure!(
+                BudgetControllers::<T>::get(&who).as_ref() == Some(&controller),
+                Error::<T>::NotBudgetController
+            );
+
+            Self::do_set_budget(who, budget)
+        }
+
+        /// Settle held usage of an ended rental once its dispute window is over: the provider
+        /// is paid unless the renter disputed it, in which case it is refunded
+        #[pallet::call_index(61)]
+        #[pallet::weight(Weight::default())]
+        pub fn settle_held_usage(
+            origin: OriginFor<T>,
+            resource_id: ResourceId,
+            report_id: UsageReportId,
+        ) -> DispatchResult {
+            ensure_signed(origin)?;
+
+            let held = HeldUsages::<T>::get(resource_id, report_id)
+                .ok_or(Error::<T>::UsageReportNotFound)?;
+
+            let current_block = frame_system::Pallet::<T>::block_number();
+            ensure!(
+                current_block
+                    >= held
+                        .record
+                        .submitted_at
+                        .saturating_add(T::UsageDisputeWindow::get()),
+                Error::<T>::UsageDisputeWindowOpen
+            );
+
+            HeldUsages::<T>::remove(resource_id, report_id);
+
+            let (paid, refunded) = if held.record.disputed {
+                T::Currency::transfer(
+                    &Self::escrow_account(),
+                    &held.renter,
+                    held.held,
+                    ExistenceRequirement::AllowDeath,
+                )?;
+                (Zero::zero(), held.held)
+            } else {
+                Self::pay_provider(&held.provider, held.held)?;
+                Self::incur_usage_debt(
+                    &held.renter,
+                    &held.provider,
+                    held.record.amount.saturating_sub(held.held),
+                );
+                (held.held, Zero::zero())
+            };
+
+            Self::deposit_event(Event::HeldUsageSettled {
+                resource_id,
+                report_id,
+                paid,
+                refunded,
+            });
+
+            Ok(())
+        }
+
+        /// Pay the usage owed to a provider, renting again requires all usage debts paid
+        #[pallet::call_index(62)]
+        #[pallet::weight(Weight::default())]
+        pub fn pay_usage_debt(
+            origin: OriginFor<T>,
+            renter: T::AccountId,
+            provider: T::AccountId,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            Self::ensure_acting_for(&who, &renter, OrgRole::Billing)?;
+
+            let amount = UsageDebts::<T>::take(&renter, &provider);
+            ensure!(!amount.is_zero(), Error::<T>::NoUsageDebt);
+
+            // Paid through the escrow, the watcher fee applies as to any payment
+            T::Currency::transfer(
+                &renter,
+                &Self::escrow_account(),
+                amount,
+                ExistenceRequirement::KeepAlive,
+            )?;
+            Self::pay_provider(&provider, amount)?;
+
+            Self::deposit_event(Event::UsageDebtPaid {
+                renter,
+                provider,
+                amount,
+            });
+
+            Ok(())
+        }
+
+        /// Benchmark result of a whole host, attested like `attest_hardware`. The slices of
+        /// the host are verified along.
+        #[pallet::call_index(63)]
+        #[pallet::weight(Weight::default())]
+        pub fn attest_host(
+            origin: OriginFor<T>,
+            host_id: HostId,
+            benchmark: T::Hash,
+            measured: CustomResourceSpecs,
+        ) -> DispatchResult {
+            let verifier = ensure_signed(origin)?;
+            let host = Hosts::<T>::get(host_id).ok_or(Error::<T>::HostNotFound)?;
+            ensure!(host.provider != verifier, Error::<T>::NotVerifier);
+
+            let whole = host
+                .slice(&host.capacity)
+                .ok_or(Error::<T>::InvalidCapacity)?;
+            let verified = whole.category.is_backed_by(&measured);
+            let Some(verifier) = Self::vote_hardware(
+                AttestationTarget::Host(host_id),
+                HostAttestations::<T>::get(host_id),
+                verifier,
+                verified,
+            )?
+            else {
+                return Ok(());
+            };
+
+            HostAttestations::<T>::insert(
+                host_id,
+                HardwareAttestation {
+                    verifier: verifier.clone(),
+                    benchmark,
+                    measured,
+                    verified,
+                    attested_at: frame_system::Pallet::<T>::block_number(),
+                },
+            );
+
+            Self::deposit_event(Event::HostAttested {
+                host_id,
+                verifier,
+                benchmark,
+                verified,
+            });
+
+            Ok(())
+        }
+
+        /// Change the uptime guarantee, capacity and prices of a host. The capacity must
+        /// still hold the rented slices, which keep the price they were rented at.
+        #[pallet::call_index(64)]
+        #[pallet::weight(Weight::default())]
+        pub fn update_host(
+            origin: OriginFor<T>,
+            host_id: HostId,
+            uptime_guarantee: u8,
+            capacity: HostCapacity,
+            prices: SlicePrices<BalanceOf<T>>,
+        ) -> DispatchResult {
+            let provider = ensure_signed(origin)?;
+
+            Hosts::<T>::try_mutate(host_id, |maybe_host| -> DispatchResult {
+                let host = maybe_host.as_mut().ok_or(Error::<T>::HostNotFound)?;
+                ensure!(host.provider == provider, Error::<T>::NotResourceOwner);
+                ensure!(
+                    capacity.vcpu > 0 && (capacity.gpu_count == 0 || host.gpu.is_some()),
+                    Error::<T>::InvalidCapacity
+                );
+
+                let allocated = host.capacity.saturating_sub(&host.remaining);
+                ensure!(capacity.contains(&allocated), Error::<T>::HostInUse);
+
+                // A verified benchmark no longer covers the new capacity, a failed one stays
+                if capacity != host.capacity {
+                    HostAttestations::<T>::mutate_exists(host_id, |attestation| {
+                        if attestation.as_ref().is_some_and(|a| a.verified) {
+                            *attestation = None;
+                        }
+                    });
+                    let _ = HardwareVotes::<T>::clear_prefix(
+                        AttestationTarget::Host(host_id),
+                        u32::MAX,
+                        None,
+                    );
+                }
+
+                host.uptime_guarantee = uptime_guarantee;
+                host.remaining = capacity.saturating_sub(&allocated);
+                host.capacity = capacity;
+                host.prices = prices;
+
+                Ok(())
+            })?;
+
+            Self::deposit_event(Event::HostUpdated { host_id });
+
+            Ok(())
+        }
+
+        /// Remove a host once none of its slices is rented
+        #[pallet::call_index(65)]
+        #[pallet::weight(Weight::default())]
+        pub fn deregister_host(origin: OriginFor<T>, host_id: HostId) -> DispatchResult {
+            let provider = ensure_signed(origin)?;
+
+            let host = Hosts::<T>::get(host_id).ok_or(Error::<T>::HostNotFound)?;
+            ensure!(host.provider == provider, Error::<T>::NotResourceOwner);
+
+            Self::purge_host(host_id)?;
+
+            Self::deposit_event(Event::HostDeregistered { host_id, provider });
+
+            Ok(())
+        }
+    }
+
+    impl<T: Config> Pallet<T> {
+        /// The account ID of the escrow
+        fn escrow_account() -> T::AccountId {
+            T::PalletId::get().into_account_truncating()
+        }
+
+        fn ensure_valid_info(name: &ProviderName, website: &Option<Website>) -> DispatchResult {
+            ensure!(is_valid_name(name), Error::<T>::InvalidProviderName);
+            ensure!(
+                website.as_ref().is_none_or(|url| is_valid_url(url)),
+                Error::<T>::InvalidWebsite
+            );
+
+            Ok(())
+        }
+
+        /// Verified provider badge, held while the linked identity of the provider is judged
+        pub fn is_verified_provider(who: &T::AccountId) -> bool {
+            VerifiedProviders::<T>::contains_key(who) && T::Identity::is_verified(who)
+        }
+
+        /// Hardware verification badge of a resource
+        pub fn verification_status(resource_id: ResourceId) -> VerificationStatus {
+            // Slices are verified with their host
+            let attestation = match Allocations::<T>::get(resource_id) {
+                Some((host_id, _)) => HostAttestations::<T>::get(host_id),
+                None => HardwareAttestations::<T>::get(resource_id),
+            };
+            match attestation {
+                None => VerificationStatus::Unverified,
+                Some(attestation) if attestation.verified => VerificationStatus::Verified,
+                Some(_) => VerificationStatus::Failed,
+            }
+        }
+
+        /// Resources available for rent with their verification badge
+        pub fn available_resources(
+            verified_only: bool,
+        ) -> Vec<(ResourceId, Resource<T::AccountId>, VerificationStatus)> {
+            Resources::<T>::iter()
+                .filter(|(_, resource)| resource.is_available)
+                .map(|(id, resource)| (id, resource, Self::verification_status(id)))
+                .filter(|(.., status)| !verified_only || *status == VerificationStatus::Verified)
+                .collect()
+        }
+
+        /// Available resources in the countries of `filter`, read from the country index
+        pub fn resources_in_region(
+            filter: RegionFilter,
+            verified_only: bool,
+        ) -> Vec<(ResourceId, Resource<T::AccountId>, VerificationStatus)> {
+            filter
+                .countries()
+                .iter()
+                .flat_map(AvailableByCountry::<T>::iter_key_prefix)
+                .filter_map(|id| {
+                    let resource = Resources::<T>::get(id)?;
+                    Some((id, resource, Self::verification_status(id)))
+                })
+                .filter(|(.., status)| !verified_only || *status == VerificationStatus::Verified)
+                .collect()
+        }
+
+        /// Whether a resource is listed in the country index: available, and its provider
+        /// taking rentals
+        fn is_listed(resource: &Resource<T::AccountId>) -> bool {
+            resource.is_available && Self::accepts_rentals(&resource.provider)
+        }
+
+        pub(crate) fn index_resource(resource_id: ResourceId, resource: &Resource<T::AccountId>) {
+            if let Some(code) = resource
+                .location
+                .iso_country()
+                .filter(|_| Self::is_listed(resource))
+            {
+                AvailableByCountry::<T>::insert(code, resource_id, ());
+            }
+        }
+
+        fn unindex_resource(resource_id: ResourceId, resource: &Resource<T::AccountId>) {
+            if let Some(code) = resource.location.iso_country() {
+                AvailableByCountry::<T>::remove(code, resource_id);
+            }
+        }
+
+        /// Count the verdict of a verifier on some hardware. Returns who attests it, an auditor
+        /// alone or the watcher completing a quorum, the votes being cleared then.
+        fn vote_hardware(
+            target: AttestationTarget,
+            current: Option<HardwareAttestationOf<T>>,
+            verifier: T::AccountId,
+            verified: bool,
+        ) -> Result<Option<T::AccountId>, DispatchError> {
+            ensure!(
+                current.is_none_or(|attestation| attestation.verified),
+                Error::<T>::VerificationFailed
+            );
+            ensure!(Self::is_verifier(&verifier), Error::<T>::NotVerifier);
+
+            if !Auditors::<T>::contains_key(&verifier) {
+                HardwareVotes::<T>::insert(target, &verifier, verified);
+                Self::deposit_event(Event::HardwareVoted {
+                    target,
+                    watcher: verifier.clone(),
+                    verified,
+                });
+
+                // Watchers who left since their vote no longer count
+                let agreeing = HardwareVotes::<T>::iter_prefix(target)
+                    .filter(|(watcher, vote)| *vote == verified && Self::is_verifier(watcher))
+                    .count() as u32;
+                if agreeing < T::HardwareQuorum::get() {
+                    return Ok(None);
+                }
+            }
+
+            let _ = HardwareVotes::<T>::clear_prefix(target, u32::MAX, None);
+
+            Ok(Some(verifier))
+        }
+
+        fn is_verifier(who: &T::AccountId) -> bool {
+            Auditors::<T>::contains_key(who)
+                || Watchers::<T>::get(who).is_some_and(|watcher| watcher.leaving.is_none())
+        }
+
+        /// The account ID of the pot paying the watchers, hashed rather than truncated so
+        /// that it never collides with the escrow
+        pub fn watcher_pot() -> T::AccountId {
+            let entropy =
+                (T::PalletId::get(), b"watchers").using_encoded(sp_io::hashing::blake2_256);
+            Decode::decode(&mut TrailingZeroInput::new(entropy.as_ref()))
+                .expect("infinite length input; no invalid inputs for type; qed")
+        }
+
+        /// Pay a provider from the escrow, `WatcherFee` of it going to the watcher pot. The
+        /// provider is paid in full when the pot can't receive the fee.
+        fn pay_provider(provider: &T::AccountId, amount: BalanceOf<T>) -> DispatchResult {
+            let escrow_account = Self::escrow_account();
+            let fee = T::WatcherFee::get() * amount;
+            let fee = if !fee.is_zero()
+                && T::Currency::transfer(
+                    &escrow_account,
+                    &Self::watcher_pot(),
+                    fee,
+                    ExistenceRequirement::AllowDeath,
+                )
+                .is_ok()
+            {
+                fee
+            } else {
+                Zero::zero()
+            };
+
+            T::Currency::transfer(
+                &escrow_account,
+                provider,
+                amount.saturating_sub(fee),
+                ExistenceRequirement::AllowDeath,
+            )
+        }
+
+        fn epoch_at(n: BlockNumberFor<T>) -> EpochIndex {
+            (n / T::WatcherEpoch::get()).saturated_into()
+        }
+
+        /// Snapshot the watcher set and draw the seed of the assignments of `epoch` at block
+        /// `n`. Randomness known before `n` could have been used to predict the assignments,
+        /// the epoch is then postponed to the next block.
+        fn start_watch_epoch(epoch: EpochIndex, n: BlockNumberFor<T>) {
+            let (seed, known_since) = T::Randomness::random(&(WATCHER_SUBJECT, epoch).encode());
+            if known_since < n {
+                return;
+            }
+            let watchers = WatcherSet::<T>::get();
+
+            Self::deposit_event(Event::WatchEpochStarted {
+                epoch,
+                watchers: watchers.len() as u32,
+            });
+
+            WatchEpoch::<T>::put((epoch, seed, watchers));
+        }
+
+        /// Watchers probing a resource during the current epoch, `WatchersPerResource`
+        /// consecutive ones of the epoch snapshot starting at a random offset
+        pub fn assigned_watchers(resource_id: ResourceId) -> Vec<T::AccountId> {
+            let Some((_, seed, watchers)) = WatchEpoch::<T>::get() else {
+                return Vec::new();
+            };
+            let count = watchers.len() as u32;
+            if count == 0 {
+                return Vec::new();
+            }
+
+            let hash = T::Hashing::hash_of(&(seed, resource_id));
+            let offset = u32::decode(&mut hash.as_ref()).unwrap_or_default() % count;
+
+            (0..T::WatchersPerResource::get().min(count))
+                .map(|i| watchers[((offset + i) % count) as usize].clone())
+                .collect()
+        }
+
+        fn slash_watcher(who: &T::AccountId) {
+            let Some(watcher) = Watchers::<T>::get(who) else {
+                return;
+            };
+
+            let amount = T::WatcherSlash::get() * watcher.stake;
+            let unslashed = T::Currency::repatriate_reserved(
+                who,
+                &Self::watcher_pot(),
+                amount,
+                BalanceStatus::Free,
+            )
+            .unwrap_or(amount);
+            let slashed = amount.saturating_sub(unslashed);
+
+            let stake = watcher.stake.saturating_sub(slashed);
+
+            // A stake that no longer covers a slash leaves the set
+            let drained = watcher.leaving.is_none()
+                && stake < T::WatcherSlash::get() * T::WatcherStake::get();
+            let epoch = Self::epoch_at(frame_system::Pallet::<T>::block_number());
+            Watchers::<T>::insert(
+                who,
+                Watcher {
+                    stake,
+                    leaving: if drained {
+                        Some(epoch)
+                    } else {
+                        watcher.leaving
+                    },
+                    ..watcher
+                },
+            );
+
+            Self::deposit_event(Event::WatcherSlashed {
+                who: who.clone(),
+                amount: slashed,
+            });
+
+            if drained {
+                WatcherSet::<T>::mutate(|watchers| watchers.retain(|w| w != who));
+                Self::deposit_event(Event::WatcherLeft {
+                    who: who.clone(),
+                    epoch,
+                });
+            }
+        }
+
+        /// Settle the tally of a resource for an epoch, see `finalize_liveness`
+        fn do_finalize_liveness(epoch: EpochIndex, resource_id: ResourceId) {
+            let tally = LivenessTallies::<T>::take(epoch, resource_id);
+            let verdict = tally
+                .verdict()
+                .filter(|_| tally.total() >= T::WatcherQuorum::get());
+            let attestations: Vec<_> =
+                Attestations::<T>::drain_prefix((epoch, resource_id)).collect();
+
+            for (who, _) in &attestations {
+                Watchers::<T>::mutate(who, |watcher| {
+                    if let Some(watcher) = watcher {
+                        watcher.pending.saturating_dec();
+                    }
+                });
+            }
+
+            if let Some(alive) = verdict {
+                // Slashes first, they fund the rewards
+                let (honest, dishonest): (Vec<_>, Vec<_>) =
+                    attestations.into_iter().partition(|(_, a)| *a == alive);
+                for (who, _) in dishonest {
+                    Self::slash_watcher(&who);
+                }
+                for (who, _) in honest {
+                    Self::reward_watcher(who);
+                }
+
+                // A verdict of a stale epoch doesn't override a newer one, nor outlives a
+                // purged resource or a released slice
+                Liveness::<T>::mutate(resource_id, |liveness| {
+                    if liveness.is_none_or(|(last, _)| last < epoch)
+                        && Self::resource(resource_id).is_some()
+                    {
+                        *liveness = Some((epoch, alive));
+                    }
+                });
+            }
+
+            Self::deposit_event(Event::LivenessFinalized {
+                epoch,
+                resource_id,
+                alive: verdict,
+            });
+        }
+
+        /// Finalize the tallies left over a full epoch after theirs, oldest epoch first,
+        /// within `remaining_weight`
+        fn finalize_stale_liveness(n: BlockNumberFor<T>, remaining_weight: Weight) -> Weight {
+            let mut weight = T::DbWeight::get().reads(1);
+            if remaining_weight.any_lt(weight) {
+                return Weight::zero();
+            }
+
+            // Tally, attestations, then each watcher slashed or rewarded
+            let watchers = T::WatchersPerResource::get() as u64;
+            let per_tally = T::DbWeight::get().reads_writes(2 + 3 * watchers, 3 + 4 * watchers);
+
+            let current = Self::epoch_at(n);
+            let mut epoch = OldestLivenessEpoch::<T>::get();
+            let mut advanced = false;
+            while epoch.saturating_add(1) < current {
+                // Every key read is accounted as a tally, including the end of the prefix
+                if remaining_weight.any_lt(weight.saturating_add(per_tally)) {
+                    break;
+                }
+                weight.saturating_accrue(per_tally);
+
+                match LivenessTallies::<T>::iter_key_prefix(epoch).next() {
+                    Some(resource_id) => Self::do_finalize_liveness(epoch, resource_id),
+                    None => {
+                        epoch.saturating_inc();
+                        advanced = true;
+                    }
+                }
+            }
+            if advanced {
+                OldestLivenessEpoch::<T>::put(epoch);
+                weight.saturating_accrue(T::DbWeight::get().writes(1));
+            }
+
+            weight
+        }
+
+        /// Pay `WatcherReward`, or what is left in the pot
+        fn reward_watcher(who: T::AccountId) {
+            let pot = Self::watcher_pot();
+            let amount = T::WatcherReward::get().min(T::Currency::free_balance(&pot));
+            if amount.is_zero() {
+                return;
+            }
+
+            if T::Currency::transfer(&pot, &who, amount, ExistenceRequirement::AllowDeath).is_ok() {
+                Self::deposit_event(Event::WatcherRewarded { who, amount });
+            }
+        }
+
+        /// The treasury account of an organisation, derived like the watcher pot
+        pub fn organisation_account(org_id: OrganisationId) -> T::AccountId {
+            let entropy =
+                (T::PalletId::get(), b"org", org_id).using_encoded(sp_io::hashing::blake2_256);
+            Decode::decode(&mut TrailingZeroInput::new(entropy.as_ref()))
+                .expect("infinite length input; no invalid inputs for type; qed")
+        }
+
+        fn ensure_org_role(
+            org_id: OrganisationId,
+            who: &T::AccountId,
+            required: OrgRole,
+        ) -> DispatchResult {
+            let role = OrganisationMembers::<T>::get(org_id, who)
+                .ok_or(Error::<T>::NotOrganisationMember)?;
+            ensure!(role.allows(required), Error::<T>::MissingOrganisationRole);
+
+            Ok(())
+        }
+
+        /// Check that `who` can act for the rentals of `renter`: it is the renter itself, or
+        /// `renter` is the treasury of an organisation where `who` holds `required`
+        fn ensure_acting_for(
+            who: &T::AccountId,
+            renter: &T::AccountId,
+            required: OrgRole,
+        ) -> DispatchResult {
+            if who == renter {
+                return Ok(());
+            }
+            let org_id = OrganisationAccounts::<T>::get(renter).ok_or(Error::<T>::NotRenter)?;
+
+            Self::ensure_org_role(org_id, who, required)
+        }
+
+        /// An organisation always keeps an admin besides `member`
+        fn ensure_other_admin(org_id: OrganisationId, member: &T::AccountId) -> DispatchResult {
+            ensure!(
+                OrganisationMembers::<T>::iter_prefix(org_id)
+                    .any(|(who, role)| role == OrgRole::Admin && &who != member),
+                Error::<T>::LastOrganisationAdmin
+            );
+
+            Ok(())
+        }
+
+        fn do_set_budget(who: T::AccountId, budget: Option<BudgetOf<T>>) -> DispatchResult {
+            match &budget {
+                Some(b) => {
+                    ensure!(
+                        !b.period.is_zero() && !b.cap.is_zero(),
+                        Error::<T>::InvalidBudget
+                    );
+                    Budgets::<T>::insert(&who, b);
+                }
+                None => {
+                    Budgets::<T>::remove(&who);
+                    BudgetUsages::<T>::remove(&who);
+                }
+            }
+
+            Self::deposit_event(Event::BudgetSet { who, budget });
+
+            Ok(())
+        }
+
+        /// The budget of `who` is set by its controller, or by `who` itself without one
+        fn ensure_budget_controller(caller: &T::AccountId, who: &T::AccountId) -> DispatchResult {
+            let allowed = match BudgetControllers::<T>::get(who) {
+                Some(controller) => &controller == caller,
+                None => caller == who,
+            };
+            ensure!(allowed, Error::<T>::NotBudgetController);
+
+            Ok(())
+        }
+
+        /// Count `amount` against the budget of `who` in the current period, failing if it
+        /// goes over the cap
+        fn charge_budget(who: &T::AccountId, amount: BalanceOf<T>) -> DispatchResult {
+            let Some(budget) = Budgets::<T>::get(who) else {
+                return Ok(());
+            };
+
+            // A new period starts with the first spending after the previous one ended
+            let now = frame_system::Pallet::<T>::block_number();
+            let mut usage = BudgetUsages::<T>::get(who)
+                .filter(|usage| now < usage.period_start.saturating_add(budget.period))
+                .unwrap_or(BudgetUsage {
+                    period_start: now,
+                    spent: Zero::zero(),
+                });
+
+            let spent = usage.spent.saturating_add(amount);
+            ensure!(spent <= budget.cap, Error::<T>::BudgetExceeded);
+
+            let threshold = budget.alert_threshold * budget.cap;
+            if usage.spent < threshold && spent >= threshold {
+                Self::deposit_event(Event::BudgetThresholdReached {
                     who: who.clone(),
-                    status,
+                    spent,
+                    cap: budget.cap,
                 });
+            }
 
-                Ok(())
+            usage.spent = spent;
+            BudgetUsages::<T>::insert(who, usage);
+
+            Ok(())
+        }
+
+        /// Rent a resource for `renter`, committing it for `commitment` if set. Committed rentals
+        /// are priced with the discount of their term.
+        fn do_rent_committed(
+            renter: T::AccountId,
+            resource_id: ResourceId,
+            billing_period: BillingPeriodOf<T>,
+            commitment: Option<CommitmentTerm>,
+        ) -> DispatchResult {
+            if let Some(term) = commitment {
+                // The prepaid balance of pay-as-you-go is too small to back a commitment
+                ensure!(
+                    billing_period != BillingPeriod::PayAsYouGo,
+                    Error::<T>::InvalidBillingPeriod
+                );
+
+                let current_block = frame_system::Pallet::<T>::block_number();
+                ensure!(
+                    Self::active_commitment(resource_id, current_block).is_none(),
+                    Error::<T>::ResourceCommitted
+                );
+
+                let ends_at = current_block
+                    .saturating_add(T::BlocksPerWeek::get().saturating_mul(term.weeks().into()));
+
+                Commitments::<T>::insert(
+                    resource_id,
+                    Commitment {
+                        renter: renter.clone(),
+                        term,
+                        ends_at,
+                    },
+                );
+
+                Self::deposit_event(Event::CommitmentStarted {
+                    resource_id,
+                    renter: renter.clone(),
+                    term,
+                    ends_at,
+                });
+            }
+
+            Self::do_rent_resource(renter, resource_id, billing_period, None)
+        }
+
+        /// Register a bootstrapper and schedule its expiry
+        fn do_add_bootstrapper(
+            account: T::AccountId,
+            bootstrap_type: BootstrapperType,
+            expires_at: BlockNumberFor<T>,
+            max_resources: u32,
+        ) -> DispatchResult {
+            // Check if the account is already a bootstrapper
+            ensure!(
+                !Bootstrappers::<T>::contains_key(&account),
+                Error::<T>::BootstrapperAlreadyRegistered
+            );
+
+            let current_block = frame_system::Pallet::<T>::block_number();
+
+            ensure!(
+                expires_at > current_block,
+                Error::<T>::InvalidBootstrapperExpiry
+            );
+
+            ensure!(
+                max_resources <= T::MaxBootstrappedResources::get(),
+                Error::<T>::BootstrapperResourceCapTooHigh
+            );
+
+            // Schedule the automatic cleanup
+            BootstrapperExpiries::<T>::try_mutate(expires_at, |expiring| {
+                expiring.try_push(account.clone())
             })
+            .map_err(|_| Error::<T>::TooManyExpiringBootstrappers)?;
+
+            let bootstrapper_info = BootstrapperInfo {
+                bootstrapper_info: account.clone(),
+                bootstrap_type,
+                expires_at,
+                max_resources,
+                resource_count: 0,
+            };
+
+            Bootstrappers::<T>::insert(&account, bootstrapper_info);
+
+            // A direct addition also settles any pending request
+            PendingBootstrappers::<T>::remove(&account);
+
+            Self::deposit_event(Event::BootstrapperAdded {
+                who: account,
+                bootstrap_type,
+                expires_at,
+            });
+
+            Ok(())
         }
 
-        #[pallet::call_index(3)]
-        #[pallet::weight(Weight::default())]
-        pub fn register_resource(
-            origin: OriginFor<T>,
-            resource: Resource<T::AccountId>,
+        /// Resource rented as `resource_id`, a slice being described by its host
+        fn resource(resource_id: ResourceId) -> Option<Resource<T::AccountId>> {
+            match Allocations::<T>::get(resource_id) {
+                Some((host_id, slice)) => {
+                    let resource = Hosts::<T>::get(host_id)?.slice(&slice)?;
+                    Some(Resource {
+                        is_available: !Rentals::<T>::contains_key(resource_id),
+                        ..resource
+                    })
+                }
+                None => Resources::<T>::get(resource_id),
+            }
+        }
+
+        /// Settle the rental deposit and release the resource: the blocks used since the last
+        /// payment and the settled usage go to the provider, usage still disputable is held in
+        /// escrow and the rest of the deposit is refunded to the renter
+        fn do_cancel_rental(resource_id: ResourceId, rental: &RentalOf<T>) -> DispatchResult {
+            let current_block = frame_system::Pallet::<T>::block_number();
+
+            let resource = Self::resource(resource_id).ok_or(Error::<T>::ResourceNotFound)?;
+
+            let period_length = rental.billing_period.duration(T::BlocksPerWeek::get());
+
+            let period_cost =
+                Self::period_price(resource_id, &resource, &rental.billing_period, None);
+
+            let blocks_used = current_block.saturating_sub(rental.last_paid_block);
+
+            let deposit = Deposits::<T>::get(resource_id).unwrap_or_default();
+
+            // Compute used since the last payment, pro rata
+            let used_amount: BalanceOf<T> = (period_cost
+                .saturating_mul(blocks_used.saturated_into::<u128>()))
+            .saturating_div(period_length.saturated_into::<u128>())
+            .try_into()
+            .map_err(|_| Error::<T>::ConversionError)?;
+
+            let usage_amount = Self::take_settled_usage(resource_id, current_block);
+
+            let amount_due = used_amount.saturating_add(usage_amount);
+            let used_amount = amount_due.min(deposit);
+            let unpaid_usage = amount_due.saturating_sub(deposit).min(usage_amount);
+            let mut available = deposit.saturating_sub(used_amount);
+
+            // Undisputed usage still in its dispute window is held out of the refund
+            for record in UsageReports::<T>::take(resource_id) {
+                if record.disputed {
+                    continue;
+                }
+                let held = record.amount.min(available);
+                available = available.saturating_sub(held);
+                HeldUsages::<T>::insert(
+                    resource_id,
+                    record.id,
+                    HeldUsage {
+                        renter: rental.renter.clone(),
+                        provider: resource.provider.clone(),
+                        held,
+                        record,
+                    },
+                );
+            }
+
+            // Reimburse unused compute amount
+            let reimbursement_amount = available;
+
+            // The escrow may be emptied once the last deposit is settled
+            let escrow_account = Self::escrow_account();
+
+            Self::pay_provider(&resource.provider, used_amount)?;
+
+            T::Currency::transfer(
+                &escrow_account,
+                &rental.renter,
+                reimbursement_amount,
+                ExistenceRequirement::AllowDeath,
+            )?;
+
+            Self::incur_usage_debt(&rental.renter, &resource.provider, unpaid_usage);
+
+            Self::release_rental(resource_id);
+
+            Ok(())
+        }
+
+        /// Drop a settled rental and make its resource available again. The rental stays
+        /// disputable for `DisputeWindow` blocks.
+        fn release_rental(resource_id: ResourceId) {
+            Deposits::<T>::remove(resource_id);
+            RentalRateCards::<T>::remove(resource_id);
+            MatchedPrices::<T>::remove(resource_id);
+            UsageReports::<T>::remove(resource_id);
+            Deployments::<T>::remove(resource_id);
+            AccessKeys::<T>::remove(resource_id);
+            AccessBundles::<T>::remove(resource_id);
+
+            // The resource stays reserved to the renter until the end of its commitment
+            if Self::active_commitment(resource_id, frame_system::Pallet::<T>::block_number())
+                .is_none()
+            {
+                Commitments::<T>::remove(resource_id);
+            }
+
+            let provider = match Allocations::<T>::take(resource_id) {
+                // A slice only exists for its rental, give its capacity back to the host
+                Some((host_id, slice)) => {
+                    HostSlices::<T>::remove(host_id, resource_id);
+                    ResourceHeartbeats::<T>::remove(resource_id);
+                    Liveness::<T>::remove(resource_id);
+                    let provider = Hosts::<T>::mutate(host_id, |host| {
+                        host.as_mut().map(|host| {
+                            host.remaining = host.remaining.saturating_add(&slice);
+                            host.provider.clone()
+                        })
+                    });
+
+                    Self::deposit_event(Event::SliceReleased {
+                        host_id,
+                        resource_id,
+                    });
+
+                    provider
+                }
+                // Update resource availability
+                None => Resources::<T>::mutate(resource_id, |r| {
+                    r.as_mut().map(|res| {
+                        res.is_available = true;
+                        Self::index_resource(resource_id, res);
+                        res.provider.clone()
+                    })
+                }),
+            };
+
+            // Remove rental
+            if let (Some(rental), Some(provider)) = (Rentals::<T>::take(resource_id), provider) {
+                EndedRentals::<T>::insert(
+                    resource_id,
+                    EndedRental {
+                        renter: rental.renter,
+                        provider,
+                        ended_at: frame_system::Pallet::<T>::block_number(),
+                    },
+                );
+            }
+        }
+
+        /// Rent an available resource or an allocated slice, at the category price or at a
+        /// weekly price matched with an ask, won in an auction or set for the slice
+        fn do_rent_resource(
+            renter: T::AccountId,
+            resource_id: ResourceId,
+            billing_period: BillingPeriodOf<T>,
+            matched_price: Option<BalanceOf<T>>,
         ) -> DispatchResult {
-            let provider = ensure_signed(origin)?;
+            let resource = Self::resource(resource_id).ok_or(Error::<T>::ResourceNotFound)?;
+            ensure!(resource.is_available, Error::<T>::ResourceNotAvailable);
             ensure!(
-                Providers::<T>::contains_key(&provider),
-                Error::<T>::ProviderNotRegistered
+                Self::accepts_rentals(&resource.provider),
+                Error::<T>::ProviderNotAcceptingRentals
+            );
+            ensure!(
+                !Auctions::<T>::contains_key(resource_id),
+                Error::<T>::ResourceInAuction
+            );
+            ensure!(
+                !ResourceDisputes::<T>::contains_key(resource_id),
+                Error::<T>::EscrowFrozen
+            );
+            ensure!(
+                UsageDebts::<T>::iter_prefix(&renter).next().is_none(),
+                Error::<T>::OutstandingUsageDebt
+            );
+            if let Some(commitment) =
+                Self::active_commitment(resource_id, frame_system::Pallet::<T>::block_number())
+            {
+                ensure!(commitment.renter == renter, Error::<T>::ResourceCommitted);
+            }
+            ensure!(
+                !VerificationRequired::<T>::get()
+                    || Self::verification_status(resource_id) == VerificationStatus::Verified,
+                Error::<T>::ResourceUnverified
             );
 
-            let resource_id = NextResourceId::<T>::get();
+            ensure!(
+                !billing_period.duration(T::BlocksPerWeek::get()).is_zero(),
+                Error::<T>::InvalidBillingPeriod
+            );
+
+            let deposit_amount: BalanceOf<T> = match billing_period {
+                // Pay-as-you-go starts from the minimum prepaid balance, extended by top-ups
+                BillingPeriod::PayAsYouGo => T::MinimumDeposit::get(),
+                // Calculate required deposit (2 billing periods worth)
+                _ => Self::period_price(resource_id, &resource, &billing_period, matched_price)
+                    .saturating_mul(2)
+                    .try_into()
+                    .map_err(|_| Error::<T>::ConversionError)?,
+            };
+
+            ensure!(
+                deposit_amount >= T::MinimumDeposit::get(),
+                Error::<T>::InsufficientDeposit
+            );
+            Self::charge_budget(&renter, deposit_amount)?;
+
+            // Transfer deposit to escrow account
+            let escrow_account = Self::escrow_account();
+
+            T::Currency::transfer(
+                &renter,
+                &escrow_account,
+                deposit_amount,
+                ExistenceRequirement::KeepAlive,
+            )?;
+
+            // Store deposit
+            Deposits::<T>::insert(resource_id, deposit_amount);
+
+            if let Some(price) = matched_price {
+                MatchedPrices::<T>::insert(resource_id, price);
+            }
+            // A rented resource is no longer on offer
+            Self::remove_ask(resource_id);
+
+            // Usage is priced with the rate card in force when the rental starts
+            if let Some(rate_card) = RateCards::<T>::get(&resource.provider) {
+                RentalRateCards::<T>::insert(resource_id, rate_card);
+            }
+
+            // Create rental record
+            let current_block = frame_system::Pallet::<T>::block_number();
+
+            let rental = Rental {
+                resource_id,
+                renter: renter.clone(),
+                start_block: current_block,
+                billing_period,
+                last_paid_block: current_block,
+                is_active: true,
+            };
+
+            // Update resource and store rental, a slice is rented through its allocation
+            if !Allocations::<T>::contains_key(resource_id) {
+                Self::unindex_resource(resource_id, &resource);
+                Resources::<T>::insert(
+                    resource_id,
+                    Resource {
+                        is_available: false,
+                        ..resource
+                    },
+                );
+            }
+
+            Rentals::<T>::insert(resource_id, rental);
+
+            Self::deposit_event(Event::ResourceRented {
+                resource_id,
+                renter,
+            });
+
+            Ok(())
+        }
+
+        /// Price of one billing period of a rental
+        fn period_price(
+            resource_id: ResourceId,
+            resource: &Resource<T::AccountId>,
+            billing_period: &BillingPeriodOf<T>,
+            matched_price: Option<BalanceOf<T>>,
+        ) -> u128 {
+            let price = match matched_price.or_else(|| MatchedPrices::<T>::get(resource_id)) {
+                Some(weekly_price) => {
+                    billing_period.pro_rata(weekly_price.saturated_into(), T::BlocksPerWeek::get())
+                }
+                None => resource
+                    .category
+                    .price(billing_period, T::BlocksPerWeek::get()),
+            };
+
+            match Self::active_commitment(resource_id, frame_system::Pallet::<T>::block_number()) {
+                Some(commitment) => price.saturating_sub(commitment.term.discount() * price),
+                None => price,
+            }
+        }
+
+        /// Commitment of a resource, unless its term is over
+        fn active_commitment(
+            resource_id: ResourceId,
+            now: BlockNumberFor<T>,
+        ) -> Option<CommitmentOf<T>> {
+            Commitments::<T>::get(resource_id).filter(|commitment| now < commitment.ends_at)
+        }
+
+        /// End the commitment of a renter, paying the provider its share of the remaining term
+        fn terminate_commitment(resource_id: ResourceId, renter: &T::AccountId) -> DispatchResult {
+            let now = frame_system::Pallet::<T>::block_number();
+
+            let Some(commitment) = Commitments::<T>::get(resource_id) else {
+                return Ok(());
+            };
+            if now >= commitment.ends_at {
+                Commitments::<T>::remove(resource_id);
+                return Ok(());
+            }
+
+            let resource = Self::resource(resource_id).ok_or(Error::<T>::ResourceNotFound)?;
+
+            // Discounted price of the weeks left, the commitment being still active
+            let weekly_price =
+                Self::period_price(resource_id, &resource, &BillingPeriod::Weekly, None);
+            let remaining_blocks: u128 = commitment.ends_at.saturating_sub(now).saturated_into();
+            let remaining_value = weekly_price
+                .saturating_mul(remaining_blocks)
+                .checked_div(T::BlocksPerWeek::get().saturated_into())
+                .unwrap_or_default();
+
+            let penalty: BalanceOf<T> = (T::EarlyTerminationPenalty::get() * remaining_value)
+                .try_into()
+                .map_err(|_| Error::<T>::ConversionError)?;
+
+            T::Currency::transfer(
+                renter,
+                &resource.provider,
+                penalty,
+                ExistenceRequirement::KeepAlive,
+            )?;
+            Commitments::<T>::remove(resource_id);
+
+            Self::deposit_event(Event::CommitmentTerminated {
+                resource_id,
+                penalty,
+            });
+
+            Ok(())
+        }
+
+        /// List an ask, indexed under the category of its resource
+        fn insert_ask(resource_id: ResourceId, category: &ResourceCategory, price: BalanceOf<T>) {
+            if !Asks::<T>::contains_key(resource_id) {
+                AskCategories::<T>::mutate(category, |count| {
+                    *count = Some(count.unwrap_or_default().saturating_add(1))
+                });
+            }
+            Asks::<T>::insert(resource_id, price);
+            CategoryAsks::<T>::insert(category, resource_id, price);
+        }
+
+        /// Delist an ask, before its resource is deleted. Returns whether there was one.
+        fn remove_ask(resource_id: ResourceId) -> bool {
+            if Asks::<T>::take(resource_id).is_none() {
+                return false;
+            }
+            if let Some(resource) = Resources::<T>::get(resource_id) {
+                CategoryAsks::<T>::remove(&resource.category, resource_id);
+                AskCategories::<T>::mutate_exists(&resource.category, |count| {
+                    *count = count.map(|c| c.saturating_sub(1)).filter(|c| *c > 0);
+                });
+            }
+
+            true
+        }
+
+        /// Cheapest ask fulfilling an order, among the categories providing its specs
+        fn best_ask(order: &ComputeOrderOf<T>) -> Option<ResourceId> {
+            AskCategories::<T>::iter_keys()
+                .filter(|category| category.satisfies(&order.min_specs))
+                .flat_map(CategoryAsks::<T>::iter_prefix)
+                .filter(|(resource_id, price)| {
+                    *price <= order.max_price
+                        && Resources::<T>::get(resource_id)
+                            .is_some_and(|resource| Self::fulfills(&resource, order))
+                })
+                .min_by_key(|(_, price)| *price)
+                .map(|(resource_id, _)| resource_id)
+        }
+
+        fn fulfills(resource: &Resource<T::AccountId>, order: &ComputeOrderOf<T>) -> bool {
+            resource.is_available
+                && resource.provider != order.renter
+                && resource.category.satisfies(&order.min_specs)
+                && order
+                    .country_code
+                    .as_ref()
+                    .is_none_or(|code| *code == resource.location.country_code)
+        }
+
+        /// Rent the resource of an ask for an order at the asked price, escrowing the deposit
+        fn do_match_order(
+            order_id: OrderId,
+            order: ComputeOrderOf<T>,
+            resource_id: ResourceId,
+        ) -> DispatchResult {
+            ensure!(
+                frame_system::Pallet::<T>::block_number() < order.expires_at,
+                Error::<T>::OrderExpired
+            );
+            let price = Asks::<T>::get(resource_id).ok_or(Error::<T>::AskNotFound)?;
+            let resource = Resources::<T>::get(resource_id).ok_or(Error::<T>::ResourceNotFound)?;
+            ensure!(
+                price <= order.max_price && Self::fulfills(&resource, &order),
+                Error::<T>::OrderMismatch
+            );
+
+            Self::do_rent_resource(
+                order.renter.clone(),
+                resource_id,
+                order.billing_period.clone(),
+                Some(price),
+            )?;
+
+            // The rental deposit is topped up to prepay the whole duration
+            let prepaid: BalanceOf<T> =
+                Self::period_price(resource_id, &resource, &order.billing_period, Some(price))
+                    .saturating_mul(order.duration.into())
+                    .try_into()
+                    .map_err(|_| Error::<T>::ConversionError)?;
+            let deposit = Deposits::<T>::get(resource_id).unwrap_or_default();
+            let top_up = prepaid.saturating_sub(deposit);
+            if !top_up.is_zero() {
+                Self::charge_budget(&order.renter, top_up)?;
+                T::Currency::transfer(
+                    &order.renter,
+                    &Self::escrow_account(),
+                    top_up,
+                    ExistenceRequirement::KeepAlive,
+                )?;
+                Deposits::<T>::insert(resource_id, deposit.saturating_add(top_up));
+            }
+
+            Self::close_order(order_id, &order);
+
+            Self::deposit_event(Event::OrderMatched {
+                order_id,
+                resource_id,
+                price,
+            });
+
+            Ok(())
+        }
+
+        /// Drop an order from the book and give its deposit back
+        fn close_order(order_id: OrderId, order: &ComputeOrderOf<T>) {
+            T::Currency::unreserve(&order.renter, order.deposit);
+            Orders::<T>::remove(order_id);
+            OrderQueue::<T>::mutate(|queue| queue.retain(|id| *id != order_id));
+        }
+
+        /// Release the revealed bids of an ended auction and rent the resource to the highest
+        /// bidder able to pay its clearing price, the next bidders being tried in turn.
+        /// Unrevealed bids are forfeited to the provider. Returns the number of bids and of
+        /// rentals attempted.
+        fn settle_auction(resource_id: ResourceId) -> (u32, u32) {
+            let Some(auction) = Auctions::<T>::take(resource_id) else {
+                return (0, 0);
+            };
+            let provider = Resources::<T>::get(resource_id).map(|resource| resource.provider);
+
+            let mut bids = Vec::new();
+            for (bidder, sealed) in AuctionBids::<T>::drain_prefix(resource_id) {
+                match (sealed.revealed, &provider) {
+                    (Some((bid, order)), _) => {
+                        T::Currency::unreserve(&bidder, sealed.deposit);
+                        bids.push((bid, order, bidder));
+                    }
+                    (None, Some(provider)) => {
+                        let unpaid = T::Currency::repatriate_reserved(
+                            &bidder,
+                            provider,
+                            sealed.deposit,
+                            BalanceStatus::Free,
+                        )
+                        .unwrap_or(sealed.deposit);
+                        T::Currency::unreserve(&bidder, unpaid);
+
+                        Self::deposit_event(Event::BidForfeited {
+                            resource_id,
+                            bidder,
+                            amount: sealed.deposit.saturating_sub(unpaid),
+                        });
+                    }
+                    (None, None) => {
+                        T::Currency::unreserve(&bidder, sealed.deposit);
+                    }
+                }
+            }
+
+            // Highest bid first, earlier reveals win ties
+            bids.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+
+            let mut attempts = 0u32;
+            let mut settled = None;
+            for (i, (bid, _, bidder)) in bids.iter().enumerate() {
+                let price = auction.clearing_price(*bid, bids.get(i + 1).map(|next| next.0));
+                attempts.saturating_inc();
+
+                let rented = frame_support::storage::with_storage_layer(|| {
+                    Self::do_rent_resource(
+                        bidder.clone(),
+                        resource_id,
+                        auction.billing_period.clone(),
+                        Some(price),
+                    )
+                });
+                if rented.is_ok() {
+                    settled = Some((bidder.clone(), price));
+                    break;
+                }
+            }
 
-            ProviderResourceCount::<T>::mutate(
-                provider.clone(),
-                resource.category.clone(),
-                |count| *count += 1,
-            );
+            match settled {
+                Some((winner, price)) => Self::deposit_event(Event::AuctionSettled {
+                    resource_id,
+                    winner,
+                    price,
+                }),
+                None => Self::deposit_event(Event::AuctionFailed { resource_id }),
+            }
 
-            Resources::<T>::insert(resource_id, resource);
+            (auction.bid_count, attempts)
+        }
 
-            NextResourceId::<T>::put(resource_id + 1);
+        fn do_submit_evidence(
+            dispute_id: DisputeId,
+            who: T::AccountId,
+            evidence: T::Hash,
+        ) -> DispatchResult {
+            DisputeEvidence::<T>::try_mutate(dispute_id, &who, |hashes| hashes.try_push(evidence))
+                .map_err(|_| Error::<T>::TooMuchEvidence)?;
 
-            Self::deposit_event(Event::ResourceRegistered {
-                resource_id,
-                provider,
+            Self::deposit_event(Event::EvidenceSubmitted {
+                dispute_id,
+                who,
+                evidence,
             });
 
             Ok(())
         }
 
-        #[pallet::call_index(4)]
-        #[pallet::weight(Weight::default())]
-        pub fn rent_resource(
-            origin: OriginFor<T>,
-            resource_id: ResourceId,
-            billing_period: BillingPeriod,
-        ) -> DispatchResult {
-            let renter = ensure_signed(origin)?;
+        /// Move up to `amount` of the provider bond to `beneficiary`, returns the amount slashed
+        fn slash_bond(
+            provider: &T::AccountId,
+            beneficiary: &T::AccountId,
+            amount: BalanceOf<T>,
+        ) -> BalanceOf<T> {
+            let amount = amount.min(ProviderBonds::<T>::get(provider));
+            if amount.is_zero() {
+                return amount;
+            }
 
-            let resource = Resources::<T>::get(resource_id).ok_or(Error::<T>::ResourceNotFound)?;
-            ensure!(resource.is_available, Error::<T>::ResourceNotAvailable);
+            let unslashed = T::Currency::repatriate_reserved(
+                provider,
+                beneficiary,
+                amount,
+                BalanceStatus::Free,
+            )
+            .unwrap_or(amount);
+            let slashed = amount.saturating_sub(unslashed);
 
-            // Calculate required deposit (2 billing periods worth)
-            let period_cost = resource.category.price(&billing_period);
+            ProviderBonds::<T>::mutate_exists(provider, |bond| {
+                *bond = bond
+                    .map(|b| b.saturating_sub(slashed))
+                    .filter(|b| !b.is_zero());
+            });
 
-            let deposit_amount: BalanceOf<T> = (period_cost * 2)
-                .try_into()
-                .map_err(|_| Error::<T>::ConversionError)?;
+            slashed
+        }
 
-            ensure!(
-                deposit_amount >= T::MinimumDeposit::get(),
-                Error::<T>::InsufficientDeposit
-            );
+        /// Drop the usage reports whose dispute window is over and return the amount due for
+        /// the undisputed ones
+        fn take_settled_usage(
+            resource_id: ResourceId,
+            current_block: BlockNumberFor<T>,
+        ) -> BalanceOf<T> {
+            let window = T::UsageDisputeWindow::get();
+            let mut records = UsageReports::<T>::get(resource_id);
+            let mut amount = BalanceOf::<T>::zero();
 
-            // Transfer deposit to escrow account
-            let escrow_account = Self::escrow_account();
+            records.retain(|record| {
+                if current_block < record.submitted_at.saturating_add(window) {
+                    return true;
+                }
+                if !record.disputed {
+                    amount = amount.saturating_add(record.amount);
+                }
+                false
+            });
 
-            T::Currency::transfer(
-                &renter,
-                &escrow_account,
-                deposit_amount,
-                ExistenceRequirement::KeepAlive,
-            )?;
+            if records.is_empty() {
+                UsageReports::<T>::remove(resource_id);
+            } else {
+                UsageReports::<T>::insert(resource_id, records);
+            }
 
-            // Store deposit
-            Deposits::<T>::insert(resource_id, deposit_amount);
+            amount
+        }
 
-            // Create rental record
-            let current_block = frame_system::Pallet::<T>::block_number();
+        /// Record usage a renter couldn't pay out of its deposit
+        fn incur_usage_debt(renter: &T::AccountId, provider: &T::AccountId, amount: BalanceOf<T>) {
+            if amount.is_zero() {
+                return;
+            }
+            UsageDebts::<T>::mutate(renter, provider, |debt| *debt = debt.saturating_add(amount));
+            Self::deposit_event(Event::UsageDebtIncurred {
+                renter: renter.clone(),
+                provider: provider.clone(),
+                amount,
+            });
+        }
 
-            let current_block_u32: u32 = current_block
-                .try_into()
-                .map_err(|_| Error::<T>::BlockNumberOverflow)?;
+        /// Remove a bootstrapper together with its resources, refunding ongoing rentals.
+        /// Returns the number of resources removed. Fails without removing anything if a
+        /// refund fails, the caller has to roll back the storage.
+        fn do_remove_bootstrapper(who: &T::AccountId) -> Result<u32, DispatchError> {
+            Bootstrappers::<T>::remove(who);
 
-            let rental = Rental {
-                resource_id,
-                renter: renter.clone(),
-                start_block: current_block_u32,
-                billing_period,
-                last_paid_block: current_block_u32,
-                is_active: true,
-            };
+            let mut removed = 0u32;
 
-            // Update resource and store rental
-            Resources::<T>::insert(
-                resource_id,
-                Resource {
-                    is_available: false,
-                    ..resource
-                },
+            for (resource_id, ()) in BootstrappedResources::<T>::drain_prefix(who) {
+                Self::purge_resource(resource_id)?;
+                removed += 1;
+            }
+
+            Ok(removed)
+        }
+
+        /// Delete a resource, cancelling and refunding its rental first. Fails while the rental
+        /// is disputed.
+        fn purge_resource(resource_id: ResourceId) -> DispatchResult {
+            ensure!(
+                !ResourceDisputes::<T>::contains_key(resource_id),
+                Error::<T>::EscrowFrozen
             );
 
-            Rentals::<T>::insert(resource_id, rental);
+            if let Some(rental) = Rentals::<T>::get(resource_id) {
+                Self::do_cancel_rental(resource_id, &rental)?;
 
-            Self::deposit_event(Event::ResourceRented {
-                resource_id,
-                renter,
-            });
+                Self::deposit_event(Event::RentalCancelled {
+                    resource_id,
+                    renter: rental.renter.clone(),
+                });
+            }
+
+            Self::remove_ask(resource_id);
+            Commitments::<T>::remove(resource_id);
+            // Nobody is left to dispute it against
+            EndedRentals::<T>::remove(resource_id);
+            ResourceHeartbeats::<T>::remove(resource_id);
+            Liveness::<T>::remove(resource_id);
+            HardwareAttestations::<T>::remove(resource_id);
+            let _ = HardwareVotes::<T>::clear_prefix(
+                AttestationTarget::Resource(resource_id),
+                u32::MAX,
+                None,
+            );
+            if let Some(resource) = Resources::<T>::take(resource_id) {
+                Self::unindex_resource(resource_id, &resource);
+                ProviderResources::<T>::remove(&resource.provider, resource_id);
+                ProviderResourceCount::<T>::mutate_exists(
+                    &resource.provider,
+                    resource.category,
+                    |count| {
+                        *count = count.map(|c| c.saturating_sub(1)).filter(|c| *c > 0);
+                    },
+                );
+            }
 
             Ok(())
         }
 
-        #[pallet::call_index(5)]
-        #[pallet::weight(Weight::default())]
-        pub fn cancel_rental(origin: OriginFor<T>, resource_id: ResourceId) -> DispatchResult {
-            let renter = ensure_signed(origin)?;
+        /// Delete a host and its attestation, once none of its slices is rented
+        fn purge_host(host_id: HostId) -> DispatchResult {
+            ensure!(
+                HostSlices::<T>::iter_prefix(host_id).next().is_none(),
+                Error::<T>::HostInUse
+            );
 
-            let rental = Rentals::<T>::get(resource_id).ok_or(Error::<T>::RentalNotFound)?;
-            ensure!(rental.renter == renter, Error::<T>::NotRenter);
+            if let Some(host) = Hosts::<T>::take(host_id) {
+                ProviderHosts::<T>::remove(&host.provider, host_id);
+            }
+            HostAttestations::<T>::remove(host_id);
+            let _ =
+                HardwareVotes::<T>::clear_prefix(AttestationTarget::Host(host_id), u32::MAX, None);
 
-            let current_block = frame_system::Pallet::<T>::block_number();
+            Ok(())
+        }
 
-            let current_block_u32: u32 = current_block
-                .try_into()
-                .map_err(|_| Error::<T>::BlockNumberOverflow)?;
+        /// Resources of a provider and the slices rented from its hosts
+        fn rentable_ids(provider: &T::AccountId) -> Vec<ResourceId> {
+            ProviderResources::<T>::iter_key_prefix(provider)
+                .chain(
+                    ProviderHosts::<T>::iter_key_prefix(provider)
+                        .flat_map(HostSlices::<T>::iter_key_prefix),
+                )
+                .collect()
+        }
 
-            let resource = Resources::<T>::get(resource_id).ok_or(Error::<T>::ResourceNotFound)?;
+        /// Resources of providers leaving or suspended can't be rented, bootstrappers always
+        /// can
+        fn accepts_rentals(provider: &T::AccountId) -> bool {
+            Providers::<T>::get(provider).is_none_or(|info| info.status.accepts_rentals())
+        }
 
-            // Calculate amount to refund (if any)
-            let period_length = match rental.billing_period {
-                BillingPeriod::Weekly => T::BlocksPerWeek::get(),
-                BillingPeriod::Monthly => T::BlocksPerWeek::get() * 4,
-            };
+        /// End every rental of a sanctioned provider. Renters get their whole deposit back
+        /// from escrow and up to one billing period from the bond. Disputed rentals are left to
+        /// the arbiter, their escrow stays frozen. Returns the number of rentals refunded.
+        fn refund_renters(provider: &T::AccountId) -> u32 {
+            let mut refunded = 0u32;
 
-            let period_cost = resource.category.price(&rental.billing_period);
+            for resource_id in Self::rentable_ids(provider) {
+                if ResourceDisputes::<T>::contains_key(resource_id) {
+                    continue;
+                }
+                let (Some(rental), Some(resource)) =
+                    (Rentals::<T>::get(resource_id), Self::resource(resource_id))
+                else {
+                    continue;
+                };
 
-            let blocks_used = current_block_u32.saturating_sub(rental.last_paid_block);
+                // A failed refund must not block the sanction, the deposit then stays in escrow
+                let deposit = Deposits::<T>::get(resource_id).unwrap_or_default();
+                let deposit = match T::Currency::transfer(
+                    &Self::escrow_account(),
+                    &rental.renter,
+                    deposit,
+                    ExistenceRequirement::AllowDeath,
+                ) {
+                    Ok(()) => deposit,
+                    Err(_) => Zero::zero(),
+                };
 
-            if blocks_used < period_length {
-                let remaining_blocks = period_length.saturating_sub(blocks_used);
+                let compensation =
+                    Self::period_price(resource_id, &resource, &rental.billing_period, None)
+                        .saturated_into();
+                let compensation = Self::slash_bond(provider, &rental.renter, compensation);
 
-                // Reimburse unused compute amount
-                let reimbursement_amount: BalanceOf<T> = (period_cost
-                    .saturating_mul(remaining_blocks as u128))
-                .saturating_div(period_length as u128)
-                .try_into()
-                .map_err(|_| Error::<T>::ConversionError)?;
+                // The commitment doesn't outlive the provider's sanction
+                Commitments::<T>::remove(resource_id);
+                Self::release_rental(resource_id);
 
-                let escrow_account = Self::escrow_account();
+                Self::deposit_event(Event::RenterRefunded {
+                    resource_id,
+                    renter: rental.renter,
+                    deposit,
+                    compensation,
+                });
 
-                T::Currency::transfer(
-                    &escrow_account,
-                    &renter,
-                    reimbursement_amount,
-                    ExistenceRequirement::KeepAlive,
-                )?;
+                refunded += 1;
             }
 
-            // Update resource availability
-            Resources::<T>::mutate(resource_id, |r| {
-                if let Some(res) = r {
-                    res.is_available = true;
+            refunded
+        }
+
+        /// Bring a suspended provider back as inactive and relist its available resources.
+        /// Returns the number of resources relisted.
+        fn do_lift_suspension(who: &T::AccountId) -> u32 {
+            Suspensions::<T>::remove(who);
+            Providers::<T>::mutate(who, |maybe_provider| {
+                if let Some(info) = maybe_provider {
+                    info.status = ProviderStatus::Inactive;
+                    info.last_updated = frame_system::Pallet::<T>::block_number();
                 }
             });
 
-            // Remove rental
-            Rentals::<T>::remove(resource_id);
-
-            Self::deposit_event(Event::RentalCancelled {
-                resource_id,
-                renter,
-            });
+            let mut relisted = 0u32;
+            for resource_id in ProviderResources::<T>::iter_key_prefix(who) {
+                if let Some(resource) = Resources::<T>::get(resource_id) {
+                    Self::index_resource(resource_id, &resource);
+                    relisted += 1;
+                }
+            }
 
-            Ok(())
+            relisted
         }
 
-        #[pallet::call_index(6)]
-        #[pallet::weight(Weight::default())]
-        pub fn claim_payment(origin: OriginFor<T>, resource_id: ResourceId) -> DispatchResult {
-            let provider = ensure_signed(origin)?;
-
-            // Get the resource and verify ownership
-            let resource = Resources::<T>::get(resource_id).ok_or(Error::<T>::ResourceNotFound)?;
-            ensure!(resource.provider == provider, Error::<T>::NotResourceOwner);
-
-            // Get the rental information
-            let rental = Rentals::<T>::get(resource_id).ok_or(Error::<T>::RentalNotFound)?;
-            ensure!(rental.is_active, Error::<T>::RentalNotFound);
+        /// Check the marketplace invariants:
+        /// - every rental points to an existing, unavailable resource
+        /// - the escrow holds at least the outstanding deposits
+        /// - `ProviderResourceCount` matches the registered resources
+        /// - every resource belongs to a registered provider or bootstrapper
+        /// - `NextResourceId` is above every resource id
+        /// - queued orders, asks and matched prices point to existing entries
+        /// - every auction is due for settlement and counts its bids and reveals
+        /// - host capacity is either remaining or allocated to rented slices, which are
+        ///   indexed under their host and never registered as resources
+        /// - resources and hosts are indexed under their provider
+        /// - suspensions are recorded for suspended providers and scheduled to expire
+        /// - organisations count their members and keep an admin
+        /// - watchers count their unfinalized attestations
+        /// - providers count their open disputes
+        #[cfg(any(feature = "try-runtime", test))]
+        pub(crate) fn do_try_state() -> Result<(), frame_support::sp_runtime::TryRuntimeError> {
+            for (resource_id, rental) in Rentals::<T>::iter() {
+                let resource =
+                    Self::resource(resource_id).ok_or("rental of an unknown resource")?;
+                ensure!(!resource.is_available, "rented resource marked available");
+                ensure!(
+                    rental.resource_id == resource_id,
+                    "rental stored under another id"
+                );
+            }
 
-            let current_block = frame_system::Pallet::<T>::block_number();
-            let current_block_u32: u32 = current_block
-                .try_into()
-                .map_err(|_| Error::<T>::BlockNumberOverflow)?;
+            let queue = OrderQueue::<T>::get();
+            ensure!(
+                queue.len() == Orders::<T>::iter_keys().count(),
+                "orders out of the queue"
+            );
+            for order_id in queue {
+                ensure!(
+                    Orders::<T>::contains_key(order_id),
+                    "queued order not found"
+                );
+            }
 
-            // Calculate period length in blocks
-            let period_length = match rental.billing_period {
-                BillingPeriod::Weekly => T::BlocksPerWeek::get(),
-                BillingPeriod::Monthly => T::BlocksPerWeek::get() * 4,
-            };
+            for (resource_id, price) in Asks::<T>::iter() {
+                let resource =
+                    Resources::<T>::get(resource_id).ok_or("ask of an unknown resource")?;
+                ensure!(
+                    CategoryAsks::<T>::get(&resource.category, resource_id) == Some(price),
+                    "ask not indexed under its category"
+                );
+            }
+            ensure!(
+                CategoryAsks::<T>::iter_keys().count() == Asks::<T>::count() as usize,
+                "category ask without ask"
+            );
+            let mut indexed = 0u32;
+            for (category, count) in AskCategories::<T>::iter() {
+                ensure!(
+                    CategoryAsks::<T>::iter_prefix(&category).count() == count as usize,
+                    "ask count of a category out of sync"
+                );
+                indexed += count;
+            }
+            ensure!(
+                indexed == Asks::<T>::count(),
+                "category of an ask not listed"
+            );
 
-            // Calculate blocks since last payment
-            let blocks_since_last_payment =
-                current_block_u32.saturating_sub(rental.last_paid_block);
+            for (resource_id, auction) in Auctions::<T>::iter() {
+                ensure!(
+                    AuctionEnds::<T>::get(auction.reveal_end).contains(&resource_id),
+                    "auction never settled"
+                );
+                ensure!(
+                    AuctionBids::<T>::iter_prefix(resource_id).count()
+                        == auction.bid_count as usize,
+                    "auction bid count mismatch"
+                );
+                ensure!(
+                    AuctionBids::<T>::iter_prefix_values(resource_id)
+                        .filter(|sealed| sealed.revealed.is_some())
+                        .count()
+                        == auction.reveal_count as usize,
+                    "auction reveal count mismatch"
+                );
+            }
 
-            // Only allow claiming if at least one period has passed
+            for (resource_id, (host_id, _)) in Allocations::<T>::iter() {
+                ensure!(
+                    Rentals::<T>::contains_key(resource_id),
+                    "slice allocated without rental"
+                );
+                ensure!(
+                    Hosts::<T>::contains_key(host_id),
+                    "slice of an unknown host"
+                );
+                ensure!(
+                    HostSlices::<T>::contains_key(host_id, resource_id),
+                    "slice missing from HostSlices"
+                );
+                ensure!(
+                    !Resources::<T>::contains_key(resource_id),
+                    "slice registered as a resource"
+                );
+            }
             ensure!(
-                blocks_since_last_payment >= period_length,
-                Error::<T>::NoPaymentDue
+                HostSlices::<T>::iter().count() == Allocations::<T>::iter().count(),
+                "HostSlices entry without allocation"
             );
+            for (host_id, host) in Hosts::<T>::iter() {
+                let allocated = Allocations::<T>::iter_values()
+                    .filter(|(id, _)| *id == host_id)
+                    .fold(host.remaining.clone(), |total, (_, slice)| {
+                        total.saturating_add(&slice)
+                    });
+                ensure!(allocated == host.capacity, "host capacity mismatch");
+            }
 
-            // Calculate complete periods since last payment
-            let complete_periods = blocks_since_last_payment / period_length;
-
-            // Calculate the payment amount
-            let period_cost = resource.category.price(&rental.billing_period);
+            for resource_id in ResourceHeartbeats::<T>::iter_keys() {
+                ensure!(
+                    Self::resource(resource_id).is_some(),
+                    "heartbeat of an unknown resource"
+                );
+            }
 
-            let payment_amount: BalanceOf<T> = (period_cost
-                .saturating_mul(complete_periods as u128))
-            .try_into()
-            .map_err(|_| Error::<T>::ConversionError)?;
+            for (who, _) in ProviderUnbonding::<T>::iter() {
+                let provider = Providers::<T>::get(&who).ok_or("unbonding provider not found")?;
+                ensure!(
+                    provider.status == ProviderStatus::Deregistering,
+                    "unbonding provider not deregistering"
+                );
+            }
 
-            // Update the last paid block
-            let new_last_paid_block = rental
-                .last_paid_block
-                .saturating_add(complete_periods.saturating_mul(period_length));
+            for (who, suspension) in Suspensions::<T>::iter() {
+                let provider = Providers::<T>::get(&who).ok_or("suspended provider not found")?;
+                ensure!(
+                    provider.status == ProviderStatus::Suspended,
+                    "suspension of a provider not suspended"
+                );
+                if let Some(expires_at) = suspension.expires_at {
+                    ensure!(
+                        SuspensionExpiries::<T>::get(expires_at).contains(&who),
+                        "suspension expiry not scheduled"
+                    );
+                }
+            }
 
-            Rentals::<T>::try_mutate(resource_id, |maybe_rental| -> DispatchResult {
-                let rental = maybe_rental.as_mut().ok_or(Error::<T>::RentalNotFound)?;
-                rental.last_paid_block = new_last_paid_block;
-                Ok(())
-            })?;
+            for (org_id, org) in Organisations::<T>::iter() {
+                ensure!(
+                    OrganisationAccounts::<T>::get(&org.account) == Some(org_id),
+                    "organisation account not indexed"
+                );
+                let roles: Vec<_> = OrganisationMembers::<T>::iter_prefix_values(org_id).collect();
+                ensure!(
+                    roles.len() as u32 == org.members,
+                    "organisation member count mismatch"
+                );
+                ensure!(
+                    roles.contains(&OrgRole::Admin),
+                    "organisation without admin"
+                );
+            }
+            ensure!(
+                OrganisationAccounts::<T>::iter().count() == Organisations::<T>::iter().count(),
+                "dangling organisation account"
+            );
 
-            // Transfer payment directly from escrow to provider
-            let escrow_account = Self::escrow_account();
+            for (provider, host_id, ()) in ProviderHosts::<T>::iter() {
+                let host = Hosts::<T>::get(host_id).ok_or("indexed host not found")?;
+                ensure!(
+                    host.provider == provider,
+                    "host indexed under another provider"
+                );
+            }
+            ensure!(
+                ProviderHosts::<T>::iter().count() == Hosts::<T>::iter().count(),
+                "host missing from ProviderHosts"
+            );
 
-            T::Currency::transfer(
-                &escrow_account,
-                &provider,
-                payment_amount,
-                ExistenceRequirement::KeepAlive,
-            )?;
+            for who in VerifiedProviders::<T>::iter_keys() {
+                ensure!(
+                    Providers::<T>::contains_key(&who),
+                    "verified provider not registered"
+                );
+            }
 
-            Self::deposit_event(Event::PaymentClaimed {
-                resource_id,
-                provider,
-                amount: payment_amount,
-            });
+            for (code, resource_id, ()) in AvailableByCountry::<T>::iter() {
+                let resource =
+                    Resources::<T>::get(resource_id).ok_or("indexed resource not found")?;
+                ensure!(
+                    Self::is_listed(&resource) && resource.location.iso_country() == Some(code),
+                    "stale country index entry"
+                );
+            }
+            for (resource_id, resource) in Resources::<T>::iter() {
+                if let Some(code) = resource
+                    .location
+                    .iso_country()
+                    .filter(|_| Self::is_listed(&resource))
+                {
+                    ensure!(
+                        AvailableByCountry::<T>::contains_key(code, resource_id),
+                        "available resource missing from the country index"
+                    );
+                }
+            }
 
-            Ok(())
-        }
+            for resource_id in HardwareAttestations::<T>::iter_keys() {
+                ensure!(
+                    Resources::<T>::contains_key(resource_id),
+                    "attestation of an unknown resource"
+                );
+            }
+            for host_id in HostAttestations::<T>::iter_keys() {
+                ensure!(
+                    Hosts::<T>::contains_key(host_id),
+                    "attestation of an unknown host"
+                );
+            }
+            for (target, _, _) in HardwareVotes::<T>::iter() {
+                ensure!(
+                    match target {
+                        AttestationTarget::Resource(id) => Resources::<T>::contains_key(id),
+                        AttestationTarget::Host(id) => Hosts::<T>::contains_key(id),
+                    },
+                    "hardware vote of an unknown target"
+                );
+            }
 
-        #[pallet::call_index(7)]
-        #[pallet::weight(Weight::default())]
-        pub fn add_bootstrapper(
-            origin: OriginFor<T>,
-            account: T::AccountId,
-            bootstrap_type: BootstrapperType,
-        ) -> DispatchResult {
-            ensure_root(origin)?;
+            for resource_id in Liveness::<T>::iter_keys() {
+                ensure!(
+                    Self::resource(resource_id).is_some(),
+                    "liveness of an unknown resource"
+                );
+            }
 
-            // Check if the account is already a bootstrapper
+            let watchers = WatcherSet::<T>::get();
+            for who in &watchers {
+                let watcher = Watchers::<T>::get(who).ok_or("watcher without stake")?;
+                ensure!(
+                    watcher.leaving.is_none(),
+                    "leaving watcher still in the set"
+                );
+            }
             ensure!(
-                !Bootstrappers::<T>::contains_key(&account),
-                Error::<T>::BootstrapperAlreadyRegistered
+                Watchers::<T>::iter_values()
+                    .filter(|w| w.leaving.is_none())
+                    .count()
+                    == watchers.len(),
+                "active watcher missing from the set"
+            );
+            for (who, watcher) in Watchers::<T>::iter() {
+                ensure!(
+                    Attestations::<T>::iter()
+                        .filter(|(_, w, _)| w == &who)
+                        .count()
+                        == watcher.pending as usize,
+                    "pending attestations don't match"
+                );
+            }
+            let oldest = OldestLivenessEpoch::<T>::get();
+            ensure!(
+                LivenessTallies::<T>::iter_keys().all(|(epoch, _)| epoch >= oldest),
+                "tally older than the finalized epochs"
             );
 
-            let bootstrapper_info = BootstrapperInfo {
-                bootstrapper_info: account.clone(),
-                bootstrap_type,
-            };
+            for resource_id in Commitments::<T>::iter_keys() {
+                ensure!(
+                    Resources::<T>::contains_key(resource_id),
+                    "commitment of an unknown resource"
+                );
+            }
 
-            Bootstrappers::<T>::insert(&account, bootstrapper_info);
+            for resource_id in AccessBundles::<T>::iter_keys() {
+                ensure!(
+                    AccessKeys::<T>::contains_key(resource_id),
+                    "access bundle without key"
+                );
+            }
+            for resource_id in AccessKeys::<T>::iter_keys() {
+                ensure!(
+                    Rentals::<T>::contains_key(resource_id),
+                    "access key without rental"
+                );
+            }
 
-            Self::deposit_event(Event::BootstrapperAdded {
-                who: account,
-                bootstrap_type,
-            });
+            for resource_id in Deployments::<T>::iter_keys() {
+                ensure!(
+                    Rentals::<T>::contains_key(resource_id),
+                    "deployment without rental"
+                );
+            }
 
-            Ok(())
-        }
+            for resource_id in MatchedPrices::<T>::iter_keys() {
+                ensure!(
+                    Rentals::<T>::contains_key(resource_id),
+                    "matched price without rental"
+                );
+            }
 
-        #[pallet::call_index(8)]
-        #[pallet::weight(Weight::default())]
-        pub fn register_bootstrapped_resource(
-            origin: OriginFor<T>,
-            resource: Resource<T::AccountId>,
-        ) -> DispatchResult {
-            let bootstrapper = ensure_signed(origin)?;
+            for (resource_id, dispute_id) in ResourceDisputes::<T>::iter() {
+                let dispute = Disputes::<T>::get(dispute_id).ok_or("unknown dispute")?;
+                ensure!(
+                    dispute.resource_id == resource_id,
+                    "dispute of another resource"
+                );
+                ensure!(
+                    dispute.rental_active && Rentals::<T>::contains_key(resource_id),
+                    "frozen escrow without rental"
+                );
+            }
 
-            // Check if bootstrapper exists and is valid
+            for dispute in Disputes::<T>::iter_values() {
+                ensure!(
+                    Disputes::<T>::iter_values()
+                        .filter(|d| d.provider == dispute.provider)
+                        .count()
+                        == ProviderDisputes::<T>::get(&dispute.provider) as usize,
+                    "open disputes don't match"
+                );
+            }
             ensure!(
-                Bootstrappers::<T>::contains_key(&bootstrapper),
-                Error::<T>::NotBootstrapper
+                ProviderDisputes::<T>::iter_values().sum::<u32>() as usize
+                    == Disputes::<T>::iter_values().count(),
+                "open disputes don't match"
             );
 
-            // Get new bootstrapped resource IDs
-            let bootstrapped_id = NextBootstrappedResourceId::<T>::get();
+            for resource_id in UsageReports::<T>::iter_keys() {
+                ensure!(
+                    Rentals::<T>::contains_key(resource_id),
+                    "usage reports of an ended rental"
+                );
+            }
 
-            // Store the resource in bootstrapped storage
-            BootstrappedResources::<T>::insert(bootstrapper.clone(), bootstrapped_id, resource);
+            for held in HeldUsages::<T>::iter_values() {
+                ensure!(
+                    held.held <= held.record.amount,
+                    "more usage held than reported"
+                );
+            }
 
-            NextBootstrappedResourceId::<T>::put(bootstrapped_id + 1);
+            let outstanding = Deposits::<T>::iter_values()
+                .chain(HeldUsages::<T>::iter_values().map(|held| held.held))
+                .fold(BalanceOf::<T>::zero(), |total, deposit| {
+                    total.saturating_add(deposit)
+                });
+            ensure!(
+                T::Currency::total_balance(&Self::escrow_account()) >= outstanding,
+                "escrow balance below outstanding deposits"
+            );
 
-            Self::deposit_event(Event::BootstrappedResourceAdded {
-                bootstrapped_id,
-                bootstrapper,
-            });
+            let next_resource_id = NextResourceId::<T>::get();
+            let mut resource_count = 0u32;
 
-            Ok(())
-        }
-    }
+            for (resource_id, resource) in Resources::<T>::iter() {
+                ensure!(
+                    resource_id < next_resource_id,
+                    "resource id not below NextResourceId"
+                );
+                ensure!(
+                    Providers::<T>::contains_key(&resource.provider)
+                        || Bootstrappers::<T>::contains_key(&resource.provider),
+                    "resource of an unregistered provider"
+                );
+                ensure!(
+                    ProviderResources::<T>::contains_key(&resource.provider, resource_id),
+                    "resource missing from ProviderResources"
+                );
+                resource_count += 1;
+            }
 
-    impl<T: Config> Pallet<T> {
-        /// The account ID of the escrow
-        fn escrow_account() -> T::AccountId {
-            T::PalletId::get().into_account_truncating()
+            let mut counted = 0u32;
+
+            for (provider, category, count) in ProviderResourceCount::<T>::iter() {
+                let actual = Resources::<T>::iter_values()
+                    .filter(|r| r.provider == provider && r.category == category)
+                    .count() as u32;
+                ensure!(count == actual, "ProviderResourceCount out of sync");
+                counted += count;
+            }
+            ensure!(
+                counted == resource_count,
+                "resources missing from ProviderResourceCount"
+            );
+            ensure!(
+                ProviderResources::<T>::iter().count() as u32 == resource_count,
+                "stale ProviderResources entry"
+            );
+
+            Ok(())
         }
     }
 }
@@ -0,0 +1,18 @@
+//! Storage migrations for the kumulus pallet.
+//!
+//! Every change to the encoding of a stored type bumps the pallet `STORAGE_VERSION` and ships
+//! a `vN` module here. The module holds an `UncheckedMigrateToVN` doing the actual translation
+//! and exposes it wrapped in a [`VersionedMigration`](frame_support::migrations::VersionedMigration)
+//! as `MigrateVMToVN`, so it only runs against the version it was written for and is a no-op on
+//! any later upgrade. Translations that can't fit in one block are written as a
+//! [`SteppedMigration`](frame_support::migrations::SteppedMigration) instead and run by
+//! `pallet-migrations`, guarded by the storage version in their first step.
+//!
+//! A newly added pallet starts at its in-code storage version, so moving state in from another
+//! pallet is never versioned: [`unify_bootstrap`] is a stepped migration that only has work to
+//! do while the old prefix holds entries.
+//!
+//! Migrations must keep the try-runtime `pre_upgrade`/`post_upgrade` checks up to date so they
+//! can be exercised against exported testnet state before the upgrade is enacted.
+
+pub mod unify_bootstrap;
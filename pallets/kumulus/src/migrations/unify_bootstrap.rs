@@ -0,0 +1,120 @@
+//! Moves the bootstrappers of the former `pallet-kumulus-bootstrap` into this pallet.
+//!
+//! `Kumulus` was added to the runtime after `KumulusBootstrap`, so its storage version is set
+//! at genesis of the pallet and a versioned migration would never run. This one is a stepped
+//! migration instead, run by `pallet-migrations` over as many blocks as needed and guarded by
+//! the old prefix still holding entries.
+
+use crate::pallet::{Bootstrappers, Config, PendingBootstrappers};
+use frame_support::{
+    migrations::{RemovePallet, SteppedMigration, SteppedMigrationError},
+    pallet_prelude::*,
+    parameter_types, storage_alias,
+    traits::OnRuntimeUpgrade,
+    weights::WeightMeter,
+};
+
+#[cfg(feature = "try-runtime")]
+use sp_std::vec::Vec;
+
+parameter_types! {
+    pub const OldPalletName: &'static str = "KumulusBootstrap";
+}
+
+/// Identifier of the migration in `pallet-migrations`.
+pub const MIGRATION_ID: &[u8; 24] = b"kumulus-unify-bootstrap1";
+
+/// `KumulusBootstrap::Bootstrappers` as it was stored by `pallet-kumulus-bootstrap`.
+#[storage_alias]
+pub type OldBootstrappers<T: Config> = StorageMap<
+    KumulusBootstrap,
+    Blake2_128Concat,
+    <T as frame_system::Config>::AccountId,
+    bool,
+    OptionQuery,
+>;
+
+/// Drains the old self-registered bootstrappers into `PendingBootstrappers`, one entry at a
+/// time, then removes what is left of the old pallet storage.
+///
+/// The old pallet never vetted its entries, so they are queued for governance approval
+/// rather than granted bootstrapper rights directly.
+pub struct MigrateToPendingBootstrappers<T>(PhantomData<T>);
+
+impl<T: Config> MigrateToPendingBootstrappers<T> {
+    fn step_weight() -> Weight {
+        // Old entry, current and pending bootstrapper, then the pending entry and its counter
+        T::DbWeight::get().reads_writes(3, 3)
+    }
+}
+
+impl<T: Config> SteppedMigration for MigrateToPendingBootstrappers<T> {
+    // Nothing to track, drained entries are gone from the old prefix
+    type Cursor = ();
+    type Identifier = [u8; 24];
+
+    fn id() -> Self::Identifier {
+        *MIGRATION_ID
+    }
+
+    fn step(
+        _cursor: Option<Self::Cursor>,
+        meter: &mut WeightMeter,
+    ) -> Result<Option<Self::Cursor>, SteppedMigrationError> {
+        let required = Self::step_weight();
+        if meter.remaining().any_lt(required) {
+            return Err(SteppedMigrationError::InsufficientWeight { required });
+        }
+
+        let current_block = frame_system::Pallet::<T>::block_number();
+
+        while meter.try_consume(required).is_ok() {
+            let Some((who, registered)) = OldBootstrappers::<T>::drain().next() else {
+                // Only the storage version of the old pallet is left, within the step weight
+                RemovePallet::<OldPalletName, T::DbWeight>::on_runtime_upgrade();
+                return Ok(None);
+            };
+
+            if registered
+                && !Bootstrappers::<T>::contains_key(&who)
+                && !PendingBootstrappers::<T>::contains_key(&who)
+            {
+                PendingBootstrappers::<T>::insert(&who, current_block);
+            }
+        }
+
+        Ok(Some(()))
+    }
+
+    #[cfg(feature = "try-runtime")]
+    fn pre_upgrade() -> Result<Vec<u8>, frame_support::sp_runtime::TryRuntimeError> {
+        let to_migrate = OldBootstrappers::<T>::iter()
+            .filter(|(who, registered)| {
+                *registered
+                    && !Bootstrappers::<T>::contains_key(who)
+                    && !PendingBootstrappers::<T>::contains_key(who)
+            })
+            .count() as u32;
+
+        Ok((PendingBootstrappers::<T>::count(), to_migrate).encode())
+    }
+
+    #[cfg(feature = "try-runtime")]
+    fn post_upgrade(state: Vec<u8>) -> Result<(), frame_support::sp_runtime::TryRuntimeError> {
+        let (pending_before, migrated): (u32, u32) = Decode::decode(&mut &state[..])
+            .map_err(|_| "unify_bootstrap: failed to decode pre-upgrade state")?;
+
+        ensure!(
+            !frame_support::storage::unhashed::contains_prefixed_key(&sp_io::hashing::twox_128(
+                OldPalletName::get().as_bytes()
+            )),
+            "unify_bootstrap: KumulusBootstrap storage was not removed"
+        );
+        ensure!(
+            PendingBootstrappers::<T>::count() == pending_before + migrated,
+            "unify_bootstrap: unexpected number of pending bootstrappers"
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,211 @@
+use crate::{self as pallet_kumulus, UsageReport};
+use frame_support::{
+    derive_impl, parameter_types,
+    traits::{ConstU32, ConstU64, Randomness},
+    PalletId,
+};
+use frame_system::EnsureRoot;
+use sp_runtime::{
+    testing::{TestSignature, TestXt, UintAuthorityId, H256},
+    traits::{BlakeTwo256, Hash},
+    BuildStorage, Perbill,
+};
+
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+    pub enum Test {
+        System: frame_system,
+        Balances: pallet_balances,
+        Kumulus: pallet_kumulus,
+    }
+);
+
+// System pallet configuration
+#[derive_impl(frame_system::config_preludes::TestDefaultConfig)]
+impl frame_system::Config for Test {
+    type Block = Block;
+    type AccountData = pallet_balances::AccountData<u64>;
+    type DbWeight = frame_support::weights::constants::RocksDbWeight;
+}
+
+#[derive_impl(pallet_balances::config_preludes::TestDefaultConfig)]
+impl pallet_balances::Config for Test {
+    type AccountStore = System;
+}
+
+// Custom pallet configuration
+parameter_types! {
+    pub const KumulusPalletId: PalletId = PalletId(*b"kmls/esc");
+    pub const EarlyTerminationPenalty: Perbill = Perbill::from_percent(50);
+    pub const WatcherSlash: Perbill = Perbill::from_percent(20);
+    pub static JudgedIdentities: Vec<u64> = vec![];
+    pub static WatcherFee: Perbill = Perbill::zero();
+    pub static StaleRandomness: bool = false;
+    pub const MaxUsagePerReport: UsageReport = UsageReport {
+        cpu_seconds: 1_000,
+        egress_gb: 100,
+        storage_gb_hours: 1_000,
+    };
+}
+
+pub const BLOCKS_PER_WEEK: u64 = 100;
+pub const USAGE_DISPUTE_WINDOW: u64 = 10;
+pub const DISPUTE_WINDOW: u64 = 20;
+pub const PROVIDER_BOND: u64 = 1_000;
+pub const OFFCHAIN_HEARTBEAT_INTERVAL: u64 = 5;
+pub const PROVIDER_UNBONDING_PERIOD: u64 = 30;
+pub const WATCHER_EPOCH: u64 = 10;
+pub const WATCHER_STAKE: u64 = 1_000;
+pub const WATCHER_REWARD: u64 = 50;
+pub const ORGANISATION_DEPOSIT: u64 = 100;
+pub const ORDER_DEPOSIT: u64 = 10;
+pub const ORDER_LIFETIME: u64 = 50;
+pub const BID_DEPOSIT: u64 = 50;
+
+impl pallet_kumulus::Config for Test {
+    type RuntimeEvent = RuntimeEvent;
+    type Currency = Balances;
+    type BlocksPerWeek = ConstU64<BLOCKS_PER_WEEK>;
+    type PalletId = KumulusPalletId;
+    type MinimumDeposit = ConstU64<1>;
+    type AdminOrigin = EnsureRoot<u64>;
+    type MaxBootstrappedResources = ConstU32<4>;
+    type MaxExpiringBootstrappers = ConstU32<2>;
+    type MaxPendingBootstrappers = ConstU32<2>;
+    type MaxPendingUsageReports = ConstU32<2>;
+    type UsageDisputeWindow = ConstU64<USAGE_DISPUTE_WINDOW>;
+    type MaxUsagePerReport = MaxUsagePerReport;
+    type ProviderBond = ConstU64<PROVIDER_BOND>;
+    type DisputeWindow = ConstU64<DISPUTE_WINDOW>;
+    type MaxDisputeEvidence = ConstU32<2>;
+    type ArbiterOrigin = EnsureRoot<u64>;
+    type MaxOpenOrders = ConstU32<2>;
+    type OrderDeposit = ConstU64<ORDER_DEPOSIT>;
+    type OrderLifetime = ConstU64<ORDER_LIFETIME>;
+    type AuctionBidDeposit = ConstU64<BID_DEPOSIT>;
+    type MaxAuctionBids = ConstU32<3>;
+    type MaxEndingAuctions = ConstU32<2>;
+    type EarlyTerminationPenalty = EarlyTerminationPenalty;
+    type AuthorityId = TestAuthId;
+    type OffchainHeartbeatInterval = ConstU64<OFFCHAIN_HEARTBEAT_INTERVAL>;
+    type MaxHeartbeatResources = ConstU32<4>;
+    type Randomness = TestRandomness;
+    type WatcherStake = ConstU64<WATCHER_STAKE>;
+    type WatcherEpoch = ConstU64<WATCHER_EPOCH>;
+    type WatchersPerResource = ConstU32<3>;
+    type WatcherQuorum = ConstU32<2>;
+    type HardwareQuorum = ConstU32<2>;
+    type MaxWatchers = ConstU32<4>;
+    type WatcherReward = ConstU64<WATCHER_REWARD>;
+    type WatcherSlash = WatcherSlash;
+    type WatcherFee = WatcherFee;
+    type Identity = TestIdentity;
+    type ProviderUnbondingPeriod = ConstU64<PROVIDER_UNBONDING_PERIOD>;
+    type MaxExpiringSuspensions = ConstU32<2>;
+    type MaxOrganisationMembers = ConstU32<3>;
+    type OrganisationDeposit = ConstU64<ORGANISATION_DEPOSIT>;
+}
+
+// Accounts in `JudgedIdentities` have a positively judged identity
+pub struct TestIdentity;
+
+impl pallet_kumulus::ProviderIdentity<u64> for TestIdentity {
+    fn is_verified(who: &u64) -> bool {
+        JudgedIdentities::get().contains(who)
+    }
+}
+
+// Deterministic, the subject is the only input. Known since the current block unless
+// `StaleRandomness` is set.
+pub struct TestRandomness;
+
+impl Randomness<H256, u64> for TestRandomness {
+    fn random(subject: &[u8]) -> (H256, u64) {
+        let known_since = if StaleRandomness::get() {
+            0
+        } else {
+            System::block_number()
+        };
+        (BlakeTwo256::hash(subject), known_since)
+    }
+}
+
+pub type Extrinsic = TestXt<RuntimeCall, ()>;
+
+// Offchain worker keys are plain account ids in tests
+pub struct TestAuthId;
+
+impl frame_system::offchain::AppCrypto<UintAuthorityId, TestSignature> for TestAuthId {
+    type RuntimeAppPublic = UintAuthorityId;
+    type GenericPublic = UintAuthorityId;
+    type GenericSignature = TestSignature;
+}
+
+impl frame_system::offchain::SigningTypes for Test {
+    type Public = UintAuthorityId;
+    type Signature = TestSignature;
+}
+
+impl<LocalCall> frame_system::offchain::CreateTransactionBase<LocalCall> for Test
+where
+    RuntimeCall: From<LocalCall>,
+{
+    type RuntimeCall = RuntimeCall;
+    type Extrinsic = Extrinsic;
+}
+
+impl<LocalCall> frame_system::offchain::CreateSignedTransaction<LocalCall> for Test
+where
+    RuntimeCall: From<LocalCall>,
+{
+    fn create_signed_transaction<
+        C: frame_system::offchain::AppCrypto<Self::Public, Self::Signature>,
+    >(
+        call: RuntimeCall,
+        _public: UintAuthorityId,
+        account: u64,
+        _nonce: <Test as frame_system::Config>::Nonce,
+    ) -> Option<Extrinsic> {
+        Some(Extrinsic::new_signed(call, account, (), ()))
+    }
+}
+
+pub const PROVIDER: u64 = 1;
+pub const RENTER: u64 = 2;
+pub const BOOTSTRAPPER: u64 = 3;
+pub const WATCHERS: [u64; 3] = [4, 5, 6];
+pub const INITIAL_BALANCE: u64 = 1_000_000_000_000;
+
+// Test externalities initialization
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    let mut storage = frame_system::GenesisConfig::<Test>::default()
+        .build_storage()
+        .unwrap();
+
+    pallet_balances::GenesisConfig::<Test> {
+        balances: vec![
+            (PROVIDER, INITIAL_BALANCE),
+            (RENTER, INITIAL_BALANCE),
+            (BOOTSTRAPPER, INITIAL_BALANCE),
+            (WATCHERS[0], INITIAL_BALANCE),
+            (WATCHERS[1], INITIAL_BALANCE),
+            (WATCHERS[2], INITIAL_BALANCE),
+        ],
+    }
+    .assimilate_storage(&mut storage)
+    .unwrap();
+
+    let mut ext: sp_io::TestExternalities = storage.into();
+    ext.execute_with(|| System::set_block_number(1));
+    ext
+}
+
+// Runs a test and checks the pallet invariants once it is done
+pub fn build_and_execute(test: impl FnOnce()) {
+    new_test_ext().execute_with(|| {
+        test();
+        Kumulus::do_try_state().expect("pallet invariants hold");
+    });
+}
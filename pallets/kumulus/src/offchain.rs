@@ -0,0 +1,107 @@
+//! Offchain worker probing the health of the resources of the providers whose key is in the
+//! node keystore.
+//!
+//! The health endpoint is configured per node in the persistent local storage under
+//! [`HEALTH_ENDPOINT_KEY`], e.g. `http://127.0.0.1:8080/health`. Resource `id` is healthy when
+//! `GET <endpoint>/<id>` answers `200`.
+
+use crate::{Call, Config, Pallet, Providers, ResourceId, Resources};
+use alloc::{format, vec, vec::Vec};
+use frame_support::{
+    sp_runtime::{
+        offchain::{http, Duration, StorageKind},
+        traits::{IdentifyAccount, Zero},
+        BoundedVec, KeyTypeId, RuntimeAppPublic,
+    },
+    traits::Get,
+};
+use frame_system::{
+    offchain::{AppCrypto, SendSignedTransaction, Signer},
+    pallet_prelude::BlockNumberFor,
+};
+use sp_io::offchain::local_storage_get;
+
+/// Key type of the provider keys signing the health reports
+pub const KEY_TYPE: KeyTypeId = KeyTypeId(*b"kmls");
+
+/// Local storage key of the health endpoint
+pub const HEALTH_ENDPOINT_KEY: &[u8] = b"kumulus::health-endpoint";
+
+const PROBE_TIMEOUT_MS: u64 = 2_000;
+
+pub mod crypto {
+    use super::KEY_TYPE;
+    use frame_support::sp_runtime::{
+        app_crypto::{app_crypto, sr25519},
+        MultiSignature, MultiSigner,
+    };
+
+    app_crypto!(sr25519, KEY_TYPE);
+
+    /// Provider key of the offchain worker
+    pub struct ProviderAuthId;
+
+    impl frame_system::offchain::AppCrypto<MultiSigner, MultiSignature> for ProviderAuthId {
+        type RuntimeAppPublic = Public;
+        type GenericSignature = sr25519::Signature;
+        type GenericPublic = sr25519::Public;
+    }
+}
+
+impl<T: Config> Pallet<T> {
+    pub(crate) fn probe_resources(now: BlockNumberFor<T>) {
+        if !(now % T::OffchainHeartbeatInterval::get()).is_zero() {
+            return;
+        }
+
+        let Some(endpoint) = local_storage_get(StorageKind::PERSISTENT, HEALTH_ENDPOINT_KEY) else {
+            return;
+        };
+        let Ok(endpoint) = core::str::from_utf8(&endpoint) else {
+            log::warn!(target: "kumulus", "health endpoint is not valid UTF-8");
+            return;
+        };
+
+        for key in <T::AuthorityId as AppCrypto<T::Public, T::Signature>>::RuntimeAppPublic::all() {
+            let generic: <T::AuthorityId as AppCrypto<T::Public, T::Signature>>::GenericPublic =
+                key.into();
+            let public: T::Public = generic.into();
+            let provider = public.clone().into_account();
+
+            if !Providers::<T>::contains_key(&provider) {
+                continue;
+            }
+
+            let healthy: Vec<ResourceId> = Resources::<T>::iter()
+                .filter(|(_, resource)| resource.provider == provider)
+                .map(|(resource_id, _)| resource_id)
+                .take(T::MaxHeartbeatResources::get() as usize)
+                .filter(|resource_id| Self::probe(endpoint, *resource_id))
+                .collect();
+            let resource_ids = BoundedVec::truncate_from(healthy);
+
+            let results = Signer::<T, T::AuthorityId>::all_accounts()
+                .with_filter(vec![public])
+                .send_signed_transaction(|_| Call::report_health {
+                    resource_ids: resource_ids.clone(),
+                });
+
+            for (_, result) in results {
+                if result.is_err() {
+                    log::warn!(target: "kumulus", "cannot submit the health report of {provider:?}");
+                }
+            }
+        }
+    }
+
+    fn probe(endpoint: &str, resource_id: ResourceId) -> bool {
+        let deadline = sp_io::offchain::timestamp().add(Duration::from_millis(PROBE_TIMEOUT_MS));
+        let url = format!("{endpoint}/{resource_id}");
+
+        let Ok(pending) = http::Request::get(&url).deadline(deadline).send() else {
+            return false;
+        };
+
+        matches!(pending.try_wait(deadline), Ok(Ok(response)) if response.code == 200)
+    }
+}
@@ -0,0 +1,30 @@
+//! Runtime API of the kumulus marketplace, for renters browsing the resources
+
+use alloc::vec::Vec;
+use codec::Codec;
+
+use crate::{RegionFilter, Resource, ResourceId, VerificationStatus};
+
+sp_api::decl_runtime_apis! {
+    pub trait KumulusApi<AccountId>
+    where
+        AccountId: Codec,
+    {
+        /// Verified provider badge, backed by the judged on-chain identity of the provider
+        fn is_verified_provider(provider: AccountId) -> bool;
+
+        /// Hardware verification badge of a resource
+        fn verification_status(resource_id: ResourceId) -> VerificationStatus;
+
+        /// Resources available for rent with their verification badge
+        fn available_resources(
+            verified_only: bool,
+        ) -> Vec<(ResourceId, Resource<AccountId>, VerificationStatus)>;
+
+        /// Available resources in a country or a group of countries, e.g. the EU
+        fn resources_in_region(
+            filter: RegionFilter,
+            verified_only: bool,
+        ) -> Vec<(ResourceId, Resource<AccountId>, VerificationStatus)>;
+    }
+}
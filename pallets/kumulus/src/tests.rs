@@ -0,0 +1,3037 @@
+use crate::{mock::*, offchain::HEALTH_ENDPOINT_KEY, *};
+use codec::Decode;
+use frame_support::{assert_noop, assert_ok, traits::Hooks, weights::Weight, BoundedVec};
+use sp_core::{
+    offchain::{
+        testing::{PendingRequest, TestOffchainExt, TestTransactionPoolExt},
+        OffchainDbExt, OffchainWorkerExt, StorageKind, TransactionPoolExt,
+    },
+    H256,
+};
+use sp_runtime::{
+    testing::UintAuthorityId,
+    traits::{AccountIdConversion, BlakeTwo256, Hash},
+    DispatchError, Perbill,
+};
+
+fn region() -> Region {
+    Region {
+        country_code: CountryCode::truncate_from(b"FR".to_vec()),
+        city: BoundedVec::truncate_from(b"Paris".to_vec()),
+    }
+}
+
+fn resource(provider: u64) -> Resource<u64> {
+    Resource {
+        provider,
+        category: ResourceCategory::Nano(0, 0),
+        location: region(),
+        is_available: true,
+        uptime_guarantee: 99,
+    }
+}
+
+fn register_provider_with_resource() -> ResourceId {
+    assert_ok!(Kumulus::register_provider(
+        RuntimeOrigin::signed(PROVIDER),
+        ProviderName::truncate_from(b"Kollectyve".to_vec()),
+        None
+    ));
+
+    let resource_id = NextResourceId::<Test>::get();
+    assert_ok!(Kumulus::register_resource(
+        RuntimeOrigin::signed(PROVIDER),
+        resource(PROVIDER)
+    ));
+
+    resource_id
+}
+
+fn register_gpu_resource() -> ResourceId {
+    let resource_id = NextResourceId::<Test>::get();
+    assert_ok!(Kumulus::register_resource(
+        RuntimeOrigin::signed(PROVIDER),
+        Resource {
+            category: ResourceCategory::Custom(CustomResourceSpecs {
+                vcpu: 8,
+                memory_gb: 64,
+                storage_gb: 500,
+                gpu_specs: Some(GPUSpecs {
+                    gpu_count: 1,
+                    gpu_memory_gb: Some(80),
+                    gpu_model: BoundedVec::truncate_from(b"H100".to_vec()),
+                }),
+            }),
+            ..resource(PROVIDER)
+        }
+    ));
+
+    resource_id
+}
+
+fn slice_prices() -> SlicePrices<u64> {
+    SlicePrices {
+        vcpu: 100_000_000,
+        memory_gb: 10_000_000,
+        storage_gb: 1_000_000,
+        gpu: 1_000_000_000,
+    }
+}
+
+fn register_host() -> HostId {
+    let host_id = NextHostId::<Test>::get();
+    assert_ok!(Kumulus::register_host(
+        RuntimeOrigin::signed(PROVIDER),
+        region(),
+        99,
+        HostCapacity {
+            vcpu: 64,
+            memory_gb: 256,
+            storage_gb: 2_000,
+            gpu_count: 0,
+        },
+        None,
+        slice_prices()
+    ));
+
+    host_id
+}
+
+fn escrow() -> u64 {
+    KumulusPalletId::get().into_account_truncating()
+}
+
+#[test]
+fn renting_escrows_two_periods() {
+    build_and_execute(|| {
+        let resource_id = register_provider_with_resource();
+
+        assert_ok!(Kumulus::rent_resource(
+            RuntimeOrigin::signed(RENTER),
+            resource_id,
+            BillingPeriod::Weekly,
+            None
+        ));
+
+        assert_eq!(Balances::free_balance(escrow()), 2 * BASE_PRICE as u64);
+        assert_eq!(
+            Deposits::<Test>::get(resource_id),
+            Some(2 * BASE_PRICE as u64)
+        );
+        assert!(!Resources::<Test>::get(resource_id).unwrap().is_available);
+
+        System::assert_last_event(
+            Event::ResourceRented {
+                resource_id,
+                renter: RENTER,
+            }
+            .into(),
+        );
+    });
+}
+
+#[test]
+fn claim_payment_draws_from_deposit() {
+    build_and_execute(|| {
+        let resource_id = register_provider_with_resource();
+
+        assert_ok!(Kumulus::rent_resource(
+            RuntimeOrigin::signed(RENTER),
+            resource_id,
+            BillingPeriod::Weekly,
+            None
+        ));
+
+        assert_noop!(
+            Kumulus::claim_payment(RuntimeOrigin::signed(PROVIDER), resource_id),
+            Error::<Test>::NoPaymentDue
+        );
+
+        System::set_block_number(1 + BLOCKS_PER_WEEK);
+        assert_ok!(Kumulus::claim_payment(
+            RuntimeOrigin::signed(PROVIDER),
+            resource_id
+        ));
+
+        assert_eq!(
+            Balances::free_balance(PROVIDER),
+            INITIAL_BALANCE - PROVIDER_BOND + BASE_PRICE as u64
+        );
+        assert_eq!(Deposits::<Test>::get(resource_id), Some(BASE_PRICE as u64));
+    });
+}
+
+#[test]
+fn cancel_rental_settles_deposit() {
+    build_and_execute(|| {
+        let resource_id = register_provider_with_resource();
+
+        assert_ok!(Kumulus::rent_resource(
+            RuntimeOrigin::signed(RENTER),
+            resource_id,
+            BillingPeriod::Weekly,
+            None
+        ));
+
+        assert_noop!(
+            Kumulus::cancel_rental(RuntimeOrigin::signed(PROVIDER), resource_id),
+            Error::<Test>::NotRenter
+        );
+
+        // A quarter of the week has been used
+        System::set_block_number(1 + BLOCKS_PER_WEEK / 4);
+        assert_ok!(Kumulus::cancel_rental(
+            RuntimeOrigin::signed(RENTER),
+            resource_id
+        ));
+
+        let used = BASE_PRICE as u64 / 4;
+        assert_eq!(
+            Balances::free_balance(PROVIDER),
+            INITIAL_BALANCE - PROVIDER_BOND + used
+        );
+        assert_eq!(Balances::free_balance(RENTER), INITIAL_BALANCE - used);
+        assert_eq!(Balances::free_balance(escrow()), 0);
+
+        assert!(Rentals::<Test>::get(resource_id).is_none());
+        assert!(Deposits::<Test>::get(resource_id).is_none());
+        assert!(Resources::<Test>::get(resource_id).unwrap().is_available);
+    });
+}
+
+#[test]
+fn cancelling_pays_the_provider_for_the_blocks_used() {
+    build_and_execute(|| {
+        let resource_id = register_provider_with_resource();
+
+        assert_ok!(Kumulus::rent_resource(
+            RuntimeOrigin::signed(RENTER),
+            resource_id,
+            BillingPeriod::Weekly,
+            None
+        ));
+        System::set_block_number(1 + BLOCKS_PER_WEEK);
+        assert_ok!(Kumulus::claim_payment(
+            RuntimeOrigin::signed(PROVIDER),
+            resource_id
+        ));
+
+        // Only the blocks since the last claim are due
+        System::set_block_number(1 + BLOCKS_PER_WEEK + BLOCKS_PER_WEEK / 2);
+        assert_ok!(Kumulus::cancel_rental(
+            RuntimeOrigin::signed(RENTER),
+            resource_id
+        ));
+
+        let paid = BASE_PRICE as u64 + BASE_PRICE as u64 / 2;
+        assert_eq!(
+            Balances::free_balance(PROVIDER),
+            INITIAL_BALANCE - PROVIDER_BOND + paid
+        );
+        assert_eq!(Balances::free_balance(RENTER), INITIAL_BALANCE - paid);
+    });
+}
+
+#[test]
+fn claims_are_capped_by_the_deposit() {
+    build_and_execute(|| {
+        let resource_id = register_provider_with_resource();
+
+        assert_ok!(Kumulus::rent_resource(
+            RuntimeOrigin::signed(RENTER),
+            resource_id,
+            BillingPeriod::Weekly,
+            None
+        ));
+
+        // Three weeks are due, only two were deposited
+        System::set_block_number(1 + 3 * BLOCKS_PER_WEEK);
+        assert_ok!(Kumulus::claim_payment(
+            RuntimeOrigin::signed(PROVIDER),
+            resource_id
+        ));
+        System::assert_has_event(
+            Event::PaymentClaimed {
+                resource_id,
+                provider: PROVIDER,
+                amount: 2 * BASE_PRICE as u64,
+            }
+            .into(),
+        );
+        assert_eq!(
+            Balances::free_balance(RENTER),
+            INITIAL_BALANCE - 2 * BASE_PRICE as u64
+        );
+    });
+}
+
+#[test]
+fn escrow_can_be_emptied_by_the_last_settlement() {
+    build_and_execute(|| {
+        let resource_id = register_provider_with_resource();
+
+        assert_ok!(Kumulus::rent_resource(
+            RuntimeOrigin::signed(RENTER),
+            resource_id,
+            BillingPeriod::Weekly,
+            None
+        ));
+        assert_eq!(Balances::free_balance(escrow()), 2 * BASE_PRICE as u64);
+
+        // Nothing is kept back for the existential deposit of the escrow
+        System::set_block_number(1 + 2 * BLOCKS_PER_WEEK);
+        assert_ok!(Kumulus::claim_payment(
+            RuntimeOrigin::signed(PROVIDER),
+            resource_id
+        ));
+        assert_eq!(Balances::free_balance(escrow()), 0);
+        assert!(Deposits::<Test>::get(resource_id).is_none());
+    });
+}
+
+#[test]
+fn resources_are_registered_to_the_signer() {
+    build_and_execute(|| {
+        let resource_id = register_provider_with_resource();
+        let claimed = NextResourceId::<Test>::get();
+
+        // Whatever provider is claimed, the signer owns the resource
+        assert_ok!(Kumulus::register_resource(
+            RuntimeOrigin::signed(PROVIDER),
+            resource(RENTER)
+        ));
+        assert_eq!(Resources::<Test>::get(claimed).unwrap().provider, PROVIDER);
+        assert_eq!(
+            ProviderResourceCount::<Test>::get(PROVIDER, resource(PROVIDER).category),
+            2
+        );
+        assert!(ProviderResources::<Test>::contains_key(
+            PROVIDER,
+            resource_id
+        ));
+        assert!(ProviderResources::<Test>::contains_key(PROVIDER, claimed));
+    });
+}
+
+#[test]
+fn bootstrapper_management_requires_admin() {
+    build_and_execute(|| {
+        assert_noop!(
+            Kumulus::add_bootstrapper(
+                RuntimeOrigin::signed(BOOTSTRAPPER),
+                BOOTSTRAPPER,
+                BootstrapperType::Kollectyve,
+                10,
+                1
+            ),
+            DispatchError::BadOrigin
+        );
+
+        assert_noop!(
+            Kumulus::add_bootstrapper(
+                RuntimeOrigin::root(),
+                BOOTSTRAPPER,
+                BootstrapperType::Kollectyve,
+                1,
+                1
+            ),
+            Error::<Test>::InvalidBootstrapperExpiry
+        );
+
+        assert_noop!(
+            Kumulus::add_bootstrapper(
+                RuntimeOrigin::root(),
+                BOOTSTRAPPER,
+                BootstrapperType::Kollectyve,
+                10,
+                5
+            ),
+            Error::<Test>::BootstrapperResourceCapTooHigh
+        );
+
+        assert_ok!(Kumulus::add_bootstrapper(
+            RuntimeOrigin::root(),
+            BOOTSTRAPPER,
+            BootstrapperType::Kollectyve,
+            10,
+            1
+        ));
+
+        assert_noop!(
+            Kumulus::remove_bootstrapper(RuntimeOrigin::signed(BOOTSTRAPPER), BOOTSTRAPPER),
+            DispatchError::BadOrigin
+        );
+
+        assert_ok!(Kumulus::remove_bootstrapper(
+            RuntimeOrigin::root(),
+            BOOTSTRAPPER
+        ));
+
+        assert!(Bootstrappers::<Test>::get(BOOTSTRAPPER).is_none());
+        assert!(BootstrapperExpiries::<Test>::get(10).is_empty());
+        System::assert_last_event(Event::BootstrapperRemoved { who: BOOTSTRAPPER }.into());
+    });
+}
+
+#[test]
+fn bootstrapped_resources_are_capped_and_rentable() {
+    build_and_execute(|| {
+        assert_ok!(Kumulus::add_bootstrapper(
+            RuntimeOrigin::root(),
+            BOOTSTRAPPER,
+            BootstrapperType::Kollectyve,
+            10,
+            1
+        ));
+
+        // Whatever provider is claimed, the bootstrapper owns the resource
+        let resource_id = NextResourceId::<Test>::get();
+        assert_ok!(Kumulus::register_bootstrapped_resource(
+            RuntimeOrigin::signed(BOOTSTRAPPER),
+            resource(PROVIDER)
+        ));
+        assert_eq!(
+            Resources::<Test>::get(resource_id).unwrap().provider,
+            BOOTSTRAPPER
+        );
+
+        assert_noop!(
+            Kumulus::register_bootstrapped_resource(
+                RuntimeOrigin::signed(BOOTSTRAPPER),
+                resource(BOOTSTRAPPER)
+            ),
+            Error::<Test>::BootstrapperResourceCapReached
+        );
+
+        assert_ok!(Kumulus::rent_resource(
+            RuntimeOrigin::signed(RENTER),
+            resource_id,
+            BillingPeriod::Weekly,
+            None
+        ));
+    });
+}
+
+#[test]
+fn expired_bootstrapper_is_cleaned_up() {
+    build_and_execute(|| {
+        assert_ok!(Kumulus::add_bootstrapper(
+            RuntimeOrigin::root(),
+            BOOTSTRAPPER,
+            BootstrapperType::Kollectyve,
+            11,
+            1
+        ));
+
+        let resource_id = NextResourceId::<Test>::get();
+        assert_ok!(Kumulus::register_bootstrapped_resource(
+            RuntimeOrigin::signed(BOOTSTRAPPER),
+            resource(BOOTSTRAPPER)
+        ));
+
+        assert_ok!(Kumulus::rent_resource(
+            RuntimeOrigin::signed(RENTER),
+            resource_id,
+            BillingPeriod::Weekly,
+            None
+        ));
+
+        System::set_block_number(11);
+        Kumulus::on_initialize(11);
+
+        // 10 blocks of the week were used, the rest is refunded
+        let used = BASE_PRICE as u64 / 10;
+        assert_eq!(Balances::free_balance(RENTER), INITIAL_BALANCE - used);
+
+        assert!(Bootstrappers::<Test>::get(BOOTSTRAPPER).is_none());
+        assert!(Resources::<Test>::get(resource_id).is_none());
+        assert!(Rentals::<Test>::get(resource_id).is_none());
+        assert!(BootstrappedResources::<Test>::get(BOOTSTRAPPER, resource_id).is_none());
+
+        System::assert_has_event(Event::BootstrapperExpired { who: BOOTSTRAPPER }.into());
+    });
+}
+
+#[test]
+fn bootstrappers_are_kept_until_their_renters_are_refunded() {
+    build_and_execute(|| {
+        assert_ok!(Kumulus::add_bootstrapper(
+            RuntimeOrigin::root(),
+            BOOTSTRAPPER,
+            BootstrapperType::Kollectyve,
+            11,
+            1
+        ));
+        let resource_id = NextResourceId::<Test>::get();
+        assert_ok!(Kumulus::register_bootstrapped_resource(
+            RuntimeOrigin::signed(BOOTSTRAPPER),
+            resource(BOOTSTRAPPER)
+        ));
+        assert_ok!(Kumulus::rent_resource(
+            RuntimeOrigin::signed(RENTER),
+            resource_id,
+            BillingPeriod::Weekly,
+            None
+        ));
+        let deposit = 2 * BASE_PRICE as u64;
+
+        // The refund can't be paid from the escrow
+        assert_ok!(Balances::force_set_balance(
+            RuntimeOrigin::root(),
+            escrow(),
+            0
+        ));
+        System::set_block_number(6);
+        assert!(Kumulus::remove_bootstrapper(RuntimeOrigin::root(), BOOTSTRAPPER).is_err());
+
+        System::set_block_number(11);
+        Kumulus::on_initialize(11);
+        assert!(System::events().iter().any(|record| matches!(
+            &record.event,
+            RuntimeEvent::Kumulus(Event::BootstrapperRemovalFailed { who, .. })
+                if *who == BOOTSTRAPPER
+        )));
+        assert!(Bootstrappers::<Test>::contains_key(BOOTSTRAPPER));
+        assert!(BootstrappedResources::<Test>::contains_key(
+            BOOTSTRAPPER,
+            resource_id
+        ));
+        assert!(Rentals::<Test>::contains_key(resource_id));
+        assert_eq!(Deposits::<Test>::get(resource_id), Some(deposit));
+
+        // Expired, it can't register more resources and is left to the admin
+        assert_noop!(
+            Kumulus::register_bootstrapped_resource(
+                RuntimeOrigin::signed(BOOTSTRAPPER),
+                resource(BOOTSTRAPPER)
+            ),
+            Error::<Test>::BootstrapperExpired
+        );
+        assert_ok!(Balances::force_set_balance(
+            RuntimeOrigin::root(),
+            escrow(),
+            deposit
+        ));
+        assert_ok!(Kumulus::remove_bootstrapper(
+            RuntimeOrigin::root(),
+            BOOTSTRAPPER
+        ));
+
+        // 10 blocks of the week were used, the rest is refunded
+        let used = BASE_PRICE as u64 / 10;
+        assert_eq!(Balances::free_balance(RENTER), INITIAL_BALANCE - used);
+        assert!(Bootstrappers::<Test>::get(BOOTSTRAPPER).is_none());
+        assert!(Resources::<Test>::get(resource_id).is_none());
+        assert!(Deposits::<Test>::get(resource_id).is_none());
+        System::assert_last_event(Event::BootstrapperRemoved { who: BOOTSTRAPPER }.into());
+    });
+}
+
+#[test]
+fn bootstrapper_requests_await_approval() {
+    build_and_execute(|| {
+        assert_ok!(Kumulus::request_bootstrapper(RuntimeOrigin::signed(
+            BOOTSTRAPPER
+        )));
+        assert_noop!(
+            Kumulus::request_bootstrapper(RuntimeOrigin::signed(BOOTSTRAPPER)),
+            Error::<Test>::BootstrapperRequestPending
+        );
+
+        // Pending requests are not bootstrappers yet
+        assert_noop!(
+            Kumulus::register_bootstrapped_resource(
+                RuntimeOrigin::signed(BOOTSTRAPPER),
+                resource(BOOTSTRAPPER)
+            ),
+            Error::<Test>::NotBootstrapper
+        );
+
+        assert_ok!(Kumulus::request_bootstrapper(RuntimeOrigin::signed(RENTER)));
+        assert_noop!(
+            Kumulus::request_bootstrapper(RuntimeOrigin::signed(PROVIDER)),
+            Error::<Test>::TooManyPendingBootstrappers
+        );
+
+        assert_ok!(Kumulus::reject_bootstrapper(RuntimeOrigin::root(), RENTER));
+        assert_noop!(
+            Kumulus::approve_bootstrapper(
+                RuntimeOrigin::root(),
+                RENTER,
+                BootstrapperType::Else,
+                10,
+                1
+            ),
+            Error::<Test>::BootstrapperRequestNotFound
+        );
+
+        assert_ok!(Kumulus::approve_bootstrapper(
+            RuntimeOrigin::root(),
+            BOOTSTRAPPER,
+            BootstrapperType::Else,
+            10,
+            1
+        ));
+
+        assert!(PendingBootstrappers::<Test>::get(BOOTSTRAPPER).is_none());
+        assert!(Bootstrappers::<Test>::contains_key(BOOTSTRAPPER));
+    });
+}
+
+#[test]
+fn try_state_detects_dangling_rental() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Kumulus::do_try_state());
+
+        Rentals::<Test>::insert(
+            7,
+            Rental {
+                resource_id: 7,
+                renter: RENTER,
+                start_block: 1,
+                billing_period: BillingPeriod::Weekly,
+                last_paid_block: 1,
+                is_active: true,
+            },
+        );
+
+        assert!(Kumulus::do_try_state().is_err());
+    });
+}
+
+#[test]
+fn billing_periods_are_priced_pro_rata() {
+    let category = ResourceCategory::Nano(0, 0);
+
+    assert_eq!(category.price(&BillingPeriod::Weekly, 1_680u64), BASE_PRICE);
+    assert_eq!(
+        category.price(&BillingPeriod::Monthly, 1_680u64),
+        BASE_PRICE * 4
+    );
+    assert_eq!(
+        category.price(&BillingPeriod::Daily, 1_680u64),
+        BASE_PRICE / 7
+    );
+    assert_eq!(
+        category.price(&BillingPeriod::Hourly, 1_680u64),
+        BASE_PRICE / 168
+    );
+    assert_eq!(
+        category.price(&BillingPeriod::Custom(840u64), 1_680u64),
+        BASE_PRICE / 2
+    );
+    assert_eq!(
+        category.price(&BillingPeriod::PayAsYouGo, 1_680u64),
+        BASE_PRICE / 1_680
+    );
+}
+
+#[test]
+fn empty_billing_periods_are_rejected() {
+    build_and_execute(|| {
+        let resource_id = register_provider_with_resource();
+
+        // An hour is shorter than a block with 100 blocks per week
+        assert_noop!(
+            Kumulus::rent_resource(
+                RuntimeOrigin::signed(RENTER),
+                resource_id,
+                BillingPeriod::Hourly,
+                None
+            ),
+            Error::<Test>::InvalidBillingPeriod
+        );
+
+        assert_noop!(
+            Kumulus::rent_resource(
+                RuntimeOrigin::signed(RENTER),
+                resource_id,
+                BillingPeriod::Custom(0),
+                None
+            ),
+            Error::<Test>::InvalidBillingPeriod
+        );
+    });
+}
+
+#[test]
+fn pay_as_you_go_settles_per_block_until_exhausted() {
+    build_and_execute(|| {
+        let resource_id = register_provider_with_resource();
+        let block_price = BASE_PRICE as u64 / BLOCKS_PER_WEEK;
+
+        assert_ok!(Kumulus::rent_resource(
+            RuntimeOrigin::signed(RENTER),
+            resource_id,
+            BillingPeriod::PayAsYouGo,
+            None
+        ));
+        assert_ok!(Kumulus::top_up_rental(
+            RuntimeOrigin::signed(RENTER),
+            resource_id,
+            10 * block_price
+        ));
+
+        let prepaid = 10 * block_price + 1;
+        assert_eq!(Deposits::<Test>::get(resource_id), Some(prepaid));
+
+        System::set_block_number(6);
+        assert_ok!(Kumulus::claim_payment(
+            RuntimeOrigin::signed(PROVIDER),
+            resource_id
+        ));
+        assert_eq!(
+            Deposits::<Test>::get(resource_id),
+            Some(prepaid - 5 * block_price)
+        );
+
+        // Only what is left of the prepaid balance can be claimed
+        System::set_block_number(26);
+        assert_ok!(Kumulus::claim_payment(
+            RuntimeOrigin::signed(PROVIDER),
+            resource_id
+        ));
+
+        assert_eq!(
+            Balances::free_balance(PROVIDER),
+            INITIAL_BALANCE - PROVIDER_BOND + prepaid
+        );
+        assert!(Rentals::<Test>::get(resource_id).is_none());
+        assert!(Resources::<Test>::get(resource_id).unwrap().is_available);
+
+        System::assert_last_event(
+            Event::RentalExpired {
+                resource_id,
+                renter: RENTER,
+            }
+            .into(),
+        );
+    });
+}
+
+#[test]
+fn usage_is_settled_with_claims_after_dispute_window() {
+    build_and_execute(|| {
+        let resource_id = register_provider_with_resource();
+
+        let rate_card = RateCard {
+            cpu_second: 1_000,
+            egress_gb: 100_000,
+            storage_gb_hour: 10,
+        };
+        assert_ok!(Kumulus::set_rate_card(
+            RuntimeOrigin::signed(PROVIDER),
+            rate_card
+        ));
+
+        assert_ok!(Kumulus::rent_resource(
+            RuntimeOrigin::signed(RENTER),
+            resource_id,
+            BillingPeriod::Weekly,
+            None
+        ));
+
+        let report = |cpu_seconds| UsageReport {
+            cpu_seconds,
+            egress_gb: 2,
+            storage_gb_hours: 0,
+        };
+
+        assert_noop!(
+            Kumulus::submit_usage_report(
+                RuntimeOrigin::signed(PROVIDER),
+                resource_id,
+                report(1_001)
+            ),
+            Error::<Test>::UsageReportTooLarge
+        );
+        assert_noop!(
+            Kumulus::submit_usage_report(RuntimeOrigin::signed(RENTER), resource_id, report(100)),
+            Error::<Test>::NotResourceOwner
+        );
+
+        assert_ok!(Kumulus::submit_usage_report(
+            RuntimeOrigin::signed(PROVIDER),
+            resource_id,
+            report(100)
+        ));
+        assert_ok!(Kumulus::submit_usage_report(
+            RuntimeOrigin::signed(PROVIDER),
+            resource_id,
+            report(10)
+        ));
+        assert_noop!(
+            Kumulus::submit_usage_report(RuntimeOrigin::signed(PROVIDER), resource_id, report(1)),
+            Error::<Test>::TooManyUsageReports
+        );
+
+        // The second report is rejected by the renter
+        assert_ok!(Kumulus::dispute_usage_report(
+            RuntimeOrigin::signed(RENTER),
+            resource_id,
+            1
+        ));
+
+        System::set_block_number(USAGE_DISPUTE_WINDOW);
+        assert_noop!(
+            Kumulus::claim_payment(RuntimeOrigin::signed(PROVIDER), resource_id),
+            Error::<Test>::NoPaymentDue
+        );
+
+        System::set_block_number(1 + USAGE_DISPUTE_WINDOW);
+        assert_ok!(Kumulus::claim_payment(
+            RuntimeOrigin::signed(PROVIDER),
+            resource_id
+        ));
+
+        let usage = 100 * 1_000 + 2 * 100_000;
+        assert_eq!(
+            Balances::free_balance(PROVIDER),
+            INITIAL_BALANCE - PROVIDER_BOND + usage
+        );
+        assert!(UsageReports::<Test>::get(resource_id).is_empty());
+        System::assert_has_event(
+            Event::UsageSettled {
+                resource_id,
+                amount: usage,
+            }
+            .into(),
+        );
+
+        // Disputes are only possible during the window
+        assert_ok!(Kumulus::submit_usage_report(
+            RuntimeOrigin::signed(PROVIDER),
+            resource_id,
+            report(1)
+        ));
+        System::set_block_number(1 + 2 * USAGE_DISPUTE_WINDOW);
+        assert_noop!(
+            Kumulus::dispute_usage_report(RuntimeOrigin::signed(RENTER), resource_id, 2),
+            Error::<Test>::DisputeWindowClosed
+        );
+    });
+}
+
+#[test]
+fn usage_still_disputable_is_held_when_cancelling() {
+    build_and_execute(|| {
+        let resource_id = register_provider_with_resource();
+
+        assert_ok!(Kumulus::set_rate_card(
+            RuntimeOrigin::signed(PROVIDER),
+            RateCard {
+                cpu_second: 1_000,
+                egress_gb: 0,
+                storage_gb_hour: 0,
+            }
+        ));
+        assert_ok!(Kumulus::rent_resource(
+            RuntimeOrigin::signed(RENTER),
+            resource_id,
+            BillingPeriod::Weekly,
+            None
+        ));
+
+        let report = |cpu_seconds| UsageReport {
+            cpu_seconds,
+            egress_gb: 0,
+            storage_gb_hours: 0,
+        };
+        assert_ok!(Kumulus::submit_usage_report(
+            RuntimeOrigin::signed(PROVIDER),
+            resource_id,
+            report(100)
+        ));
+        assert_ok!(Kumulus::submit_usage_report(
+            RuntimeOrigin::signed(PROVIDER),
+            resource_id,
+            report(10)
+        ));
+
+        // Both reports are still disputable, they are kept out of the refund
+        assert_ok!(Kumulus::cancel_rental(
+            RuntimeOrigin::signed(RENTER),
+            resource_id
+        ));
+        assert_eq!(Balances::free_balance(escrow()), 110_000);
+        assert_eq!(
+            Balances::free_balance(PROVIDER),
+            INITIAL_BALANCE - PROVIDER_BOND
+        );
+        assert_eq!(HeldUsages::<Test>::iter_prefix(resource_id).count(), 2);
+
+        assert_ok!(Kumulus::dispute_usage_report(
+            RuntimeOrigin::signed(RENTER),
+            resource_id,
+            1
+        ));
+        assert_noop!(
+            Kumulus::settle_held_usage(RuntimeOrigin::signed(RENTER), resource_id, 0),
+            Error::<Test>::UsageDisputeWindowOpen
+        );
+
+        System::set_block_number(1 + USAGE_DISPUTE_WINDOW);
+        assert_noop!(
+            Kumulus::dispute_usage_report(RuntimeOrigin::signed(RENTER), resource_id, 0),
+            Error::<Test>::DisputeWindowClosed
+        );
+        assert_ok!(Kumulus::settle_held_usage(
+            RuntimeOrigin::signed(PROVIDER),
+            resource_id,
+            0
+        ));
+        assert_ok!(Kumulus::settle_held_usage(
+            RuntimeOrigin::signed(PROVIDER),
+            resource_id,
+            1
+        ));
+        System::assert_last_event(
+            Event::HeldUsageSettled {
+                resource_id,
+                report_id: 1,
+                paid: 0,
+                refunded: 10_000,
+            }
+            .into(),
+        );
+
+        assert_eq!(
+            Balances::free_balance(PROVIDER),
+            INITIAL_BALANCE - PROVIDER_BOND + 100_000
+        );
+        assert_eq!(Balances::free_balance(RENTER), INITIAL_BALANCE - 100_000);
+        assert_eq!(Balances::free_balance(escrow()), 0);
+    });
+}
+
+#[test]
+fn usage_beyond_the_deposit_is_owed_by_the_renter() {
+    build_and_execute(|| {
+        let resource_id = register_provider_with_resource();
+
+        assert_ok!(Kumulus::set_rate_card(
+            RuntimeOrigin::signed(PROVIDER),
+            RateCard {
+                cpu_second: 100_000_000,
+                egress_gb: 0,
+                storage_gb_hour: 0,
+            }
+        ));
+        assert_ok!(Kumulus::rent_resource(
+            RuntimeOrigin::signed(RENTER),
+            resource_id,
+            BillingPeriod::Weekly,
+            None
+        ));
+        assert_ok!(Kumulus::submit_usage_report(
+            RuntimeOrigin::signed(PROVIDER),
+            resource_id,
+            UsageReport {
+                cpu_seconds: 1_000,
+                egress_gb: 0,
+                storage_gb_hours: 0,
+            }
+        ));
+
+        // The usage drains the deposit, the rest is owed
+        let usage = 100_000_000_000;
+        let deposit = 2 * BASE_PRICE as u64;
+        System::set_block_number(1 + USAGE_DISPUTE_WINDOW);
+        assert_ok!(Kumulus::claim_payment(
+            RuntimeOrigin::signed(PROVIDER),
+            resource_id
+        ));
+        System::assert_has_event(
+            Event::UsageDebtIncurred {
+                renter: RENTER,
+                provider: PROVIDER,
+                amount: usage - deposit,
+            }
+            .into(),
+        );
+        assert!(Rentals::<Test>::get(resource_id).is_none());
+
+        assert_noop!(
+            Kumulus::rent_resource(
+                RuntimeOrigin::signed(RENTER),
+                resource_id,
+                BillingPeriod::Weekly,
+                None
+            ),
+            Error::<Test>::OutstandingUsageDebt
+        );
+
+        assert_ok!(Kumulus::pay_usage_debt(
+            RuntimeOrigin::signed(RENTER),
+            RENTER,
+            PROVIDER
+        ));
+        assert_eq!(
+            Balances::free_balance(PROVIDER),
+            INITIAL_BALANCE - PROVIDER_BOND + usage
+        );
+        assert_noop!(
+            Kumulus::pay_usage_debt(RuntimeOrigin::signed(RENTER), RENTER, PROVIDER),
+            Error::<Test>::NoUsageDebt
+        );
+        assert_ok!(Kumulus::rent_resource(
+            RuntimeOrigin::signed(RENTER),
+            resource_id,
+            BillingPeriod::Weekly,
+            None
+        ));
+    });
+}
+
+#[test]
+fn disputes_freeze_escrow_until_resolved() {
+    build_and_execute(|| {
+        let resource_id = register_provider_with_resource();
+        assert_eq!(Balances::reserved_balance(PROVIDER), PROVIDER_BOND);
+
+        assert_ok!(Kumulus::rent_resource(
+            RuntimeOrigin::signed(RENTER),
+            resource_id,
+            BillingPeriod::Weekly,
+            None
+        ));
+
+        let evidence = H256::repeat_byte(1);
+        assert_noop!(
+            Kumulus::open_dispute(RuntimeOrigin::signed(PROVIDER), resource_id, evidence),
+            Error::<Test>::NotRenter
+        );
+        assert_ok!(Kumulus::open_dispute(
+            RuntimeOrigin::signed(RENTER),
+            resource_id,
+            evidence
+        ));
+        assert_noop!(
+            Kumulus::open_dispute(RuntimeOrigin::signed(RENTER), resource_id, evidence),
+            Error::<Test>::DisputeAlreadyOpen
+        );
+
+        // Both parties can add evidence, nobody else
+        assert_ok!(Kumulus::submit_evidence(
+            RuntimeOrigin::signed(PROVIDER),
+            0,
+            H256::repeat_byte(2)
+        ));
+        assert_noop!(
+            Kumulus::submit_evidence(RuntimeOrigin::signed(BOOTSTRAPPER), 0, evidence),
+            Error::<Test>::NotDisputeParty
+        );
+
+        // The escrow is frozen
+        System::set_block_number(1 + BLOCKS_PER_WEEK);
+        assert_noop!(
+            Kumulus::claim_payment(RuntimeOrigin::signed(PROVIDER), resource_id),
+            Error::<Test>::EscrowFrozen
+        );
+        assert_noop!(
+            Kumulus::cancel_rental(RuntimeOrigin::signed(RENTER), resource_id),
+            Error::<Test>::EscrowFrozen
+        );
+
+        assert_noop!(
+            Kumulus::resolve_dispute(RuntimeOrigin::signed(RENTER), 0, Perbill::one(), 0),
+            DispatchError::BadOrigin
+        );
+        let slash = 2 * PROVIDER_BOND;
+        assert_ok!(Kumulus::resolve_dispute(
+            RuntimeOrigin::root(),
+            0,
+            Perbill::from_percent(75),
+            slash
+        ));
+
+        // The slash is capped by the bond
+        let deposit = 2 * BASE_PRICE as u64;
+        assert_eq!(
+            Balances::free_balance(RENTER),
+            INITIAL_BALANCE - deposit / 4 + PROVIDER_BOND
+        );
+        assert_eq!(
+            Balances::free_balance(PROVIDER),
+            INITIAL_BALANCE - PROVIDER_BOND + deposit / 4
+        );
+        assert_eq!(Balances::reserved_balance(PROVIDER), 0);
+        assert!(Rentals::<Test>::get(resource_id).is_none());
+        assert!(Resources::<Test>::get(resource_id).unwrap().is_available);
+
+        System::assert_last_event(
+            Event::DisputeResolved {
+                dispute_id: 0,
+                renter_amount: deposit * 3 / 4,
+                provider_amount: deposit / 4,
+                slashed: PROVIDER_BOND,
+            }
+            .into(),
+        );
+    });
+}
+
+#[test]
+fn ended_rentals_are_disputable_during_window() {
+    build_and_execute(|| {
+        let resource_id = register_provider_with_resource();
+
+        assert_ok!(Kumulus::rent_resource(
+            RuntimeOrigin::signed(RENTER),
+            resource_id,
+            BillingPeriod::Weekly,
+            None
+        ));
+        assert_ok!(Kumulus::cancel_rental(
+            RuntimeOrigin::signed(RENTER),
+            resource_id
+        ));
+
+        System::set_block_number(1 + DISPUTE_WINDOW);
+        assert_noop!(
+            Kumulus::open_dispute(RuntimeOrigin::signed(RENTER), resource_id, H256::zero()),
+            Error::<Test>::DisputeWindowClosed
+        );
+
+        System::set_block_number(DISPUTE_WINDOW);
+        assert_ok!(Kumulus::open_dispute(
+            RuntimeOrigin::signed(RENTER),
+            resource_id,
+            H256::zero()
+        ));
+        assert_ok!(Kumulus::resolve_dispute(
+            RuntimeOrigin::root(),
+            0,
+            Perbill::zero(),
+            10
+        ));
+
+        assert_eq!(Balances::reserved_balance(PROVIDER), PROVIDER_BOND - 10);
+        assert_eq!(ProviderBonds::<Test>::get(PROVIDER), PROVIDER_BOND - 10);
+        assert!(Disputes::<Test>::get(0).is_none());
+    });
+}
+
+#[test]
+fn disputes_of_ended_rentals_leave_the_next_renter_alone() {
+    build_and_execute(|| {
+        let resource_id = register_provider_with_resource();
+
+        assert_ok!(Kumulus::rent_resource(
+            RuntimeOrigin::signed(RENTER),
+            resource_id,
+            BillingPeriod::Weekly,
+            None
+        ));
+        assert_ok!(Kumulus::cancel_rental(
+            RuntimeOrigin::signed(RENTER),
+            resource_id
+        ));
+        assert_ok!(Kumulus::rent_resource(
+            RuntimeOrigin::signed(BOOTSTRAPPER),
+            resource_id,
+            BillingPeriod::Weekly,
+            None
+        ));
+
+        // The previous renter disputes its own rental, nothing is frozen
+        assert_ok!(Kumulus::open_dispute(
+            RuntimeOrigin::signed(RENTER),
+            resource_id,
+            H256::zero()
+        ));
+        assert!(!Disputes::<Test>::get(0).unwrap().rental_active);
+        assert!(ResourceDisputes::<Test>::get(resource_id).is_none());
+        assert_noop!(
+            Kumulus::open_dispute(RuntimeOrigin::signed(RENTER), resource_id, H256::zero()),
+            Error::<Test>::NotRenter
+        );
+
+        // The current renter can still dispute its rental, frozen until its own resolution
+        assert_ok!(Kumulus::open_dispute(
+            RuntimeOrigin::signed(BOOTSTRAPPER),
+            resource_id,
+            H256::zero()
+        ));
+        assert_eq!(ResourceDisputes::<Test>::get(resource_id), Some(1));
+        assert_ok!(Kumulus::resolve_dispute(
+            RuntimeOrigin::root(),
+            0,
+            Perbill::zero(),
+            10
+        ));
+        assert_eq!(ResourceDisputes::<Test>::get(resource_id), Some(1));
+        assert_ok!(Kumulus::resolve_dispute(
+            RuntimeOrigin::root(),
+            1,
+            Perbill::one(),
+            0
+        ));
+        assert!(Rentals::<Test>::get(resource_id).is_none());
+    });
+}
+
+#[test]
+fn orders_are_matched_with_the_cheapest_fitting_ask() {
+    build_and_execute(|| {
+        let cheap = register_provider_with_resource();
+        let faster = NextResourceId::<Test>::get();
+        assert_ok!(Kumulus::register_resource(
+            RuntimeOrigin::signed(PROVIDER),
+            Resource {
+                category: ResourceCategory::Nano(2, 0),
+                ..resource(PROVIDER)
+            }
+        ));
+
+        assert_noop!(
+            Kumulus::post_ask(RuntimeOrigin::signed(RENTER), cheap, 10),
+            Error::<Test>::NotResourceOwner
+        );
+        assert_ok!(Kumulus::post_ask(
+            RuntimeOrigin::signed(PROVIDER),
+            cheap,
+            1_000
+        ));
+        assert_ok!(Kumulus::post_ask(
+            RuntimeOrigin::signed(PROVIDER),
+            faster,
+            2_000
+        ));
+
+        let min_specs = ComputeSpecs {
+            vcpu: BASE_VCPU + 1,
+            ..Default::default()
+        };
+        assert_noop!(
+            Kumulus::place_order(
+                RuntimeOrigin::signed(RENTER),
+                min_specs.clone(),
+                None,
+                3_000,
+                BillingPeriod::Weekly,
+                0
+            ),
+            Error::<Test>::InvalidOrderDuration
+        );
+        assert_ok!(Kumulus::place_order(
+            RuntimeOrigin::signed(RENTER),
+            min_specs.clone(),
+            Some(region().country_code),
+            3_000,
+            BillingPeriod::Weekly,
+            3
+        ));
+        assert_eq!(Balances::reserved_balance(RENTER), ORDER_DEPOSIT);
+
+        // The cheapest ask is too small for the order
+        assert_noop!(
+            Kumulus::match_order(RuntimeOrigin::signed(BOOTSTRAPPER), 0, cheap),
+            Error::<Test>::OrderMismatch
+        );
+
+        Kumulus::on_idle(1, Weight::MAX);
+
+        System::assert_has_event(
+            Event::OrderMatched {
+                order_id: 0,
+                resource_id: faster,
+                price: 2_000,
+            }
+            .into(),
+        );
+        assert!(OrderQueue::<Test>::get().is_empty());
+        assert!(Asks::<Test>::get(faster).is_none());
+        assert_eq!(Balances::reserved_balance(RENTER), 0);
+        // The three weeks of the order are prepaid
+        assert_eq!(Deposits::<Test>::get(faster), Some(6_000));
+
+        // The rental is billed at the matched price
+        System::set_block_number(1 + BLOCKS_PER_WEEK);
+        assert_ok!(Kumulus::claim_payment(
+            RuntimeOrigin::signed(PROVIDER),
+            faster
+        ));
+        assert_eq!(Deposits::<Test>::get(faster), Some(4_000));
+
+        // Orders placed later can be matched explicitly
+        assert_ok!(Kumulus::place_order(
+            RuntimeOrigin::signed(RENTER),
+            ComputeSpecs::default(),
+            None,
+            1_000,
+            BillingPeriod::Weekly,
+            1
+        ));
+        assert_ok!(Kumulus::match_order(
+            RuntimeOrigin::signed(BOOTSTRAPPER),
+            1,
+            cheap
+        ));
+        assert_eq!(Rentals::<Test>::get(cheap).unwrap().renter, RENTER);
+        assert!(Orders::<Test>::get(1).is_none());
+        // Never less than the two periods of any rental
+        assert_eq!(Deposits::<Test>::get(cheap), Some(2_000));
+    });
+}
+
+#[test]
+fn unmatched_orders_expire_with_their_deposit_back() {
+    build_and_execute(|| {
+        let resource_id = register_provider_with_resource();
+
+        assert_ok!(Kumulus::place_order(
+            RuntimeOrigin::signed(RENTER),
+            ComputeSpecs::default(),
+            None,
+            1_000,
+            BillingPeriod::Weekly,
+            1
+        ));
+        assert_eq!(Balances::reserved_balance(RENTER), ORDER_DEPOSIT);
+
+        // Nothing on offer, the order waits
+        Kumulus::on_idle(1, Weight::MAX);
+        assert_eq!(OrderQueue::<Test>::get().len(), 1);
+
+        System::set_block_number(1 + ORDER_LIFETIME);
+        assert_ok!(Kumulus::post_ask(
+            RuntimeOrigin::signed(PROVIDER),
+            resource_id,
+            1_000
+        ));
+        assert_noop!(
+            Kumulus::match_order(RuntimeOrigin::signed(BOOTSTRAPPER), 0, resource_id),
+            Error::<Test>::OrderExpired
+        );
+
+        Kumulus::on_idle(1 + ORDER_LIFETIME, Weight::MAX);
+        System::assert_last_event(Event::OrderExpired { order_id: 0 }.into());
+        assert!(OrderQueue::<Test>::get().is_empty());
+        assert!(Orders::<Test>::get(0).is_none());
+        assert_eq!(Balances::reserved_balance(RENTER), 0);
+        assert!(Rentals::<Test>::get(resource_id).is_none());
+    });
+}
+
+#[test]
+fn gpu_auctions_rent_to_the_highest_sealed_bid() {
+    build_and_execute(|| {
+        let cpu_only = register_provider_with_resource();
+        let gpu = register_gpu_resource();
+
+        assert_noop!(
+            Kumulus::create_auction(
+                RuntimeOrigin::signed(PROVIDER),
+                cpu_only,
+                AuctionKind::SecondPrice,
+                100,
+                BillingPeriod::Weekly,
+                5,
+                5
+            ),
+            Error::<Test>::NotGpuResource
+        );
+        assert_ok!(Kumulus::create_auction(
+            RuntimeOrigin::signed(PROVIDER),
+            gpu,
+            AuctionKind::SecondPrice,
+            100,
+            BillingPeriod::Weekly,
+            5,
+            5
+        ));
+        assert_noop!(
+            Kumulus::rent_resource(
+                RuntimeOrigin::signed(RENTER),
+                gpu,
+                BillingPeriod::Weekly,
+                None
+            ),
+            Error::<Test>::ResourceInAuction
+        );
+
+        let salt = H256::repeat_byte(7);
+        let commitment = |bid: u64| BlakeTwo256::hash_of(&(bid, salt));
+        assert_noop!(
+            Kumulus::commit_bid(RuntimeOrigin::signed(PROVIDER), gpu, commitment(1_000)),
+            Error::<Test>::OwnAuction
+        );
+        assert_ok!(Kumulus::commit_bid(
+            RuntimeOrigin::signed(RENTER),
+            gpu,
+            commitment(1_000)
+        ));
+        assert_ok!(Kumulus::commit_bid(
+            RuntimeOrigin::signed(BOOTSTRAPPER),
+            gpu,
+            commitment(600)
+        ));
+        assert_eq!(Balances::reserved_balance(RENTER), BID_DEPOSIT);
+
+        // Bids stay sealed until the commit phase is over
+        assert_noop!(
+            Kumulus::reveal_bid(RuntimeOrigin::signed(RENTER), gpu, 1_000, salt),
+            Error::<Test>::NotRevealPhase
+        );
+
+        System::set_block_number(6);
+        assert_noop!(
+            Kumulus::commit_bid(RuntimeOrigin::signed(WATCHERS[0]), gpu, commitment(1)),
+            Error::<Test>::BiddingClosed
+        );
+        assert_noop!(
+            Kumulus::reveal_bid(RuntimeOrigin::signed(RENTER), gpu, 900, salt),
+            Error::<Test>::InvalidBidReveal
+        );
+        assert_ok!(Kumulus::reveal_bid(
+            RuntimeOrigin::signed(RENTER),
+            gpu,
+            1_000,
+            salt
+        ));
+        assert_ok!(Kumulus::reveal_bid(
+            RuntimeOrigin::signed(BOOTSTRAPPER),
+            gpu,
+            600,
+            salt
+        ));
+
+        System::set_block_number(11);
+        Kumulus::on_initialize(11);
+
+        // The winner pays the second highest bid
+        System::assert_has_event(
+            Event::AuctionSettled {
+                resource_id: gpu,
+                winner: RENTER,
+                price: 600,
+            }
+            .into(),
+        );
+        assert_eq!(Balances::reserved_balance(RENTER), 0);
+        assert_eq!(Balances::reserved_balance(BOOTSTRAPPER), 0);
+        assert_eq!(Rentals::<Test>::get(gpu).unwrap().renter, RENTER);
+        assert_eq!(Deposits::<Test>::get(gpu), Some(1_200));
+        assert!(Auctions::<Test>::get(gpu).is_none());
+    });
+}
+
+#[test]
+fn auctions_fall_back_to_the_next_bidder_and_forfeit_unrevealed_bids() {
+    build_and_execute(|| {
+        register_provider_with_resource();
+        let gpu = register_gpu_resource();
+
+        assert_ok!(Kumulus::create_auction(
+            RuntimeOrigin::signed(PROVIDER),
+            gpu,
+            AuctionKind::SecondPrice,
+            100,
+            BillingPeriod::Weekly,
+            5,
+            5
+        ));
+
+        let salt = H256::repeat_byte(7);
+        let commitment = |bid: u64| BlakeTwo256::hash_of(&(bid, salt));
+        for (bidder, bid) in [(RENTER, 1_000), (BOOTSTRAPPER, 600), (WATCHERS[0], 800)] {
+            assert_ok!(Kumulus::commit_bid(
+                RuntimeOrigin::signed(bidder),
+                gpu,
+                commitment(bid)
+            ));
+        }
+
+        // The third bidder never reveals
+        System::set_block_number(6);
+        assert_ok!(Kumulus::reveal_bid(
+            RuntimeOrigin::signed(RENTER),
+            gpu,
+            1_000,
+            salt
+        ));
+        assert_ok!(Kumulus::reveal_bid(
+            RuntimeOrigin::signed(BOOTSTRAPPER),
+            gpu,
+            600,
+            salt
+        ));
+
+        // The highest bidder can't pay the deposit anymore
+        assert_ok!(Balances::force_set_balance(
+            RuntimeOrigin::root(),
+            RENTER,
+            1_000
+        ));
+
+        System::set_block_number(11);
+        Kumulus::on_initialize(11);
+
+        System::assert_has_event(
+            Event::BidForfeited {
+                resource_id: gpu,
+                bidder: WATCHERS[0],
+                amount: BID_DEPOSIT,
+            }
+            .into(),
+        );
+        System::assert_has_event(
+            Event::AuctionSettled {
+                resource_id: gpu,
+                winner: BOOTSTRAPPER,
+                price: 100,
+            }
+            .into(),
+        );
+        assert_eq!(Rentals::<Test>::get(gpu).unwrap().renter, BOOTSTRAPPER);
+        assert_eq!(Balances::reserved_balance(RENTER), 0);
+        assert_eq!(Balances::reserved_balance(WATCHERS[0]), 0);
+        assert_eq!(
+            Balances::free_balance(PROVIDER),
+            INITIAL_BALANCE - PROVIDER_BOND + BID_DEPOSIT
+        );
+    });
+}
+
+#[test]
+fn host_capacity_is_rented_in_slices() {
+    build_and_execute(|| {
+        register_provider_with_resource();
+        let host_id = register_host();
+
+        let slice = HostCapacity {
+            vcpu: 40,
+            memory_gb: 128,
+            storage_gb: 1_000,
+            gpu_count: 0,
+        };
+        let first = NextResourceId::<Test>::get();
+        assert_ok!(Kumulus::rent_slice(
+            RuntimeOrigin::signed(RENTER),
+            host_id,
+            slice.clone(),
+            BillingPeriod::Weekly
+        ));
+        assert_eq!(Rentals::<Test>::get(first).unwrap().renter, RENTER);
+        assert_eq!(Hosts::<Test>::get(host_id).unwrap().remaining.vcpu, 24);
+
+        // The rental references the allocation, no resource is registered for the slice
+        assert_eq!(
+            Allocations::<Test>::get(first),
+            Some((host_id, slice.clone()))
+        );
+        assert!(Resources::<Test>::get(first).is_none());
+        assert!(!ProviderResources::<Test>::contains_key(PROVIDER, first));
+
+        // Priced by capacity: 40 vCPUs, 128 GB of memory and 1000 GB of storage a week
+        let weekly = 40 * 100_000_000 + 128 * 10_000_000 + 1_000 * 1_000_000;
+        assert_eq!(Deposits::<Test>::get(first), Some(2 * weekly));
+
+        assert_noop!(
+            Kumulus::rent_slice(
+                RuntimeOrigin::signed(BOOTSTRAPPER),
+                host_id,
+                slice.clone(),
+                BillingPeriod::Weekly
+            ),
+            Error::<Test>::InsufficientCapacity
+        );
+        assert_noop!(
+            Kumulus::rent_slice(
+                RuntimeOrigin::signed(BOOTSTRAPPER),
+                host_id,
+                HostCapacity {
+                    vcpu: 1,
+                    gpu_count: 1,
+                    ..Default::default()
+                },
+                BillingPeriod::Weekly
+            ),
+            Error::<Test>::InsufficientCapacity
+        );
+
+        // Ending the rental gives the slice back to the host
+        assert_ok!(Kumulus::cancel_rental(RuntimeOrigin::signed(RENTER), first));
+        assert!(Allocations::<Test>::get(first).is_none());
+        assert_eq!(Hosts::<Test>::get(host_id).unwrap().remaining.vcpu, 64);
+        assert_eq!(EndedRentals::<Test>::get(first).unwrap().provider, PROVIDER);
+
+        assert_ok!(Kumulus::rent_slice(
+            RuntimeOrigin::signed(BOOTSTRAPPER),
+            host_id,
+            slice,
+            BillingPeriod::Weekly
+        ));
+    });
+}
+
+#[test]
+fn hosts_are_updated_and_deregistered_around_their_slices() {
+    build_and_execute(|| {
+        register_provider_with_resource();
+        let host_id = register_host();
+        let slice_id = NextResourceId::<Test>::get();
+        assert_ok!(Kumulus::rent_slice(
+            RuntimeOrigin::signed(RENTER),
+            host_id,
+            HostCapacity {
+                vcpu: 40,
+                memory_gb: 128,
+                storage_gb: 1_000,
+                gpu_count: 0,
+            },
+            BillingPeriod::Weekly
+        ));
+        let deposit = Deposits::<Test>::get(slice_id);
+
+        let capacity = |vcpu| HostCapacity {
+            vcpu,
+            memory_gb: 256,
+            storage_gb: 2_000,
+            gpu_count: 0,
+        };
+        assert_noop!(
+            Kumulus::update_host(
+                RuntimeOrigin::signed(RENTER),
+                host_id,
+                99,
+                capacity(128),
+                slice_prices()
+            ),
+            Error::<Test>::NotResourceOwner
+        );
+        // The rented slice must still fit
+        assert_noop!(
+            Kumulus::update_host(
+                RuntimeOrigin::signed(PROVIDER),
+                host_id,
+                99,
+                capacity(32),
+                slice_prices()
+            ),
+            Error::<Test>::HostInUse
+        );
+        assert_ok!(Kumulus::update_host(
+            RuntimeOrigin::signed(PROVIDER),
+            host_id,
+            95,
+            capacity(128),
+            SlicePrices {
+                vcpu: 1,
+                ..slice_prices()
+            }
+        ));
+        let host = Hosts::<Test>::get(host_id).unwrap();
+        assert_eq!((host.uptime_guarantee, host.remaining.vcpu), (95, 88));
+        // The rented slice keeps its price
+        assert_eq!(Deposits::<Test>::get(slice_id), deposit);
+
+        assert_noop!(
+            Kumulus::deregister_host(RuntimeOrigin::signed(PROVIDER), host_id),
+            Error::<Test>::HostInUse
+        );
+        assert_ok!(Kumulus::cancel_rental(
+            RuntimeOrigin::signed(RENTER),
+            slice_id
+        ));
+        assert_noop!(
+            Kumulus::deregister_host(RuntimeOrigin::signed(RENTER), host_id),
+            Error::<Test>::NotResourceOwner
+        );
+        assert_ok!(Kumulus::deregister_host(
+            RuntimeOrigin::signed(PROVIDER),
+            host_id
+        ));
+        assert!(Hosts::<Test>::get(host_id).is_none());
+        assert!(!ProviderHosts::<Test>::contains_key(PROVIDER, host_id));
+        System::assert_last_event(
+            Event::HostDeregistered {
+                host_id,
+                provider: PROVIDER,
+            }
+            .into(),
+        );
+    });
+}
+
+#[test]
+fn commitments_are_discounted_and_reserved() {
+    build_and_execute(|| {
+        let resource_id = register_provider_with_resource();
+
+        assert_ok!(Kumulus::rent_resource(
+            RuntimeOrigin::signed(RENTER),
+            resource_id,
+            BillingPeriod::Weekly,
+            Some(CommitmentTerm::SixMonths)
+        ));
+
+        // 15% off the weekly price
+        let weekly = BASE_PRICE as u64 * 85 / 100;
+        assert_eq!(Deposits::<Test>::get(resource_id), Some(2 * weekly));
+
+        System::set_block_number(1 + BLOCKS_PER_WEEK);
+        assert_ok!(Kumulus::claim_payment(
+            RuntimeOrigin::signed(PROVIDER),
+            resource_id
+        ));
+        assert_eq!(
+            Balances::free_balance(PROVIDER),
+            INITIAL_BALANCE - PROVIDER_BOND + weekly
+        );
+
+        // Terminating early pays half of the 23 weeks left
+        assert_ok!(Kumulus::cancel_rental(
+            RuntimeOrigin::signed(RENTER),
+            resource_id
+        ));
+        let penalty = weekly * 23 / 2;
+        System::assert_has_event(
+            Event::CommitmentTerminated {
+                resource_id,
+                penalty,
+            }
+            .into(),
+        );
+        assert_eq!(
+            Balances::free_balance(PROVIDER),
+            INITIAL_BALANCE - PROVIDER_BOND + weekly + penalty
+        );
+        assert!(Commitments::<Test>::get(resource_id).is_none());
+    });
+}
+
+#[test]
+fn commitments_end_with_their_rental() {
+    build_and_execute(|| {
+        let resource_id = register_provider_with_resource();
+
+        assert_noop!(
+            Kumulus::rent_resource(
+                RuntimeOrigin::signed(RENTER),
+                resource_id,
+                BillingPeriod::PayAsYouGo,
+                Some(CommitmentTerm::TwelveMonths)
+            ),
+            Error::<Test>::InvalidBillingPeriod
+        );
+        assert_ok!(Kumulus::rent_resource(
+            RuntimeOrigin::signed(RENTER),
+            resource_id,
+            BillingPeriod::Weekly,
+            Some(CommitmentTerm::TwelveMonths)
+        ));
+
+        // The deposit runs out with 46 of the 48 weeks left, half of them are due
+        System::set_block_number(1 + 2 * BLOCKS_PER_WEEK);
+        assert_ok!(Kumulus::claim_payment(
+            RuntimeOrigin::signed(PROVIDER),
+            resource_id
+        ));
+        assert!(Rentals::<Test>::get(resource_id).is_none());
+        let weekly = BASE_PRICE as u64 * 70 / 100;
+        System::assert_has_event(
+            Event::CommitmentTerminated {
+                resource_id,
+                penalty: weekly * 46 / 2,
+            }
+            .into(),
+        );
+
+        // No longer reserved to the renter
+        assert!(Commitments::<Test>::get(resource_id).is_none());
+        assert_ok!(Kumulus::rent_resource(
+            RuntimeOrigin::signed(BOOTSTRAPPER),
+            resource_id,
+            BillingPeriod::Weekly,
+            None
+        ));
+    });
+}
+
+#[test]
+fn deployments_are_versioned_per_rental() {
+    build_and_execute(|| {
+        let resource_id = register_provider_with_resource();
+        let manifest = |vcpu| {
+            DeploymentManifest::Container(ContainerManifest {
+                image: ImageDigest::truncate_from(b"sha256:0123".to_vec()),
+                ports: DeploymentPorts::truncate_from(vec![443]),
+                env_hash: H256::repeat_byte(1),
+                requests: ComputeSpecs {
+                    vcpu,
+                    ..Default::default()
+                },
+            })
+        };
+
+        assert_noop!(
+            Kumulus::update_deployment(RuntimeOrigin::signed(RENTER), resource_id, manifest(1)),
+            Error::<Test>::RentalNotFound
+        );
+
+        assert_ok!(Kumulus::rent_resource(
+            RuntimeOrigin::signed(RENTER),
+            resource_id,
+            BillingPeriod::Weekly,
+            None
+        ));
+
+        assert_noop!(
+            Kumulus::update_deployment(RuntimeOrigin::signed(RENTER), resource_id, manifest(8)),
+            Error::<Test>::ManifestExceedsResource
+        );
+        assert_ok!(Kumulus::update_deployment(
+            RuntimeOrigin::signed(RENTER),
+            resource_id,
+            manifest(1)
+        ));
+        assert_ok!(Kumulus::update_deployment(
+            RuntimeOrigin::signed(RENTER),
+            resource_id,
+            DeploymentManifest::OffChain(H256::repeat_byte(2))
+        ));
+
+        System::assert_last_event(
+            Event::DeploymentUpdated {
+                resource_id,
+                version: 2,
+                manifest: DeploymentManifest::OffChain(H256::repeat_byte(2)),
+            }
+            .into(),
+        );
+
+        assert_ok!(Kumulus::cancel_rental(
+            RuntimeOrigin::signed(RENTER),
+            resource_id
+        ));
+        assert!(Deployments::<Test>::get(resource_id).is_none());
+    });
+}
+
+#[test]
+fn access_bundles_are_encrypted_to_the_renter_key() {
+    build_and_execute(|| {
+        let resource_id = register_provider_with_resource();
+        assert_ok!(Kumulus::rent_resource(
+            RuntimeOrigin::signed(RENTER),
+            resource_id,
+            BillingPeriod::Weekly,
+            None
+        ));
+
+        let bundle = EncryptedBundle::truncate_from(vec![1; 64]);
+        assert_noop!(
+            Kumulus::post_access_bundle(
+                RuntimeOrigin::signed(PROVIDER),
+                resource_id,
+                bundle.clone()
+            ),
+            Error::<Test>::AccessKeyNotSet
+        );
+
+        assert_noop!(
+            Kumulus::set_access_key(
+                RuntimeOrigin::signed(PROVIDER),
+                resource_id,
+                AccessKey::X25519([1; 32])
+            ),
+            Error::<Test>::NotRenter
+        );
+        assert_ok!(Kumulus::set_access_key(
+            RuntimeOrigin::signed(RENTER),
+            resource_id,
+            AccessKey::X25519([1; 32])
+        ));
+        assert_ok!(Kumulus::post_access_bundle(
+            RuntimeOrigin::signed(PROVIDER),
+            resource_id,
+            bundle.clone()
+        ));
+
+        // Rotation
+        assert_ok!(Kumulus::set_access_key(
+            RuntimeOrigin::signed(RENTER),
+            resource_id,
+            AccessKey::Sr25519([2; 32])
+        ));
+        assert_eq!(
+            AccessBundles::<Test>::get(resource_id).unwrap().key,
+            AccessKey::X25519([1; 32])
+        );
+        assert_ok!(Kumulus::post_access_bundle(
+            RuntimeOrigin::signed(PROVIDER),
+            resource_id,
+            bundle
+        ));
+
+        let posted = AccessBundles::<Test>::get(resource_id).unwrap();
+        assert_eq!(posted.key, AccessKey::Sr25519([2; 32]));
+        assert_eq!(posted.version, 2);
+
+        assert_ok!(Kumulus::cancel_rental(
+            RuntimeOrigin::signed(RENTER),
+            resource_id
+        ));
+        assert!(AccessBundles::<Test>::get(resource_id).is_none());
+        assert!(AccessKeys::<Test>::get(resource_id).is_none());
+    });
+}
+
+#[test]
+fn heartbeats_are_recorded_for_providers() {
+    build_and_execute(|| {
+        assert_noop!(
+            Kumulus::heartbeat(RuntimeOrigin::signed(PROVIDER)),
+            Error::<Test>::ProviderNotRegistered
+        );
+
+        register_provider_with_resource();
+        System::set_block_number(5);
+        assert_ok!(Kumulus::heartbeat(RuntimeOrigin::signed(PROVIDER)));
+
+        assert_eq!(Heartbeats::<Test>::get(PROVIDER), Some(5));
+    });
+}
+
+#[test]
+fn offchain_worker_reports_healthy_resources() {
+    let (offchain, offchain_state) = TestOffchainExt::new();
+    let (pool, pool_state) = TestTransactionPoolExt::new();
+
+    let mut ext = new_test_ext();
+    ext.register_extension(OffchainDbExt::new(offchain.clone()));
+    ext.register_extension(OffchainWorkerExt::new(offchain));
+    ext.register_extension(TransactionPoolExt::new(pool));
+
+    ext.execute_with(|| {
+        let healthy = register_provider_with_resource();
+
+        UintAuthorityId::set_all_keys(vec![PROVIDER]);
+        sp_io::offchain::local_storage_set(
+            StorageKind::PERSISTENT,
+            HEALTH_ENDPOINT_KEY,
+            b"http://localhost:8080/health",
+        );
+
+        let mut state = offchain_state.write();
+        state.expect_request(PendingRequest {
+            method: "GET".into(),
+            uri: format!("http://localhost:8080/health/{healthy}"),
+            response: Some(b"ok".to_vec()),
+            sent: true,
+            ..Default::default()
+        });
+        drop(state);
+
+        // Only every `OffchainHeartbeatInterval` blocks
+        Kumulus::offchain_worker(OFFCHAIN_HEARTBEAT_INTERVAL - 1);
+        assert!(pool_state.read().transactions.is_empty());
+
+        Kumulus::offchain_worker(OFFCHAIN_HEARTBEAT_INTERVAL);
+
+        let tx = pool_state.write().transactions.pop().unwrap();
+        let tx = Extrinsic::decode(&mut &*tx).unwrap();
+        let resource_ids = BoundedVec::truncate_from(vec![healthy]);
+        assert_eq!(
+            tx.function,
+            RuntimeCall::Kumulus(Call::report_health {
+                resource_ids: resource_ids.clone()
+            })
+        );
+
+        assert_ok!(Kumulus::report_health(
+            RuntimeOrigin::signed(PROVIDER),
+            resource_ids
+        ));
+        assert_eq!(ResourceHeartbeats::<Test>::get(healthy), Some(1));
+        assert_eq!(Heartbeats::<Test>::get(PROVIDER), Some(1));
+    });
+}
+
+#[test]
+fn watchers_attest_liveness_and_are_paid_by_the_majority() {
+    build_and_execute(|| {
+        let resource_id = register_provider_with_resource();
+        assert_ok!(Kumulus::rent_resource(
+            RuntimeOrigin::signed(RENTER),
+            resource_id,
+            BillingPeriod::Weekly,
+            None
+        ));
+        for watcher in WATCHERS {
+            assert_ok!(Kumulus::join_watchers(RuntimeOrigin::signed(watcher)));
+        }
+        assert_noop!(
+            Kumulus::attest_liveness(RuntimeOrigin::signed(WATCHERS[0]), resource_id, true),
+            Error::<Test>::EpochNotStarted
+        );
+
+        // Assignments are drawn at the end of the previous block
+        Kumulus::on_finalize(1);
+        assert_eq!(Kumulus::assigned_watchers(resource_id).len(), 3);
+        assert_ok!(Balances::transfer_allow_death(
+            RuntimeOrigin::signed(RENTER),
+            Kumulus::watcher_pot(),
+            1_000
+        ));
+
+        assert_noop!(
+            Kumulus::attest_liveness(RuntimeOrigin::signed(RENTER), resource_id, true),
+            Error::<Test>::NotAssignedWatcher
+        );
+        for (watcher, alive) in WATCHERS.into_iter().zip([true, true, false]) {
+            assert_ok!(Kumulus::attest_liveness(
+                RuntimeOrigin::signed(watcher),
+                resource_id,
+                alive
+            ));
+        }
+        assert_noop!(
+            Kumulus::attest_liveness(RuntimeOrigin::signed(WATCHERS[0]), resource_id, false),
+            Error::<Test>::AlreadyAttested
+        );
+        assert_noop!(
+            Kumulus::finalize_liveness(RuntimeOrigin::signed(RENTER), 0, resource_id),
+            Error::<Test>::EpochNotOver
+        );
+
+        System::set_block_number(WATCHER_EPOCH);
+        assert_ok!(Kumulus::finalize_liveness(
+            RuntimeOrigin::signed(RENTER),
+            0,
+            resource_id
+        ));
+        assert_eq!(Liveness::<Test>::get(resource_id), Some((0, true)));
+
+        // The contradicted watcher funds part of the rewards
+        let slash = WatcherSlash::get() * WATCHER_STAKE;
+        assert_eq!(
+            Balances::reserved_balance(WATCHERS[2]),
+            WATCHER_STAKE - slash
+        );
+        assert_eq!(
+            Balances::free_balance(WATCHERS[0]),
+            INITIAL_BALANCE - WATCHER_STAKE + WATCHER_REWARD
+        );
+        assert_eq!(
+            Balances::free_balance(Kumulus::watcher_pot()),
+            1_000 + slash - 2 * WATCHER_REWARD
+        );
+        assert_noop!(
+            Kumulus::finalize_liveness(RuntimeOrigin::signed(RENTER), 0, resource_id),
+            Error::<Test>::NoAttestations
+        );
+
+        // The stake stays bonded for a full epoch after leaving
+        assert_ok!(Kumulus::leave_watchers(RuntimeOrigin::signed(WATCHERS[2])));
+        assert_noop!(
+            Kumulus::withdraw_watcher_stake(RuntimeOrigin::signed(WATCHERS[2])),
+            Error::<Test>::WatcherStillBonded
+        );
+        System::set_block_number(3 * WATCHER_EPOCH);
+        assert_ok!(Kumulus::withdraw_watcher_stake(RuntimeOrigin::signed(
+            WATCHERS[2]
+        )));
+        assert_eq!(Balances::reserved_balance(WATCHERS[2]), 0);
+        assert_eq!(Balances::free_balance(WATCHERS[2]), INITIAL_BALANCE - slash);
+    });
+}
+
+#[test]
+fn watcher_epochs_are_settled_without_the_watchers() {
+    build_and_execute(|| {
+        let resource_id = register_provider_with_resource();
+        assert_ok!(Kumulus::rent_resource(
+            RuntimeOrigin::signed(RENTER),
+            resource_id,
+            BillingPeriod::Weekly,
+            None
+        ));
+        for watcher in WATCHERS {
+            assert_ok!(Kumulus::join_watchers(RuntimeOrigin::signed(watcher)));
+        }
+
+        // Randomness known in advance postpones the assignments
+        StaleRandomness::set(true);
+        Kumulus::on_finalize(1);
+        assert!(Kumulus::assigned_watchers(resource_id).is_empty());
+        StaleRandomness::set(false);
+        Kumulus::on_finalize(1);
+        assert_eq!(Kumulus::assigned_watchers(resource_id).len(), 3);
+
+        for (watcher, alive) in WATCHERS.into_iter().zip([true, true, false]) {
+            assert_ok!(Kumulus::attest_liveness(
+                RuntimeOrigin::signed(watcher),
+                resource_id,
+                alive
+            ));
+        }
+
+        // The pot is funded by a share of the payments
+        WatcherFee::set(Perbill::from_percent(1));
+        let provider_balance = Balances::free_balance(PROVIDER);
+        System::set_block_number(5);
+        assert_ok!(Kumulus::cancel_rental(
+            RuntimeOrigin::signed(RENTER),
+            resource_id
+        ));
+        let paid = Balances::free_balance(PROVIDER) - provider_balance;
+        let fee = Balances::free_balance(Kumulus::watcher_pot());
+        assert!(fee > 2 * WATCHER_REWARD);
+        assert_eq!(fee, Perbill::from_percent(1) * (paid + fee));
+
+        // Unfinalized attestations keep the stake bonded
+        assert_ok!(Kumulus::leave_watchers(RuntimeOrigin::signed(WATCHERS[0])));
+        System::set_block_number(2 * WATCHER_EPOCH);
+        assert_noop!(
+            Kumulus::withdraw_watcher_stake(RuntimeOrigin::signed(WATCHERS[0])),
+            Error::<Test>::WatcherStillBonded
+        );
+
+        // A stake that no longer covers a slash is removed from the set
+        let stake = WatcherSlash::get() * WATCHER_STAKE + 20;
+        Watchers::<Test>::mutate(WATCHERS[2], |watcher| {
+            watcher.as_mut().unwrap().stake = stake
+        });
+
+        // Nobody finalized the epoch, it is finalized on idle
+        Kumulus::on_idle(2 * WATCHER_EPOCH, Weight::MAX);
+        assert_eq!(LivenessTallies::<Test>::iter().count(), 0);
+        assert_eq!(OldestLivenessEpoch::<Test>::get(), 1);
+        assert_eq!(Liveness::<Test>::get(resource_id), Some((0, true)));
+        assert_eq!(
+            Balances::free_balance(Kumulus::watcher_pot()),
+            fee + WatcherSlash::get() * stake - 2 * WATCHER_REWARD
+        );
+        assert_eq!(WatcherSet::<Test>::get().into_inner(), vec![WATCHERS[1]]);
+        assert_eq!(Watchers::<Test>::get(WATCHERS[2]).unwrap().leaving, Some(2));
+
+        assert_ok!(Kumulus::withdraw_watcher_stake(RuntimeOrigin::signed(
+            WATCHERS[0]
+        )));
+        assert_eq!(
+            Balances::free_balance(WATCHERS[0]),
+            INITIAL_BALANCE + WATCHER_REWARD
+        );
+    });
+}
+
+#[test]
+fn watchers_attest_hardware_by_quorum() {
+    build_and_execute(|| {
+        let resource_id = register_provider_with_resource();
+        for watcher in WATCHERS {
+            assert_ok!(Kumulus::join_watchers(RuntimeOrigin::signed(watcher)));
+        }
+        let measured = CustomResourceSpecs {
+            vcpu: 2,
+            memory_gb: 2,
+            storage_gb: 10,
+            gpu_specs: None,
+        };
+        let benchmark = BlakeTwo256::hash(b"benchmark");
+
+        // A single watcher can't verify a resource
+        assert_ok!(Kumulus::attest_hardware(
+            RuntimeOrigin::signed(WATCHERS[0]),
+            resource_id,
+            benchmark,
+            measured.clone()
+        ));
+        assert_eq!(
+            Kumulus::verification_status(resource_id),
+            VerificationStatus::Unverified
+        );
+        assert_ok!(Kumulus::attest_hardware(
+            RuntimeOrigin::signed(WATCHERS[1]),
+            resource_id,
+            benchmark,
+            CustomResourceSpecs {
+                vcpu: 1,
+                ..measured.clone()
+            }
+        ));
+        assert_eq!(
+            Kumulus::verification_status(resource_id),
+            VerificationStatus::Unverified
+        );
+
+        assert_ok!(Kumulus::attest_hardware(
+            RuntimeOrigin::signed(WATCHERS[2]),
+            resource_id,
+            benchmark,
+            measured
+        ));
+        assert_eq!(
+            Kumulus::verification_status(resource_id),
+            VerificationStatus::Verified
+        );
+        System::assert_last_event(
+            Event::HardwareAttested {
+                resource_id,
+                verifier: WATCHERS[2],
+                benchmark,
+                verified: true,
+            }
+            .into(),
+        );
+        assert_eq!(
+            HardwareVotes::<Test>::iter_prefix(AttestationTarget::Resource(resource_id)).count(),
+            0
+        );
+    });
+}
+
+#[test]
+fn slices_are_verified_with_their_host() {
+    build_and_execute(|| {
+        register_provider_with_resource();
+        register_host();
+        let slice = HostCapacity {
+            vcpu: 4,
+            memory_gb: 16,
+            storage_gb: 100,
+            gpu_count: 0,
+        };
+
+        assert_ok!(Kumulus::set_verification_required(
+            RuntimeOrigin::root(),
+            true
+        ));
+        assert_noop!(
+            Kumulus::rent_slice(
+                RuntimeOrigin::signed(RENTER),
+                0,
+                slice.clone(),
+                BillingPeriod::Weekly
+            ),
+            Error::<Test>::ResourceUnverified
+        );
+
+        assert_ok!(Kumulus::add_auditor(RuntimeOrigin::root(), WATCHERS[0]));
+        assert_ok!(Kumulus::attest_host(
+            RuntimeOrigin::signed(WATCHERS[0]),
+            0,
+            BlakeTwo256::hash(b"benchmark"),
+            CustomResourceSpecs {
+                vcpu: 64,
+                memory_gb: 256,
+                storage_gb: 2_000,
+                gpu_specs: None,
+            }
+        ));
+        assert_ok!(Kumulus::rent_slice(
+            RuntimeOrigin::signed(RENTER),
+            0,
+            slice.clone(),
+            BillingPeriod::Weekly
+        ));
+
+        // The benchmark no longer covers a host grown since
+        assert_ok!(Kumulus::update_host(
+            RuntimeOrigin::signed(PROVIDER),
+            0,
+            99,
+            HostCapacity {
+                vcpu: 128,
+                memory_gb: 256,
+                storage_gb: 2_000,
+                gpu_count: 0,
+            },
+            slice_prices()
+        ));
+        assert!(HostAttestations::<Test>::get(0).is_none());
+        assert_noop!(
+            Kumulus::rent_slice(
+                RuntimeOrigin::signed(BOOTSTRAPPER),
+                0,
+                slice,
+                BillingPeriod::Weekly
+            ),
+            Error::<Test>::ResourceUnverified
+        );
+    });
+}
+
+#[test]
+fn only_verified_resources_are_rentable_when_required() {
+    build_and_execute(|| {
+        let resource_id = register_provider_with_resource();
+        let auditor = WATCHERS[0];
+        let measured = |vcpu| CustomResourceSpecs {
+            vcpu,
+            memory_gb: 2,
+            storage_gb: 10,
+            gpu_specs: None,
+        };
+        let benchmark = BlakeTwo256::hash(b"benchmark");
+
+        assert_ok!(Kumulus::set_verification_required(
+            RuntimeOrigin::root(),
+            true
+        ));
+        assert_noop!(
+            Kumulus::rent_resource(
+                RuntimeOrigin::signed(RENTER),
+                resource_id,
+                BillingPeriod::Weekly,
+                None
+            ),
+            Error::<Test>::ResourceUnverified
+        );
+
+        assert_noop!(
+            Kumulus::attest_hardware(
+                RuntimeOrigin::signed(auditor),
+                resource_id,
+                benchmark,
+                measured(2)
+            ),
+            Error::<Test>::NotVerifier
+        );
+        assert_ok!(Kumulus::add_auditor(RuntimeOrigin::root(), auditor));
+
+        // Fewer cores than the claimed category, final for the resource
+        let failing = NextResourceId::<Test>::get();
+        assert_ok!(Kumulus::register_resource(
+            RuntimeOrigin::signed(PROVIDER),
+            resource(PROVIDER)
+        ));
+        assert_ok!(Kumulus::attest_hardware(
+            RuntimeOrigin::signed(auditor),
+            failing,
+            benchmark,
+            measured(1)
+        ));
+        assert_eq!(
+            Kumulus::verification_status(failing),
+            VerificationStatus::Failed
+        );
+        assert_noop!(
+            Kumulus::attest_hardware(
+                RuntimeOrigin::signed(auditor),
+                failing,
+                benchmark,
+                measured(2)
+            ),
+            Error::<Test>::VerificationFailed
+        );
+        assert!(Kumulus::available_resources(true).is_empty());
+
+        assert_ok!(Kumulus::attest_hardware(
+            RuntimeOrigin::signed(auditor),
+            resource_id,
+            benchmark,
+            measured(2)
+        ));
+        assert_eq!(
+            Kumulus::verification_status(resource_id),
+            VerificationStatus::Verified
+        );
+        assert_eq!(
+            Kumulus::available_resources(true),
+            vec![(
+                resource_id,
+                resource(PROVIDER),
+                VerificationStatus::Verified
+            )]
+        );
+
+        assert_ok!(Kumulus::rent_resource(
+            RuntimeOrigin::signed(RENTER),
+            resource_id,
+            BillingPeriod::Weekly,
+            None
+        ));
+    });
+}
+
+#[test]
+fn available_resources_are_indexed_by_country() {
+    build_and_execute(|| {
+        let in_country = |code: &[u8]| Resource {
+            location: Region {
+                country_code: CountryCode::truncate_from(code.to_vec()),
+                ..region()
+            },
+            ..resource(PROVIDER)
+        };
+        let paris = register_provider_with_resource();
+
+        assert_noop!(
+            Kumulus::register_resource(RuntimeOrigin::signed(PROVIDER), in_country(b"XX")),
+            Error::<Test>::InvalidCountryCode
+        );
+        let new_york = NextResourceId::<Test>::get();
+        assert_ok!(Kumulus::register_resource(
+            RuntimeOrigin::signed(PROVIDER),
+            in_country(b"us")
+        ));
+        assert_eq!(
+            Resources::<Test>::get(new_york)
+                .unwrap()
+                .location
+                .country_code,
+            CountryCode::truncate_from(b"US".to_vec())
+        );
+
+        let ids = |filter| {
+            Kumulus::resources_in_region(filter, false)
+                .into_iter()
+                .map(|(id, ..)| id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ids(RegionFilter::EuropeanUnion), vec![paris]);
+        assert_eq!(ids(RegionFilter::Country(*b"US")), vec![new_york]);
+
+        // Rented resources leave the index until released
+        assert_ok!(Kumulus::rent_resource(
+            RuntimeOrigin::signed(RENTER),
+            paris,
+            BillingPeriod::Weekly,
+            None
+        ));
+        assert!(ids(RegionFilter::Country(*b"FR")).is_empty());
+
+        assert_ok!(Kumulus::cancel_rental(RuntimeOrigin::signed(RENTER), paris));
+        assert_eq!(ids(RegionFilter::Country(*b"FR")), vec![paris]);
+    });
+}
+
+#[test]
+fn provider_info_is_validated_and_linked_to_identity() {
+    build_and_execute(|| {
+        let website = |url: &[u8]| Some(Website::truncate_from(url.to_vec()));
+
+        assert_noop!(
+            Kumulus::register_provider(
+                RuntimeOrigin::signed(PROVIDER),
+                ProviderName::truncate_from(b"  ".to_vec()),
+                None
+            ),
+            Error::<Test>::InvalidProviderName
+        );
+        assert_noop!(
+            Kumulus::register_provider(
+                RuntimeOrigin::signed(PROVIDER),
+                ProviderName::truncate_from(b"Kollectyve".to_vec()),
+                website(b"ftp://kollectyve.network")
+            ),
+            Error::<Test>::InvalidWebsite
+        );
+        register_provider_with_resource();
+
+        assert_noop!(
+            Kumulus::update_provider_info(
+                RuntimeOrigin::signed(PROVIDER),
+                ProviderName::truncate_from(vec![0xff, 0xfe]),
+                None
+            ),
+            Error::<Test>::InvalidProviderName
+        );
+        assert_ok!(Kumulus::update_provider_info(
+            RuntimeOrigin::signed(PROVIDER),
+            ProviderName::truncate_from(b"Kollectyve Labs".to_vec()),
+            website(b"https://kollectyve.network/providers")
+        ));
+        System::assert_last_event(
+            Event::ProviderInfoUpdated {
+                who: PROVIDER,
+                name: ProviderName::truncate_from(b"Kollectyve Labs".to_vec()),
+                website: website(b"https://kollectyve.network/providers"),
+            }
+            .into(),
+        );
+
+        assert_noop!(
+            Kumulus::link_identity(RuntimeOrigin::signed(PROVIDER)),
+            Error::<Test>::IdentityNotVerified
+        );
+        JudgedIdentities::set(vec![PROVIDER]);
+        assert_ok!(Kumulus::link_identity(RuntimeOrigin::signed(PROVIDER)));
+        assert_eq!(VerifiedProviders::<Test>::get(PROVIDER), Some(1));
+        assert!(Kumulus::is_verified_provider(&PROVIDER));
+
+        // Anyone can revoke the status once the judgement is gone
+        assert_noop!(
+            Kumulus::revoke_provider_verification(RuntimeOrigin::signed(RENTER), PROVIDER),
+            Error::<Test>::IdentityStillVerified
+        );
+        // The badge goes with the judgement, before any revocation
+        JudgedIdentities::set(vec![]);
+        assert!(!Kumulus::is_verified_provider(&PROVIDER));
+        assert_ok!(Kumulus::revoke_provider_verification(
+            RuntimeOrigin::signed(RENTER),
+            PROVIDER
+        ));
+        assert!(VerifiedProviders::<Test>::get(PROVIDER).is_none());
+    });
+}
+
+#[test]
+fn deregistered_providers_are_offboarded_after_unbonding() {
+    build_and_execute(|| {
+        let idle = register_provider_with_resource();
+        let rented = NextResourceId::<Test>::get();
+        assert_ok!(Kumulus::register_resource(
+            RuntimeOrigin::signed(PROVIDER),
+            resource(PROVIDER)
+        ));
+        assert_ok!(Kumulus::rent_resource(
+            RuntimeOrigin::signed(RENTER),
+            rented,
+            BillingPeriod::Weekly,
+            None
+        ));
+
+        assert_noop!(
+            Kumulus::update_provider_status(
+                RuntimeOrigin::signed(PROVIDER),
+                ProviderStatus::Terminated
+            ),
+            Error::<Test>::StatusReservedForGovernance
+        );
+
+        // The running rental ends and is settled right away
+        let renter_balance = Balances::free_balance(RENTER);
+        System::set_block_number(11);
+        assert_ok!(Kumulus::deregister_provider(RuntimeOrigin::signed(
+            PROVIDER
+        )));
+        System::assert_last_event(
+            Event::ProviderDeregistering {
+                who: PROVIDER,
+                unbonded_at: 11 + PROVIDER_UNBONDING_PERIOD,
+                refunded_rentals: 1,
+            }
+            .into(),
+        );
+        let used = BASE_PRICE as u64 / 10;
+        assert_eq!(
+            Balances::free_balance(RENTER),
+            renter_balance + 2 * BASE_PRICE as u64 - used
+        );
+        assert!(Rentals::<Test>::get(rented).is_none());
+        assert_noop!(
+            Kumulus::deregister_provider(RuntimeOrigin::signed(PROVIDER)),
+            Error::<Test>::ProviderDeregistering
+        );
+        // Delisted while unbonding, though still available
+        assert!(AvailableByCountry::<Test>::iter_keys().all(|(_, id)| id != idle));
+        assert_ok!(Kumulus::do_try_state());
+
+        // No new rentals nor resources while leaving
+        assert_noop!(
+            Kumulus::rent_resource(
+                RuntimeOrigin::signed(RENTER),
+                idle,
+                BillingPeriod::Weekly,
+                None
+            ),
+            Error::<Test>::ProviderNotAcceptingRentals
+        );
+        assert_noop!(
+            Kumulus::register_resource(RuntimeOrigin::signed(PROVIDER), resource(PROVIDER)),
+            Error::<Test>::ProviderNotAcceptingRentals
+        );
+        assert!(Kumulus::resources_in_region(RegionFilter::EuropeanUnion, false).is_empty());
+
+        assert_noop!(
+            Kumulus::finalize_deregistration(RuntimeOrigin::signed(PROVIDER)),
+            Error::<Test>::ProviderStillUnbonding
+        );
+
+        // The ended rental is disputed, the bond is kept for the arbiter
+        assert_ok!(Kumulus::open_dispute(
+            RuntimeOrigin::signed(RENTER),
+            rented,
+            H256::zero()
+        ));
+        System::set_block_number(11 + PROVIDER_UNBONDING_PERIOD);
+        assert_noop!(
+            Kumulus::finalize_deregistration(RuntimeOrigin::signed(PROVIDER)),
+            Error::<Test>::EscrowFrozen
+        );
+        assert_ok!(Kumulus::resolve_dispute(
+            RuntimeOrigin::root(),
+            0,
+            Perbill::zero(),
+            10
+        ));
+
+        assert_ok!(Kumulus::finalize_deregistration(RuntimeOrigin::signed(
+            PROVIDER
+        )));
+        System::assert_last_event(Event::ProviderDeregistered { who: PROVIDER }.into());
+
+        assert!(Providers::<Test>::get(PROVIDER).is_none());
+        assert!(Resources::<Test>::iter().next().is_none());
+        assert!(EndedRentals::<Test>::iter().next().is_none());
+        assert_eq!(Balances::reserved_balance(PROVIDER), 0);
+    });
+}
+
+#[test]
+fn sanctioned_providers_refund_their_renters() {
+    build_and_execute(|| {
+        let idle = register_provider_with_resource();
+        let rented = NextResourceId::<Test>::get();
+        assert_ok!(Kumulus::register_resource(
+            RuntimeOrigin::signed(PROVIDER),
+            resource(PROVIDER)
+        ));
+        assert_ok!(Kumulus::rent_resource(
+            RuntimeOrigin::signed(RENTER),
+            rented,
+            BillingPeriod::Weekly,
+            None
+        ));
+        let renter_balance = Balances::free_balance(RENTER);
+
+        assert_noop!(
+            Kumulus::suspend_provider(
+                RuntimeOrigin::signed(RENTER),
+                PROVIDER,
+                SanctionReason::Downtime,
+                None
+            ),
+            DispatchError::BadOrigin
+        );
+        assert_noop!(
+            Kumulus::suspend_provider(
+                RuntimeOrigin::root(),
+                PROVIDER,
+                SanctionReason::Downtime,
+                Some(1)
+            ),
+            Error::<Test>::InvalidSuspensionExpiry
+        );
+
+        assert_ok!(Kumulus::suspend_provider(
+            RuntimeOrigin::root(),
+            PROVIDER,
+            SanctionReason::Downtime,
+            Some(11)
+        ));
+        System::assert_has_event(
+            Event::RenterRefunded {
+                resource_id: rented,
+                renter: RENTER,
+                deposit: 2 * BASE_PRICE as u64,
+                compensation: PROVIDER_BOND,
+            }
+            .into(),
+        );
+
+        // Whole deposit back, plus what the bond could cover of one period
+        assert_eq!(
+            Balances::free_balance(RENTER),
+            renter_balance + 2 * BASE_PRICE as u64 + PROVIDER_BOND
+        );
+        assert_eq!(Balances::reserved_balance(PROVIDER), 0);
+        assert!(Rentals::<Test>::get(rented).is_none());
+        assert!(Kumulus::resources_in_region(RegionFilter::EuropeanUnion, false).is_empty());
+        assert_ok!(Kumulus::do_try_state());
+        assert_noop!(
+            Kumulus::rent_resource(
+                RuntimeOrigin::signed(RENTER),
+                idle,
+                BillingPeriod::Weekly,
+                None
+            ),
+            Error::<Test>::ProviderNotAcceptingRentals
+        );
+        assert_noop!(
+            Kumulus::update_provider_status(
+                RuntimeOrigin::signed(PROVIDER),
+                ProviderStatus::Active
+            ),
+            Error::<Test>::StatusReservedForGovernance
+        );
+
+        // Lifted at expiry
+        System::set_block_number(11);
+        Kumulus::on_initialize(11);
+        System::assert_last_event(Event::SuspensionLifted { who: PROVIDER }.into());
+        assert_eq!(
+            Providers::<Test>::get(PROVIDER).unwrap().status,
+            ProviderStatus::Inactive
+        );
+        assert_eq!(
+            Kumulus::resources_in_region(RegionFilter::EuropeanUnion, false).len(),
+            2
+        );
+        assert_ok!(Kumulus::do_try_state());
+
+        assert_ok!(Kumulus::terminate_provider(
+            RuntimeOrigin::root(),
+            PROVIDER,
+            SanctionReason::Fraud
+        ));
+        assert_noop!(
+            Kumulus::suspend_provider(RuntimeOrigin::root(), PROVIDER, SanctionReason::Fraud, None),
+            Error::<Test>::ProviderTerminated
+        );
+        assert_noop!(
+            Kumulus::register_provider(
+                RuntimeOrigin::signed(PROVIDER),
+                ProviderName::truncate_from(b"Kollectyve".to_vec()),
+                None
+            ),
+            Error::<Test>::ProviderAlreadyRegistered
+        );
+        assert!(Resources::<Test>::iter().next().is_none());
+    });
+}
+
+#[test]
+fn sanctions_leave_disputed_rentals_to_the_arbiter() {
+    build_and_execute(|| {
+        let resource_id = register_provider_with_resource();
+        assert_ok!(Kumulus::rent_resource(
+            RuntimeOrigin::signed(RENTER),
+            resource_id,
+            BillingPeriod::Weekly,
+            None
+        ));
+        assert_ok!(Kumulus::open_dispute(
+            RuntimeOrigin::signed(RENTER),
+            resource_id,
+            H256::repeat_byte(1)
+        ));
+
+        // The escrow stays frozen, the rental isn't refunded
+        assert_ok!(Kumulus::suspend_provider(
+            RuntimeOrigin::root(),
+            PROVIDER,
+            SanctionReason::Downtime,
+            None
+        ));
+        System::assert_last_event(
+            Event::ProviderSuspended {
+                who: PROVIDER,
+                reason: SanctionReason::Downtime,
+                expires_at: None,
+                refunded_rentals: 0,
+            }
+            .into(),
+        );
+        assert!(Rentals::<Test>::get(resource_id).is_some());
+        assert_eq!(Balances::reserved_balance(PROVIDER), PROVIDER_BOND);
+        assert_ok!(Kumulus::do_try_state());
+
+        assert_noop!(
+            Kumulus::terminate_provider(RuntimeOrigin::root(), PROVIDER, SanctionReason::Fraud),
+            Error::<Test>::EscrowFrozen
+        );
+
+        assert_ok!(Kumulus::resolve_dispute(
+            RuntimeOrigin::root(),
+            0,
+            Perbill::one(),
+            PROVIDER_BOND
+        ));
+        assert!(Rentals::<Test>::get(resource_id).is_none());
+
+        // Lifting the suspension lists the resource again
+        assert_ok!(Kumulus::lift_suspension(RuntimeOrigin::root(), PROVIDER));
+        assert_eq!(
+            Kumulus::resources_in_region(RegionFilter::EuropeanUnion, false).len(),
+            1
+        );
+        assert_ok!(Kumulus::do_try_state());
+
+        assert_ok!(Kumulus::terminate_provider(
+            RuntimeOrigin::root(),
+            PROVIDER,
+            SanctionReason::Fraud
+        ));
+    });
+}
+
+#[test]
+fn organisation_rentals_are_managed_by_role() {
+    const DEPLOYER: u64 = 7;
+    const BILLING: u64 = 8;
+
+    build_and_execute(|| {
+        let resource_id = register_provider_with_resource();
+
+        assert_ok!(Kumulus::create_organisation(RuntimeOrigin::signed(RENTER)));
+        assert_eq!(Balances::reserved_balance(RENTER), ORGANISATION_DEPOSIT);
+        let treasury = Kumulus::organisation_account(0);
+        assert_ok!(Balances::transfer_keep_alive(
+            RuntimeOrigin::signed(RENTER),
+            treasury,
+            10 * BASE_PRICE as u64
+        ));
+        assert_ok!(Kumulus::set_member_role(
+            RuntimeOrigin::signed(RENTER),
+            0,
+            DEPLOYER,
+            OrgRole::Deployer
+        ));
+        assert_ok!(Kumulus::set_member_role(
+            RuntimeOrigin::signed(RENTER),
+            0,
+            BILLING,
+            OrgRole::Billing
+        ));
+        assert_noop!(
+            Kumulus::set_member_role(RuntimeOrigin::signed(BILLING), 0, BILLING, OrgRole::Admin),
+            Error::<Test>::MissingOrganisationRole
+        );
+
+        // Only billing spends from the treasury
+        assert_noop!(
+            Kumulus::rent_for_organisation(
+                RuntimeOrigin::signed(DEPLOYER),
+                0,
+                resource_id,
+                BillingPeriod::Weekly,
+                None
+            ),
+            Error::<Test>::MissingOrganisationRole
+        );
+        assert_ok!(Kumulus::rent_for_organisation(
+            RuntimeOrigin::signed(BILLING),
+            0,
+            resource_id,
+            BillingPeriod::Weekly,
+            None
+        ));
+        assert_eq!(Rentals::<Test>::get(resource_id).unwrap().renter, treasury);
+        assert_eq!(Balances::free_balance(treasury), 8 * BASE_PRICE as u64);
+
+        assert_noop!(
+            Kumulus::update_deployment(
+                RuntimeOrigin::signed(BILLING),
+                resource_id,
+                DeploymentManifest::OffChain(H256::repeat_byte(1))
+            ),
+            Error::<Test>::MissingOrganisationRole
+        );
+        assert_ok!(Kumulus::update_deployment(
+            RuntimeOrigin::signed(DEPLOYER),
+            resource_id,
+            DeploymentManifest::OffChain(H256::repeat_byte(1))
+        ));
+
+        assert_noop!(
+            Kumulus::top_up_rental(RuntimeOrigin::signed(DEPLOYER), resource_id, 100),
+            Error::<Test>::MissingOrganisationRole
+        );
+        assert_ok!(Kumulus::top_up_rental(
+            RuntimeOrigin::signed(BILLING),
+            resource_id,
+            100
+        ));
+        assert_eq!(
+            Balances::free_balance(treasury),
+            8 * BASE_PRICE as u64 - 100
+        );
+
+        assert_noop!(
+            Kumulus::cancel_rental(RuntimeOrigin::signed(PROVIDER), resource_id),
+            Error::<Test>::NotOrganisationMember
+        );
+        assert_noop!(
+            Kumulus::remove_member(RuntimeOrigin::signed(RENTER), 0, RENTER),
+            Error::<Test>::LastOrganisationAdmin
+        );
+
+        // The unused deposit goes back to the treasury
+        assert_ok!(Kumulus::cancel_rental(
+            RuntimeOrigin::signed(BILLING),
+            resource_id
+        ));
+        assert_eq!(Balances::free_balance(treasury), 10 * BASE_PRICE as u64);
+
+        // Only admins withdraw from the treasury
+        assert_noop!(
+            Kumulus::withdraw_organisation_funds(RuntimeOrigin::signed(BILLING), 0, BILLING, 100),
+            Error::<Test>::MissingOrganisationRole
+        );
+        assert_ok!(Kumulus::withdraw_organisation_funds(
+            RuntimeOrigin::signed(RENTER),
+            0,
+            BILLING,
+            100
+        ));
+        assert_eq!(
+            Balances::free_balance(treasury),
+            10 * BASE_PRICE as u64 - 100
+        );
+
+        NextOrganisationId::<Test>::put(OrganisationId::MAX);
+        assert_noop!(
+            Kumulus::create_organisation(RuntimeOrigin::signed(BILLING)),
+            Error::<Test>::OrganisationIdOverflow
+        );
+    });
+}
+
+#[test]
+fn budgets_cap_spending_per_period() {
+    build_and_execute(|| {
+        let resource_id = register_provider_with_resource();
+        let budget = Budget {
+            cap: 3 * BASE_PRICE as u64,
+            period: 50,
+            alert_threshold: Perbill::from_percent(50),
+        };
+
+        assert_noop!(
+            Kumulus::set_budget(
+                RuntimeOrigin::signed(RENTER),
+                Some(Budget {
+                    period: 0,
+                    ..budget.clone()
+                })
+            ),
+            Error::<Test>::InvalidBudget
+        );
+        assert_ok!(Kumulus::set_budget(
+            RuntimeOrigin::signed(RENTER),
+            Some(budget)
+        ));
+
+        // Two weeks escrowed, above half of the cap
+        assert_ok!(Kumulus::rent_resource(
+            RuntimeOrigin::signed(RENTER),
+            resource_id,
+            BillingPeriod::Weekly,
+            None
+        ));
+        System::assert_has_event(
+            Event::BudgetThresholdReached {
+                who: RENTER,
+                spent: 2 * BASE_PRICE as u64,
+                cap: 3 * BASE_PRICE as u64,
+            }
+            .into(),
+        );
+
+        assert_ok!(Kumulus::top_up_rental(
+            RuntimeOrigin::signed(RENTER),
+            resource_id,
+            BASE_PRICE as u64
+        ));
+        assert_noop!(
+            Kumulus::top_up_rental(RuntimeOrigin::signed(RENTER), resource_id, 1),
+            Error::<Test>::BudgetExceeded
+        );
+
+        // The next period starts from scratch
+        System::set_block_number(51);
+        assert_ok!(Kumulus::top_up_rental(
+            RuntimeOrigin::signed(RENTER),
+            resource_id,
+            1
+        ));
+        assert_eq!(BudgetUsages::<Test>::get(RENTER).unwrap().spent, 1);
+
+        // A controller keeps the renter from lifting its own cap
+        assert_ok!(Kumulus::set_budget_controller(
+            RuntimeOrigin::signed(RENTER),
+            RENTER,
+            Some(BOOTSTRAPPER)
+        ));
+        assert_noop!(
+            Kumulus::set_budget(RuntimeOrigin::signed(RENTER), None),
+            Error::<Test>::NotBudgetController
+        );
+        assert_noop!(
+            Kumulus::set_budget_controller(RuntimeOrigin::signed(RENTER), RENTER, None),
+            Error::<Test>::NotBudgetController
+        );
+        assert_noop!(
+            Kumulus::set_controlled_budget(RuntimeOrigin::signed(PROVIDER), RENTER, None),
+            Error::<Test>::NotBudgetController
+        );
+        assert_ok!(Kumulus::set_controlled_budget(
+            RuntimeOrigin::signed(BOOTSTRAPPER),
+            RENTER,
+            None
+        ));
+        assert_eq!(Budgets::<Test>::get(RENTER), None);
+        assert_ok!(Kumulus::set_budget_controller(
+            RuntimeOrigin::signed(BOOTSTRAPPER),
+            RENTER,
+            None
+        ));
+        assert_eq!(BudgetControllers::<Test>::get(RENTER), None);
+
+        // Organisation budgets are set by admins only
+        assert_ok!(Kumulus::create_organisation(RuntimeOrigin::signed(RENTER)));
+        assert_ok!(Kumulus::set_member_role(
+            RuntimeOrigin::signed(RENTER),
+            0,
+            PROVIDER,
+            OrgRole::Billing
+        ));
+        assert_noop!(
+            Kumulus::set_organisation_budget(RuntimeOrigin::signed(PROVIDER), 0, None),
+            Error::<Test>::MissingOrganisationRole
+        );
+        assert_ok!(Kumulus::set_organisation_budget(
+            RuntimeOrigin::signed(RENTER),
+            0,
+            None
+        ));
+    });
+}
+
+#[test]
+fn bootstrappers_of_the_old_pallet_are_migrated_in_steps() {
+    use frame_support::{
+        migrations::{SteppedMigration, SteppedMigrationError},
+        weights::WeightMeter,
+    };
+    use migrations::unify_bootstrap::{MigrateToPendingBootstrappers, OldBootstrappers};
+
+    build_and_execute(|| {
+        OldBootstrappers::<Test>::insert(RENTER, true);
+        OldBootstrappers::<Test>::insert(PROVIDER, true);
+        OldBootstrappers::<Test>::insert(WATCHERS[0], false);
+
+        let step = <Test as frame_system::Config>::DbWeight::get().reads_writes(3, 3);
+        assert!(matches!(
+            MigrateToPendingBootstrappers::<Test>::step(
+                None,
+                &mut WeightMeter::with_limit(step / 2)
+            ),
+            Err(SteppedMigrationError::InsufficientWeight { .. })
+        ));
+
+        // One entry per step, then the old prefix is removed
+        let mut cursor = None;
+        let mut steps = 0;
+        loop {
+            cursor = MigrateToPendingBootstrappers::<Test>::step(
+                cursor,
+                &mut WeightMeter::with_limit(step),
+            )
+            .unwrap();
+            steps += 1;
+            if cursor.is_none() {
+                break;
+            }
+        }
+        assert_eq!(steps, 4);
+        assert_eq!(OldBootstrappers::<Test>::iter().count(), 0);
+        assert!(PendingBootstrappers::<Test>::contains_key(RENTER));
+        assert!(PendingBootstrappers::<Test>::contains_key(PROVIDER));
+        assert!(!PendingBootstrappers::<Test>::contains_key(WATCHERS[0]));
+
+        // Nothing left to move on a later run
+        assert_eq!(
+            MigrateToPendingBootstrappers::<Test>::step(None, &mut WeightMeter::with_limit(step))
+                .unwrap(),
+            None
+        );
+        assert_eq!(PendingBootstrappers::<Test>::count(), 2);
+    });
+}
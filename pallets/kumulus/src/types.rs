@@ -1,22 +1,109 @@
+use codec::DecodeWithMemTracking;
 use frame_support::pallet_prelude::*;
+use frame_support::sp_runtime::{traits::AtLeast32BitUnsigned, Perbill, SaturatedConversion};
 use frame_support::BoundedVec;
 use scale_info::TypeInfo;
 
 pub type CountryCode = BoundedVec<u8, ConstU32<5>>;
+pub type IsoCountry = [u8; 2]; // Normalized ISO 3166-1 alpha-2 code
 pub type Website = BoundedVec<u8, ConstU32<99>>;
 pub type ProviderName = BoundedVec<u8, ConstU32<99>>;
 pub type ResourceId = u32;
+pub type UsageReportId = u64;
+pub type DisputeId = u32;
+pub type OrderId = u32;
+pub type HostId = u32;
+pub type EpochIndex = u32;
+pub type OrganisationId = u32;
+pub type ImageDigest = BoundedVec<u8, ConstU32<128>>; // e.g. sha256:<hex>
+pub type DeploymentPorts = BoundedVec<u16, ConstU32<16>>;
+pub type EncryptedBundle = BoundedVec<u8, ConstU32<1024>>;
 pub type StorageExtension = u64;
 pub type VCPUExtension = u8;
 pub const BASE_PRICE: u128 = 10_000_000_000; // for 1 week
+pub const HOURS_PER_WEEK: u32 = 168;
+pub const DAYS_PER_WEEK: u32 = 7;
 pub const BASE_MEMORY_GB: u32 = 2;
 pub const BASE_STORAGE_GB: u64 = 10;
 pub const BASE_VCPU: u8 = 2;
 
+/// Officially assigned ISO 3166-1 alpha-2 codes, sorted
+pub const ISO_3166_ALPHA2: [IsoCountry; 249] = [
+    *b"AD", *b"AE", *b"AF", *b"AG", *b"AI", *b"AL", *b"AM", *b"AO", *b"AQ", *b"AR", *b"AS", *b"AT",
+    *b"AU", *b"AW", *b"AX", *b"AZ",
+    *b"BA", *b"BB", *b"BD", *b"BE", *b"BF", *b"BG", *b"BH", *b"BI", *b"BJ", *b"BL", *b"BM", *b"BN",
+    *b"BO", *b"BQ", *b"BR", *b"BS", *b"BT", *b"BV", *b"BW", *b"BY", *b"BZ",
+    *b"CA", *b"CC", *b"CD", *b"CF", *b"CG", *b"CH", *b"CI", *b"CK", *b"CL", *b"CM", *b"CN", *b"CO",
+    *b"CR", *b"CU", *b"CV", *b"CW", *b"CX", *b"CY", *b"CZ",
+    *b"DE", *b"DJ", *b"DK", *b"DM", *b"DO", *b"DZ",
+    *b"EC", *b"EE", *b"EG", *b"EH", *b"ER", *b"ES", *b"ET",
+    *b"FI", *b"FJ", *b"FK", *b"FM", *b"FO", *b"FR",
+    *b"GA", *b"GB", *b"GD", *b"GE", *b"GF", *b"GG", *b"GH", *b"GI", *b"GL", *b"GM", *b"GN", *b"GP",
+    *b"GQ", *b"GR", *b"GS", *b"GT", *b"GU", *b"GW", *b"GY",
+    *b"HK", *b"HM", *b"HN", *b"HR", *b"HT", *b"HU",
+    *b"ID", *b"IE", *b"IL", *b"IM", *b"IN", *b"IO", *b"IQ", *b"IR", *b"IS", *b"IT",
+    *b"JE", *b"JM", *b"JO", *b"JP",
+    *b"KE", *b"KG", *b"KH", *b"KI", *b"KM", *b"KN", *b"KP", *b"KR", *b"KW", *b"KY", *b"KZ",
+    *b"LA", *b"LB", *b"LC", *b"LI", *b"LK", *b"LR", *b"LS", *b"LT", *b"LU", *b"LV", *b"LY",
+    *b"MA", *b"MC", *b"MD", *b"ME", *b"MF", *b"MG", *b"MH", *b"MK", *b"ML", *b"MM", *b"MN", *b"MO",
+    *b"MP", *b"MQ", *b"MR", *b"MS", *b"MT", *b"MU", *b"MV", *b"MW", *b"MX", *b"MY", *b"MZ",
+    *b"NA", *b"NC", *b"NE", *b"NF", *b"NG", *b"NI", *b"NL", *b"NO", *b"NP", *b"NR", *b"NU", *b"NZ",
+    *b"OM",
+    *b"PA", *b"PE", *b"PF", *b"PG", *b"PH", *b"PK", *b"PL", *b"PM", *b"PN", *b"PR", *b"PS", *b"PT",
+    *b"PW", *b"PY",
+    *b"QA",
+    *b"RE", *b"RO", *b"RS", *b"RU", *b"RW",
+    *b"SA", *b"SB", *b"SC", *b"SD", *b"SE", *b"SG", *b"SH", *b"SI", *b"SJ", *b"SK", *b"SL", *b"SM",
+    *b"SN", *b"SO", *b"SR", *b"SS", *b"ST", *b"SV", *b"SX", *b"SY", *b"SZ",
+    *b"TC", *b"TD", *b"TF", *b"TG", *b"TH", *b"TJ", *b"TK", *b"TL", *b"TM", *b"TN", *b"TO", *b"TR",
+    *b"TT", *b"TV", *b"TW", *b"TZ",
+    *b"UA", *b"UG", *b"UM", *b"US", *b"UY", *b"UZ",
+    *b"VA", *b"VC", *b"VE", *b"VG", *b"VI", *b"VN", *b"VU",
+    *b"WF", *b"WS",
+    *b"YE", *b"YT",
+    *b"ZA", *b"ZM", *b"ZW",
+];
+
+pub const EU_MEMBER_STATES: [IsoCountry; 27] = [
+    *b"AT", *b"BE", *b"BG", *b"CY", *b"CZ", *b"DE", *b"DK", *b"EE", *b"ES", *b"FI", *b"FR", *b"GR",
+    *b"HR", *b"HU", *b"IE", *b"IT", *b"LT", *b"LU", *b"LV", *b"MT", *b"NL", *b"PL", *b"PT", *b"RO",
+    *b"SE", *b"SI", *b"SK",
+];
+
 #[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen, DecodeWithMemTracking)]
-pub enum BillingPeriod {
+pub enum BillingPeriod<BlockNumber> {
+    // New variants are appended to keep the encoding of stored rentals
     Weekly,
-    Monthly,
+    Monthly, // 4 weeks
+    Hourly,
+    Daily,
+    Custom(BlockNumber), // Period of the given number of blocks
+    PayAsYouGo,          // Settled per block from the prepaid deposit
+}
+
+impl<BlockNumber: AtLeast32BitUnsigned + Copy> BillingPeriod<BlockNumber> {
+    /// Length of the period in blocks, a single block for pay-as-you-go
+    pub fn duration(&self, blocks_per_week: BlockNumber) -> BlockNumber {
+        match self {
+            BillingPeriod::Weekly => blocks_per_week,
+            BillingPeriod::Monthly => blocks_per_week.saturating_mul(4u32.into()),
+            BillingPeriod::Hourly => blocks_per_week / HOURS_PER_WEEK.into(),
+            BillingPeriod::Daily => blocks_per_week / DAYS_PER_WEEK.into(),
+            BillingPeriod::Custom(blocks) => *blocks,
+            BillingPeriod::PayAsYouGo => 1u32.into(),
+        }
+    }
+
+    /// Price of one period given a price per week
+    pub fn pro_rata(&self, weekly_price: u128, blocks_per_week: BlockNumber) -> u128 {
+        let period_blocks: u128 = self.duration(blocks_per_week).saturated_into();
+        let week_blocks: u128 = blocks_per_week.saturated_into();
+
+        weekly_price
+            .saturating_mul(period_blocks)
+            .checked_div(week_blocks)
+            .unwrap_or_default()
+    }
 }
 
 #[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen, DecodeWithMemTracking)]
@@ -54,13 +141,28 @@ pub enum ResourceCategory {
 }
 
 impl ResourceCategory {
-    pub fn price(&self, billing_period: &BillingPeriod) -> u128 {
+    /// Price of one billing period, pro rata of the weekly base price
+    pub fn price<BlockNumber: AtLeast32BitUnsigned + Copy>(
+        &self,
+        billing_period: &BillingPeriod<BlockNumber>,
+        blocks_per_week: BlockNumber,
+    ) -> u128 {
         // TODO: Complete the pricing model (as of now will be onchain based (storage,constant)
         // without using runtime constant
-        match billing_period {
-            BillingPeriod::Weekly => BASE_PRICE,
-            BillingPeriod::Monthly => BASE_PRICE * 4,
-        }
+        billing_period.pro_rata(BASE_PRICE, blocks_per_week)
+    }
+
+    pub fn is_gpu(&self) -> bool {
+        matches!(self, ResourceCategory::Custom(specs) if specs.gpu_specs.is_some())
+    }
+
+    /// Whether the category provides at least the requested specs
+    pub fn satisfies(&self, min_specs: &ComputeSpecs) -> bool {
+        let (vcpu, memory_gb, storage_gb) = self.specs();
+
+        vcpu >= min_specs.vcpu
+            && memory_gb >= min_specs.memory_gb
+            && storage_gb >= min_specs.storage_gb
     }
 
     pub fn specs(&self) -> (u8, u32, u64) {
@@ -93,48 +195,211 @@ impl ResourceCategory {
             ResourceCategory::Custom(specs) => (specs.vcpu, specs.memory_gb, specs.storage_gb),
         }
     }
+
+    /// Whether the measured hardware is at least what the category claims, GPUs included
+    pub fn is_backed_by(&self, measured: &CustomResourceSpecs) -> bool {
+        let (vcpu, memory_gb, storage_gb) = self.specs();
+        if measured.vcpu < vcpu
+            || measured.memory_gb < memory_gb
+            || measured.storage_gb < storage_gb
+        {
+            return false;
+        }
+
+        let ResourceCategory::Custom(CustomResourceSpecs {
+            gpu_specs: Some(claimed),
+            ..
+        }) = self
+        else {
+            return true;
+        };
+        measured.gpu_specs.as_ref().is_some_and(|gpu| {
+            gpu.gpu_model == claimed.gpu_model
+                && gpu.gpu_count >= claimed.gpu_count
+                && gpu.gpu_memory_gb >= claimed.gpu_memory_gb
+        })
+    }
 }
 
 #[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen, DecodeWithMemTracking)]
-pub struct Rental<AccountId> {
+pub struct Rental<AccountId, BlockNumber> {
     pub resource_id: ResourceId,
     pub renter: AccountId,
-    pub start_block: u32,
-    pub billing_period: BillingPeriod,
-    pub last_paid_block: u32,
+    pub start_block: BlockNumber,
+    pub billing_period: BillingPeriod<BlockNumber>,
+    pub last_paid_block: BlockNumber,
     pub is_active: bool,
 }
 
+#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen, DecodeWithMemTracking)]
+pub struct Organisation<AccountId, Balance> {
+    pub account: AccountId, // Treasury, owner of the rentals of the organisation
+    pub members: u32,
+    pub depositor: AccountId, // Creator, reserving the deposit
+    pub deposit: Balance,
+}
+
+#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen, Copy, DecodeWithMemTracking)]
+pub enum OrgRole {
+    Admin,    // Manages members and withdraws funds, and everything the other roles can do
+    Billing,  // Spends from the treasury: rents, tops up, cancels and disputes
+    Deployer, // Manages deployments and access keys of the rentals
+}
+
+impl OrgRole {
+    /// Whether a member with this role can act where `required` is needed
+    pub fn allows(&self, required: OrgRole) -> bool {
+        *self == OrgRole::Admin || *self == required
+    }
+}
+
+/// Spending cap of an account per period, for rentals and top-ups
+#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct Budget<Balance, BlockNumber> {
+    pub cap: Balance,             // Maximum spent per period
+    pub period: BlockNumber,      // Length of a period, starting with its first spending
+    pub alert_threshold: Perbill, // Share of the cap above which an alert is raised
+}
+
+#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen, DecodeWithMemTracking)]
+pub struct BudgetUsage<Balance, BlockNumber> {
+    pub period_start: BlockNumber,
+    pub spent: Balance,
+}
+
 #[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen, DecodeWithMemTracking)]
 pub struct Region {
     pub country_code: CountryCode,
     pub city: BoundedVec<u8, ConstU32<32>>,
 }
 
+/// Upper-cased ISO 3166-1 alpha-2 code, none if the code is not assigned
+pub fn iso_country(country_code: &[u8]) -> Option<IsoCountry> {
+    let code: IsoCountry = country_code.try_into().ok()?;
+    let code = code.map(|c| c.to_ascii_uppercase());
+
+    ISO_3166_ALPHA2.binary_search(&code).ok().map(|_| code)
+}
+
+impl Region {
+    pub fn iso_country(&self) -> Option<IsoCountry> {
+        iso_country(&self.country_code)
+    }
+
+    /// The region with its country code normalized, none if the code is not assigned
+    pub fn normalized(self) -> Option<Self> {
+        let code = self.iso_country()?;
+
+        Some(Region {
+            country_code: CountryCode::truncate_from(code.to_vec()),
+            ..self
+        })
+    }
+}
+
+#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen, DecodeWithMemTracking)]
+pub enum RegionFilter {
+    Country(IsoCountry),
+    EuropeanUnion,
+}
+
+impl RegionFilter {
+    pub fn countries(&self) -> &[IsoCountry] {
+        match self {
+            RegionFilter::Country(code) => core::slice::from_ref(code),
+            RegionFilter::EuropeanUnion => &EU_MEMBER_STATES,
+        }
+    }
+}
+
 #[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen, DecodeWithMemTracking)]
-pub struct ProviderInfo<AccountId> {
+pub struct ProviderInfo<AccountId, BlockNumber> {
     pub account: AccountId,
-    pub name: ProviderName,       // Provider's name/organization
-    pub website: Option<Website>, // Optional website URL
-    pub total_resources: u32,     // Total resources across all categories
-    pub reputation_score: u32,    // Score from 0-100
-    pub registration_block: u32,  // Block number when registered
-    pub last_updated: u32,        // Last update block number
-    pub status: ProviderStatus,   // Current status
+    pub name: ProviderName,              // Provider's name/organization
+    pub website: Option<Website>,        // Optional website URL
+    pub total_resources: u32,            // Total resources across all categories
+    pub reputation_score: u32,           // Score from 0-100
+    pub registration_block: BlockNumber, // Block number when registered
+    pub last_updated: BlockNumber,       // Last update block number
+    pub status: ProviderStatus,          // Current status
+}
+
+/// Non-blank UTF-8 without control characters
+pub fn is_valid_name(name: &[u8]) -> bool {
+    core::str::from_utf8(name)
+        .is_ok_and(|name| !name.trim().is_empty() && !name.chars().any(char::is_control))
+}
+
+/// Absolute http(s) URL with a host and no whitespace
+pub fn is_valid_url(url: &[u8]) -> bool {
+    let Ok(url) = core::str::from_utf8(url) else {
+        return false;
+    };
+    let Some(rest) = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+    else {
+        return false;
+    };
+    let host = rest.split(['/', '?', '#']).next().unwrap_or_default();
+
+    !host.is_empty()
+        && host.chars().all(|c| c.is_ascii_alphanumeric() || "-.:[]".contains(c))
+        && !url.chars().any(|c| c.is_whitespace() || c.is_control())
+}
+
+/// On-chain identity of the providers, judged by registrars
+pub trait ProviderIdentity<AccountId> {
+    /// Whether the account has an identity with a positive judgement
+    fn is_verified(who: &AccountId) -> bool;
+}
+
+impl<AccountId> ProviderIdentity<AccountId> for () {
+    fn is_verified(_: &AccountId) -> bool {
+        false
+    }
 }
 
 #[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen, DecodeWithMemTracking)]
 pub enum ProviderStatus {
     Active,
     Inactive,
-    Suspended,
-    Terminated,
+    Suspended,     // Set by governance only
+    Terminated,    // Set by governance only
+    Deregistering, // Leaving, no new rentals until the bond is released
+}
+
+impl ProviderStatus {
+    /// Whether the resources of the provider can take new rentals
+    pub fn accepts_rentals(&self) -> bool {
+        matches!(self, ProviderStatus::Active | ProviderStatus::Inactive)
+    }
+}
+
+/// Why governance suspended or terminated a provider
+#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen, Copy, DecodeWithMemTracking)]
+pub enum SanctionReason {
+    Downtime,
+    Fraud,
+    Abuse,
+    PolicyViolation,
+    Other(u16), // Code defined off-chain by governance
+}
+
+#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen, DecodeWithMemTracking)]
+pub struct Suspension<BlockNumber> {
+    pub reason: SanctionReason,
+    pub suspended_at: BlockNumber,
+    pub expires_at: Option<BlockNumber>, // Lifted automatically at this block, by governance otherwise
 }
 
 #[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen, DecodeWithMemTracking)]
-pub struct BootstrapperInfo<AccountId> {
+pub struct BootstrapperInfo<AccountId, BlockNumber> {
     pub bootstrapper_info: AccountId,
     pub bootstrap_type: BootstrapperType,
+    pub expires_at: BlockNumber, // Block at which the bootstrapper and its resources are removed
+    pub max_resources: u32,      // Maximum number of bootstrapped resources
+    pub resource_count: u32,     // Currently registered bootstrapped resources
 }
 
 #[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen, Copy, DecodeWithMemTracking)]
@@ -142,3 +407,336 @@ pub enum BootstrapperType {
     Kollectyve,
     Else,
 }
+
+#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen, Default, DecodeWithMemTracking)]
+pub struct UsageReport {
+    pub cpu_seconds: u64,      // CPU time consumed
+    pub egress_gb: u64,        // Outbound traffic
+    pub storage_gb_hours: u64, // Storage used over time
+}
+
+impl UsageReport {
+    /// Whether every counter stays within the given bounds
+    pub fn fits_within(&self, max: &UsageReport) -> bool {
+        self.cpu_seconds <= max.cpu_seconds
+            && self.egress_gb <= max.egress_gb
+            && self.storage_gb_hours <= max.storage_gb_hours
+    }
+}
+
+#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen, Default, DecodeWithMemTracking)]
+pub struct RateCard<Balance> {
+    pub cpu_second: Balance,      // Price of one CPU-second
+    pub egress_gb: Balance,       // Price of one GB of egress
+    pub storage_gb_hour: Balance, // Price of one GB stored for an hour
+}
+
+impl<Balance: AtLeast32BitUnsigned + Copy> RateCard<Balance> {
+    pub fn price(&self, report: &UsageReport) -> Balance {
+        self.cpu_second
+            .saturating_mul(report.cpu_seconds.saturated_into())
+            .saturating_add(self.egress_gb.saturating_mul(report.egress_gb.saturated_into()))
+            .saturating_add(
+                self.storage_gb_hour
+                    .saturating_mul(report.storage_gb_hours.saturated_into()),
+            )
+    }
+}
+
+#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen, DecodeWithMemTracking)]
+pub struct UsageRecord<BlockNumber, Balance> {
+    pub id: UsageReportId,
+    pub report: UsageReport,
+    pub amount: Balance,           // Priced with the rate card of the rental
+    pub submitted_at: BlockNumber, // The dispute window starts here
+    pub disputed: bool,
+}
+
+#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen, DecodeWithMemTracking)]
+pub struct HeldUsage<AccountId, BlockNumber, Balance> {
+    pub renter: AccountId,
+    pub provider: AccountId,
+    pub held: Balance, // Kept in escrow, the rest of the record is owed by the renter
+    pub record: UsageRecord<BlockNumber, Balance>,
+}
+
+#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen, DecodeWithMemTracking)]
+pub struct EndedRental<AccountId, BlockNumber> {
+    pub renter: AccountId,
+    pub provider: AccountId,
+    pub ended_at: BlockNumber,
+}
+
+#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen, DecodeWithMemTracking)]
+pub struct Dispute<AccountId, BlockNumber> {
+    pub resource_id: ResourceId,
+    pub renter: AccountId,
+    pub provider: AccountId,
+    pub opened_at: BlockNumber,
+    pub rental_active: bool, // Whether the rental escrow is frozen by the dispute
+}
+
+#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen, Default, DecodeWithMemTracking)]
+pub struct ComputeSpecs {
+    pub vcpu: u8,
+    pub memory_gb: u32,
+    pub storage_gb: u64,
+}
+
+#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen, DecodeWithMemTracking)]
+pub struct ComputeOrder<AccountId, Balance, BlockNumber> {
+    pub renter: AccountId,
+    pub min_specs: ComputeSpecs,
+    pub country_code: Option<CountryCode>, // Any region when not set
+    pub max_price: Balance,                // Per week
+    pub billing_period: BillingPeriod<BlockNumber>,
+    pub duration: u32,          // Billing periods prepaid once matched, at least the usual two
+    pub deposit: Balance,       // Reserved from the renter until the order is closed
+    pub expires_at: BlockNumber, // Dropped from the queue from here
+}
+
+#[derive(Clone, Copy, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen, DecodeWithMemTracking)]
+pub enum AuctionKind {
+    FirstPrice,  // The winner pays its bid
+    SecondPrice, // The winner pays the second highest bid, at least the reserve price
+}
+
+#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen, DecodeWithMemTracking)]
+pub struct Auction<Balance, BlockNumber> {
+    pub kind: AuctionKind,
+    pub reserve_price: Balance, // Per week
+    pub billing_period: BillingPeriod<BlockNumber>,
+    pub commit_end: BlockNumber, // Bids are revealed from here
+    pub reveal_end: BlockNumber, // The auction is settled here
+    pub bid_count: u32,
+    pub reveal_count: u32,
+}
+
+impl<Balance: Copy + PartialOrd, BlockNumber> Auction<Balance, BlockNumber> {
+    /// Price per week paid by a winning `bid`, `next_bid` being the highest bid below it
+    pub fn clearing_price(&self, bid: Balance, next_bid: Option<Balance>) -> Balance {
+        match (self.kind, next_bid) {
+            (AuctionKind::FirstPrice, _) => bid,
+            (AuctionKind::SecondPrice, Some(next)) if next > self.reserve_price => next,
+            (AuctionKind::SecondPrice, _) => self.reserve_price,
+        }
+    }
+}
+
+#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen, DecodeWithMemTracking)]
+pub struct SealedBid<Hash, Balance> {
+    pub commitment: Hash, // Hash of the bid and a salt
+    pub deposit: Balance, // Reserved until settlement, forfeited if the bid is never revealed
+    pub revealed: Option<(Balance, u32)>, // Bid and reveal order, earlier reveals win ties
+}
+
+#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen, Default, DecodeWithMemTracking)]
+pub struct HostCapacity {
+    pub vcpu: u32,
+    pub memory_gb: u32,
+    pub storage_gb: u64,
+    pub gpu_count: u32,
+}
+
+impl HostCapacity {
+    pub fn contains(&self, slice: &HostCapacity) -> bool {
+        self.vcpu >= slice.vcpu
+            && self.memory_gb >= slice.memory_gb
+            && self.storage_gb >= slice.storage_gb
+            && self.gpu_count >= slice.gpu_count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == HostCapacity::default()
+    }
+
+    pub fn saturating_add(&self, other: &HostCapacity) -> HostCapacity {
+        HostCapacity {
+            vcpu: self.vcpu.saturating_add(other.vcpu),
+            memory_gb: self.memory_gb.saturating_add(other.memory_gb),
+            storage_gb: self.storage_gb.saturating_add(other.storage_gb),
+            gpu_count: self.gpu_count.saturating_add(other.gpu_count),
+        }
+    }
+
+    pub fn saturating_sub(&self, other: &HostCapacity) -> HostCapacity {
+        HostCapacity {
+            vcpu: self.vcpu.saturating_sub(other.vcpu),
+            memory_gb: self.memory_gb.saturating_sub(other.memory_gb),
+            storage_gb: self.storage_gb.saturating_sub(other.storage_gb),
+            gpu_count: self.gpu_count.saturating_sub(other.gpu_count),
+        }
+    }
+}
+
+#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen, Default, DecodeWithMemTracking)]
+pub struct SlicePrices<Balance> {
+    pub vcpu: Balance,       // Price of one vCPU for a week
+    pub memory_gb: Balance,  // Price of one GB of memory for a week
+    pub storage_gb: Balance, // Price of one GB of storage for a week
+    pub gpu: Balance,        // Price of one GPU for a week
+}
+
+impl<Balance: AtLeast32BitUnsigned + Copy> SlicePrices<Balance> {
+    /// Weekly price of a slice, summed over its capacity
+    pub fn price(&self, slice: &HostCapacity) -> Balance {
+        self.vcpu
+            .saturating_mul(slice.vcpu.into())
+            .saturating_add(self.memory_gb.saturating_mul(slice.memory_gb.into()))
+            .saturating_add(self.storage_gb.saturating_mul(slice.storage_gb.saturated_into()))
+            .saturating_add(self.gpu.saturating_mul(slice.gpu_count.into()))
+    }
+}
+
+#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen, DecodeWithMemTracking)]
+pub struct Host<AccountId, Balance> {
+    pub provider: AccountId,
+    pub location: Region,
+    pub uptime_guarantee: u8,
+    pub gpu: Option<GPUSpecs>, // Model of the host GPUs, `gpu_count` is ignored
+    pub capacity: HostCapacity,
+    pub remaining: HostCapacity, // Capacity not allocated to rented slices
+    pub prices: SlicePrices<Balance>,
+}
+
+impl<AccountId: Clone, Balance> Host<AccountId, Balance> {
+    /// Resource offered by a slice of the host
+    pub fn slice(&self, slice: &HostCapacity) -> Option<Resource<AccountId>> {
+        let gpu_specs = match (&self.gpu, slice.gpu_count) {
+            (_, 0) => None,
+            (Some(gpu), gpu_count) => Some(GPUSpecs {
+                gpu_count,
+                ..gpu.clone()
+            }),
+            (None, _) => return None,
+        };
+
+        Some(Resource {
+            provider: self.provider.clone(),
+            category: ResourceCategory::Custom(CustomResourceSpecs {
+                vcpu: slice.vcpu.try_into().ok()?,
+                memory_gb: slice.memory_gb,
+                storage_gb: slice.storage_gb,
+                gpu_specs,
+            }),
+            location: self.location.clone(),
+            is_available: true,
+            uptime_guarantee: self.uptime_guarantee,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen, DecodeWithMemTracking)]
+pub enum CommitmentTerm {
+    SixMonths,    // 24 weeks, 15% off
+    TwelveMonths, // 48 weeks, 30% off
+}
+
+impl CommitmentTerm {
+    pub fn weeks(&self) -> u32 {
+        match self {
+            CommitmentTerm::SixMonths => 24,
+            CommitmentTerm::TwelveMonths => 48,
+        }
+    }
+
+    /// Discount on the price of every billing period during the commitment
+    pub fn discount(&self) -> Perbill {
+        match self {
+            CommitmentTerm::SixMonths => Perbill::from_percent(15),
+            CommitmentTerm::TwelveMonths => Perbill::from_percent(30),
+        }
+    }
+}
+
+#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen, DecodeWithMemTracking)]
+pub struct Commitment<AccountId, BlockNumber> {
+    pub renter: AccountId, // The resource is reserved to the renter until the end of the term
+    pub term: CommitmentTerm,
+    pub ends_at: BlockNumber,
+}
+
+#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen, DecodeWithMemTracking)]
+pub struct ContainerManifest<Hash> {
+    pub image: ImageDigest,
+    pub ports: DeploymentPorts, // Exposed ports
+    pub env_hash: Hash,         // Hash of the environment, shared off-chain with the provider
+    pub requests: ComputeSpecs, // Must fit within the rented resource
+}
+
+#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen, DecodeWithMemTracking)]
+pub enum DeploymentManifest<Hash> {
+    Container(ContainerManifest<Hash>),
+    OffChain(Hash), // Content hash of a manifest stored off-chain
+}
+
+#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen, DecodeWithMemTracking)]
+pub struct Deployment<Hash, BlockNumber> {
+    pub manifest: DeploymentManifest<Hash>,
+    pub version: u32, // Incremented on every update, starting at 1
+    pub updated_at: BlockNumber,
+}
+
+#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen, DecodeWithMemTracking)]
+pub enum AccessKey {
+    X25519([u8; 32]),
+    Sr25519([u8; 32]),
+}
+
+#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen, DecodeWithMemTracking)]
+pub struct AccessBundle<BlockNumber> {
+    pub key: AccessKey,               // Renter key the bundle is encrypted to
+    pub ciphertext: EncryptedBundle, // Endpoint and credentials, readable only by the renter
+    pub version: u32,                // Incremented on every rotation, starting at 1
+    pub posted_at: BlockNumber,
+}
+
+#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen, DecodeWithMemTracking)]
+pub struct Watcher<Balance> {
+    pub stake: Balance,              // Reserved, reduced by slashes
+    pub leaving: Option<EpochIndex>, // Epoch the watcher left the set in
+    pub pending: u32,                // Attestations not finalized yet, the stake stays bonded
+}
+
+#[derive(Clone, Copy, Default, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen, DecodeWithMemTracking)]
+pub struct LivenessTally {
+    pub alive: u32,
+    pub down: u32,
+}
+
+impl LivenessTally {
+    pub fn total(&self) -> u32 {
+        self.alive.saturating_add(self.down)
+    }
+
+    /// Liveness according to the majority of the attestations, none on a tie
+    pub fn verdict(&self) -> Option<bool> {
+        match self.alive.cmp(&self.down) {
+            core::cmp::Ordering::Greater => Some(true),
+            core::cmp::Ordering::Less => Some(false),
+            core::cmp::Ordering::Equal => None,
+        }
+    }
+}
+
+#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen, DecodeWithMemTracking)]
+pub struct HardwareAttestation<AccountId, Hash, BlockNumber> {
+    pub verifier: AccountId,
+    pub benchmark: Hash, // Hash of the full benchmark result, published off-chain
+    pub measured: CustomResourceSpecs,
+    pub verified: bool, // Whether the measured specs back the claimed category
+    pub attested_at: BlockNumber,
+}
+
+#[derive(Clone, Copy, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen, DecodeWithMemTracking)]
+pub enum AttestationTarget {
+    Resource(ResourceId),
+    Host(HostId), // Slices of the host share its attestation
+}
+
+#[derive(Clone, Copy, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen, DecodeWithMemTracking)]
+pub enum VerificationStatus {
+    Unverified,
+    Verified,
+    Failed, // The measured specs fall short of the claimed ones
+}
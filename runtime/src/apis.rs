@@ -45,9 +45,9 @@ use sp_version::RuntimeVersion;
 
 // Local module imports
 use super::{
-    AccountId, Balance, Block, ConsensusHook, Executive, InherentDataExt, Nonce, ParachainSystem,
-    Runtime, RuntimeCall, RuntimeGenesisConfig, SessionKeys, System, TransactionPayment,
-    SLOT_DURATION, VERSION,
+    AccountId, Balance, Block, ConsensusHook, Executive, InherentDataExt, Kumulus, Nonce,
+    ParachainSystem, Runtime, RuntimeCall, RuntimeGenesisConfig, SessionKeys, System,
+    TransactionPayment, SLOT_DURATION, VERSION,
 };
 
 // we move some impls outside so we can easily use them with `docify`.
@@ -169,6 +169,39 @@ impl_runtime_apis! {
         }
     }
 
+    impl pallet_kumulus::runtime_api::KumulusApi<Block, AccountId> for Runtime {
+        fn is_verified_provider(provider: AccountId) -> bool {
+            Kumulus::is_verified_provider(&provider)
+        }
+
+        fn verification_status(
+            resource_id: pallet_kumulus::ResourceId,
+        ) -> pallet_kumulus::VerificationStatus {
+            Kumulus::verification_status(resource_id)
+        }
+
+        fn available_resources(
+            verified_only: bool,
+        ) -> Vec<(
+            pallet_kumulus::ResourceId,
+            pallet_kumulus::Resource<AccountId>,
+            pallet_kumulus::VerificationStatus,
+        )> {
+            Kumulus::available_resources(verified_only)
+        }
+
+        fn resources_in_region(
+            filter: pallet_kumulus::RegionFilter,
+            verified_only: bool,
+        ) -> Vec<(
+            pallet_kumulus::ResourceId,
+            pallet_kumulus::Resource<AccountId>,
+            pallet_kumulus::VerificationStatus,
+        )> {
+            Kumulus::resources_in_region(filter, verified_only)
+        }
+    }
+
     impl pallet_transaction_payment_rpc_runtime_api::TransactionPaymentApi<Block, Balance> for Runtime {
         fn query_info(
             uxt: <Block as BlockT>::Extrinsic,
@@ -33,5 +33,4 @@ polkadot_sdk::frame_benchmarking::define_benchmarks!(
     [pallet_collator_selection, CollatorSelection]
     [cumulus_pallet_parachain_system, ParachainSystem]
     [cumulus_pallet_xcmp_queue, XcmpQueue]
-    [kumulus_bootstrap, KumulusBootstrap]
 );
@@ -30,15 +30,16 @@ use polkadot_sdk::{staging_parachain_info as parachain_info, staging_xcm as xcm,
 use polkadot_sdk::{staging_xcm_builder as xcm_builder, staging_xcm_executor as xcm_executor};
 
 // Substrate and Polkadot dependencies
-use cumulus_pallet_parachain_system::RelayNumberMonotonicallyIncreases;
-use cumulus_primitives_core::{AggregateMessageOrigin, ParaId};
+use cumulus_pallet_parachain_system::{RelayChainStateProof, RelayNumberMonotonicallyIncreases};
+use cumulus_primitives_core::{relay_chain::well_known_keys, AggregateMessageOrigin, ParaId};
 use frame_support::{
     derive_impl,
     dispatch::DispatchClass,
     parameter_types,
     traits::{
-        ConstBool, ConstU32, ConstU64, ConstU8, EitherOfDiverse, TransformOrigin, VariantCountOf,
-        AsEnsureOriginWithArg,
+        ConstBool, ConstU32, ConstU64, ConstU8, EitherOfDiverse, InstanceFilter, Randomness,
+        TransformOrigin,
+        VariantCountOf, AsEnsureOriginWithArg,
     },
     weights::{ConstantMultiplier, Weight},
     PalletId,
@@ -53,7 +54,11 @@ use polkadot_runtime_common::{
     xcm_sender::NoPriceForMessageDelivery, BlockHashCount, SlowAdjustingFeeUpdate,
 };
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
-use sp_runtime::Perbill;
+use codec::{Decode, DecodeWithMemTracking, Encode, MaxEncodedLen};
+use sp_runtime::{
+    traits::{BlakeTwo256, Hash as _, Verify},
+    Perbill, RuntimeDebug,
+};
 use sp_version::RuntimeVersion;
 use xcm::latest::prelude::BodyId;
 
@@ -61,10 +66,12 @@ use xcm::latest::prelude::BodyId;
 use super::{
     weights::{BlockExecutionWeight, ExtrinsicBaseWeight, RocksDbWeight},
     AccountId, Aura, Balance, Balances, Block, BlockNumber, CollatorSelection, ConsensusHook, Hash,
-    MessageQueue, Nonce, PalletInfo, ParachainSystem, Runtime, RuntimeCall, RuntimeEvent,
-    RuntimeFreezeReason, RuntimeHoldReason, RuntimeOrigin, RuntimeTask, Session, SessionKeys,
-    System, WeightToFee, XcmpQueue, AVERAGE_ON_INITIALIZE_RATIO, EXISTENTIAL_DEPOSIT, HOURS,
-    MAXIMUM_BLOCK_WEIGHT, MICRO_UNIT, NORMAL_DISPATCH_RATIO, SLOT_DURATION, VERSION,
+    MessageQueue, MultiBlockMigrations, Nonce, OriginCaller, PalletInfo, ParachainInfo,
+    ParachainSystem, Runtime, RuntimeCall, RuntimeEvent, RuntimeFreezeReason, RuntimeHoldReason,
+    RuntimeOrigin, RuntimeTask, Session, SessionKeys, Signature, System, TxExtension,
+    UncheckedExtrinsic, WeightToFee, XcmpQueue, AVERAGE_ON_INITIALIZE_RATIO, DAYS,
+    EXISTENTIAL_DEPOSIT, HOURS, MAXIMUM_BLOCK_WEIGHT, MICRO_UNIT, MILLI_UNIT, MINUTES,
+    NORMAL_DISPATCH_RATIO, SLOT_DURATION, VERSION,
 };
 use xcm_config::{RelayLocation, XcmOriginToTransactDispatchOrigin};
 
@@ -131,6 +138,29 @@ impl frame_system::Config for Runtime {
     /// The action to take on a Runtime Upgrade
     type OnSetCode = cumulus_pallet_parachain_system::ParachainSetCode<Self>;
     type MaxConsumers = frame_support::traits::ConstU32<16>;
+    /// Multi-block migrations, blocking transactions until they are done.
+    type MultiBlockMigrator = MultiBlockMigrations;
+}
+
+parameter_types! {
+    pub MbmServiceWeight: Weight = Perbill::from_percent(80) * RuntimeBlockWeights::get().max_block;
+}
+
+impl pallet_migrations::Config for Runtime {
+    type RuntimeEvent = RuntimeEvent;
+    #[cfg(not(feature = "runtime-benchmarks"))]
+    type Migrations = (
+        // `KumulusBootstrap` (index 51) was folded into `Kumulus`
+        pallet_kumulus::migrations::unify_bootstrap::MigrateToPendingBootstrappers<Runtime>,
+    );
+    #[cfg(feature = "runtime-benchmarks")]
+    type Migrations = pallet_migrations::mock_helpers::MockedMigrations;
+    type CursorMaxLen = ConstU32<65_536>;
+    type IdentifierMaxLen = ConstU32<256>;
+    type MigrationStatusHandler = ();
+    type FailedMigrationHandler = frame_support::migrations::FreezeChainOnFailedMigration;
+    type MaxServiceWeight = MbmServiceWeight;
+    type WeightInfo = pallet_migrations::weights::SubstrateWeight<Runtime>;
 }
 
 impl pallet_timestamp::Config for Runtime {
@@ -314,10 +344,370 @@ impl pallet_collator_selection::Config for Runtime {
     type WeightInfo = ();
 }
 
-/// Configure the pallet template in pallets/template.
-impl pallet_kumulus_bootstrap::Config for Runtime {
+parameter_types! {
+    pub const IdentityBasicDeposit: Balance = 10 * MILLI_UNIT;
+    pub const IdentityByteDeposit: Balance = 10 * MICRO_UNIT;
+    pub const IdentityUsernameDeposit: Balance = 10 * MILLI_UNIT;
+    pub const IdentitySubAccountDeposit: Balance = 10 * MILLI_UNIT;
+    pub const MaxAdditionalFields: u32 = 100;
+}
+
+/// On-chain identities, judged by registrars to verify kumulus providers.
+impl pallet_identity::Config for Runtime {
+    type RuntimeEvent = RuntimeEvent;
+    type Currency = Balances;
+    type BasicDeposit = IdentityBasicDeposit;
+    type ByteDeposit = IdentityByteDeposit;
+    type UsernameDeposit = IdentityUsernameDeposit;
+    type SubAccountDeposit = IdentitySubAccountDeposit;
+    type MaxSubAccounts = ConstU32<100>;
+    type IdentityInformation = pallet_identity::legacy::IdentityInfo<MaxAdditionalFields>;
+    type MaxRegistrars = ConstU32<20>;
+    type Slashed = ();
+    type ForceOrigin = EnsureRoot<AccountId>;
+    type RegistrarOrigin = EnsureRoot<AccountId>;
+    type OffchainSignature = Signature;
+    type SigningPublicKey = <Signature as Verify>::Signer;
+    type UsernameAuthorityOrigin = EnsureRoot<AccountId>;
+    type PendingUsernameExpiration = ConstU32<{ 7 * DAYS }>;
+    type UsernameGracePeriod = ConstU32<{ 30 * DAYS }>;
+    type MaxSuffixLength = ConstU32<7>;
+    type MaxUsernameLength = ConstU32<32>;
+    #[cfg(feature = "runtime-benchmarks")]
+    type BenchmarkHelper = ();
+    type WeightInfo = pallet_identity::weights::SubstrateWeight<Runtime>;
+}
+
+impl pallet_utility::Config for Runtime {
     type RuntimeEvent = RuntimeEvent;
-    type WeightInfo = pallet_kumulus_bootstrap::weights::SubstrateWeight<Runtime>;
+    type RuntimeCall = RuntimeCall;
+    type PalletsOrigin = OriginCaller;
+    type WeightInfo = pallet_utility::weights::SubstrateWeight<Runtime>;
+}
+
+parameter_types! {
+    // One storage item; key size is 32 + 32, value is 8 + 16 + 32 bytes
+    pub const MultisigDepositBase: Balance = 10 * MILLI_UNIT + 120 * 10 * MICRO_UNIT;
+    // Additional storage item size of 32 bytes per signatory
+    pub const MultisigDepositFactor: Balance = 32 * 10 * MICRO_UNIT;
+}
+
+impl pallet_multisig::Config for Runtime {
+    type RuntimeEvent = RuntimeEvent;
+    type RuntimeCall = RuntimeCall;
+    type Currency = Balances;
+    type DepositBase = MultisigDepositBase;
+    type DepositFactor = MultisigDepositFactor;
+    type MaxSignatories = ConstU32<100>;
+    type WeightInfo = pallet_multisig::weights::SubstrateWeight<Runtime>;
+}
+
+/// What a proxy is allowed to dispatch on behalf of its delegator. Batches of
+/// `pallet_utility` are allowed, their calls being filtered one by one.
+#[derive(
+    Copy,
+    Clone,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Encode,
+    Decode,
+    DecodeWithMemTracking,
+    RuntimeDebug,
+    MaxEncodedLen,
+    scale_info::TypeInfo,
+    Default,
+)]
+pub enum ProxyType {
+    /// Any call
+    #[default]
+    Any,
+    /// Calls that can't move funds to another account
+    NonTransfer,
+    /// Only rejecting the announcements of other proxies
+    CancelProxy,
+    /// Hot key of a kumulus provider: heartbeats, health reports and resource updates
+    KumulusProvider,
+    /// Renting, cancelling and topping up kumulus rentals
+    KumulusRenter,
+}
+
+impl InstanceFilter<RuntimeCall> for ProxyType {
+    fn filter(&self, c: &RuntimeCall) -> bool {
+        match self {
+            ProxyType::Any => true,
+            // Whitelisted, pallets added later stay out until reviewed
+            ProxyType::NonTransfer => match c {
+                RuntimeCall::Kumulus(call) => !matches!(
+                    call,
+                    pallet_kumulus::Call::withdraw_organisation_funds { .. }
+                ),
+                _ => matches!(
+                    c,
+                    RuntimeCall::System(..)
+                        | RuntimeCall::Session(..)
+                        | RuntimeCall::CollatorSelection(..)
+                        | RuntimeCall::Identity(..)
+                        | RuntimeCall::Utility(..)
+                        | RuntimeCall::Multisig(..)
+                        | RuntimeCall::Proxy(..)
+                ),
+            },
+            ProxyType::CancelProxy => matches!(
+                c,
+                RuntimeCall::Proxy(pallet_proxy::Call::reject_announcement { .. })
+                    | RuntimeCall::Utility(..)
+                    | RuntimeCall::Multisig(..)
+            ),
+            ProxyType::KumulusProvider => matches!(
+                c,
+                RuntimeCall::Kumulus(
+                    pallet_kumulus::Call::heartbeat { .. }
+                        | pallet_kumulus::Call::report_health { .. }
+                        | pallet_kumulus::Call::register_resource { .. }
+                        | pallet_kumulus::Call::register_host { .. }
+                        | pallet_kumulus::Call::update_host { .. }
+                        | pallet_kumulus::Call::deregister_host { .. }
+                        | pallet_kumulus::Call::set_rate_card { .. }
+                        | pallet_kumulus::Call::submit_usage_report { .. }
+                        | pallet_kumulus::Call::post_access_bundle { .. }
+                ) | RuntimeCall::Utility(..)
+            ),
+            ProxyType::KumulusRenter => matches!(
+                c,
+                RuntimeCall::Kumulus(
+                    pallet_kumulus::Call::rent_resource { .. }
+                        | pallet_kumulus::Call::rent_slice { .. }
+                        | pallet_kumulus::Call::rent_for_organisation { .. }
+                        | pallet_kumulus::Call::cancel_rental { .. }
+                        | pallet_kumulus::Call::top_up_rental { .. }
+                ) | RuntimeCall::Utility(..)
+            ),
+        }
+    }
+
+    fn is_superset(&self, o: &Self) -> bool {
+        match (self, o) {
+            (x, y) if x == y => true,
+            (ProxyType::Any, _) => true,
+            (_, ProxyType::Any) => false,
+            (ProxyType::NonTransfer, _) => true,
+            _ => false,
+        }
+    }
+}
+
+parameter_types! {
+    // One storage item; key size 32, value size 8
+    pub const ProxyDepositBase: Balance = 10 * MILLI_UNIT + 40 * 10 * MICRO_UNIT;
+    // Additional storage item size of 33 bytes
+    pub const ProxyDepositFactor: Balance = 33 * 10 * MICRO_UNIT;
+    pub const AnnouncementDepositBase: Balance = 10 * MILLI_UNIT + 48 * 10 * MICRO_UNIT;
+    pub const AnnouncementDepositFactor: Balance = 66 * 10 * MICRO_UNIT;
+}
+
+impl pallet_proxy::Config for Runtime {
+    type RuntimeEvent = RuntimeEvent;
+    type RuntimeCall = RuntimeCall;
+    type Currency = Balances;
+    type ProxyType = ProxyType;
+    type ProxyDepositBase = ProxyDepositBase;
+    type ProxyDepositFactor = ProxyDepositFactor;
+    type MaxProxies = ConstU32<32>;
+    type WeightInfo = pallet_proxy::weights::SubstrateWeight<Runtime>;
+    type MaxPending = ConstU32<32>;
+    type CallHasher = BlakeTwo256;
+    type AnnouncementDepositBase = AnnouncementDepositBase;
+    type AnnouncementDepositFactor = AnnouncementDepositFactor;
+}
+
+parameter_types! {
+    pub const KumulusPalletId: PalletId = PalletId(*b"kmls/esc");
+    pub const BlocksPerWeek: BlockNumber = 7 * DAYS;
+    pub const MinimumRentalDeposit: Balance = 10 * MILLI_UNIT;
+    pub const UsageDisputeWindow: BlockNumber = DAYS;
+    pub const ProviderBond: Balance = 100 * MILLI_UNIT;
+    pub const RentalDisputeWindow: BlockNumber = 7 * DAYS;
+    pub const ProviderUnbondingPeriod: BlockNumber = 14 * DAYS;
+    pub const EarlyTerminationPenalty: Perbill = Perbill::from_percent(50);
+    pub const OffchainHeartbeatInterval: BlockNumber = 10 * MINUTES;
+    pub const WatcherStake: Balance = 100 * MILLI_UNIT;
+    pub const WatcherEpoch: BlockNumber = HOURS;
+    pub const WatcherReward: Balance = MILLI_UNIT;
+    pub const WatcherSlash: Perbill = Perbill::from_percent(10);
+    pub const WatcherFee: Perbill = Perbill::from_percent(1);
+    pub const OrganisationDeposit: Balance = 10 * MILLI_UNIT;
+    pub const OrderDeposit: Balance = MILLI_UNIT;
+    pub const OrderLifetime: BlockNumber = 7 * DAYS;
+    pub const AuctionBidDeposit: Balance = MILLI_UNIT;
+    pub const MaxUsagePerReport: pallet_kumulus::UsageReport = pallet_kumulus::UsageReport {
+        cpu_seconds: 30 * 24 * 3600 * 256,
+        egress_gb: 100_000,
+        storage_gb_hours: 30 * 24 * 100_000,
+    };
+}
+
+/// Configure the kumulus marketplace pallet in pallets/kumulus.
+impl pallet_kumulus::Config for Runtime {
+    type RuntimeEvent = RuntimeEvent;
+    type Currency = Balances;
+    type BlocksPerWeek = BlocksPerWeek;
+    type PalletId = KumulusPalletId;
+    type MinimumDeposit = MinimumRentalDeposit;
+    type AdminOrigin = EnsureRoot<AccountId>;
+    type MaxBootstrappedResources = ConstU32<32>;
+    type MaxExpiringBootstrappers = ConstU32<16>;
+    type MaxPendingBootstrappers = ConstU32<256>;
+    type MaxPendingUsageReports = ConstU32<32>;
+    type UsageDisputeWindow = UsageDisputeWindow;
+    type MaxUsagePerReport = MaxUsagePerReport;
+    type ProviderBond = ProviderBond;
+    type DisputeWindow = RentalDisputeWindow;
+    type MaxDisputeEvidence = ConstU32<16>;
+    type ArbiterOrigin = EnsureRoot<AccountId>;
+    type MaxOpenOrders = ConstU32<256>;
+    type OrderDeposit = OrderDeposit;
+    type OrderLifetime = OrderLifetime;
+    type AuctionBidDeposit = AuctionBidDeposit;
+    type MaxAuctionBids = ConstU32<64>;
+    type MaxEndingAuctions = ConstU32<16>;
+    type EarlyTerminationPenalty = EarlyTerminationPenalty;
+    type AuthorityId = pallet_kumulus::crypto::ProviderAuthId;
+    type OffchainHeartbeatInterval = OffchainHeartbeatInterval;
+    type MaxHeartbeatResources = ConstU32<64>;
+    type Randomness = RelayBlockRandomness;
+    type WatcherStake = WatcherStake;
+    type WatcherEpoch = WatcherEpoch;
+    type WatchersPerResource = ConstU32<5>;
+    type WatcherQuorum = ConstU32<3>;
+    type HardwareQuorum = ConstU32<3>;
+    type MaxWatchers = ConstU32<256>;
+    type WatcherReward = WatcherReward;
+    type WatcherSlash = WatcherSlash;
+    type WatcherFee = WatcherFee;
+    type Identity = JudgedIdentity;
+    type ProviderUnbondingPeriod = ProviderUnbondingPeriod;
+    type MaxExpiringSuspensions = ConstU32<16>;
+    type MaxOrganisationMembers = ConstU32<64>;
+    type OrganisationDeposit = OrganisationDeposit;
+}
+
+/// Providers are verified by a `Reasonable` or `KnownGood` judgement of their identity.
+pub struct JudgedIdentity;
+
+impl pallet_kumulus::ProviderIdentity<AccountId> for JudgedIdentity {
+    fn is_verified(who: &AccountId) -> bool {
+        pallet_identity::IdentityOf::<Runtime>::get(who).is_some_and(|registration| {
+            registration.judgements.iter().any(|(_, judgement)| {
+                matches!(
+                    judgement,
+                    pallet_identity::Judgement::Reasonable | pallet_identity::Judgement::KnownGood
+                )
+            })
+        })
+    }
+}
+
+/// Randomness of the relay parent block, read from the relay state proof. Only available after
+/// the parachain inherent, i.e. from extrinsics and `on_finalize`. Reported as known since
+/// genesis when missing from the proof, so the watcher assignments wait for the next block.
+pub struct RelayBlockRandomness;
+
+impl Randomness<Hash, BlockNumber> for RelayBlockRandomness {
+    fn random(subject: &[u8]) -> (Hash, BlockNumber) {
+        let randomness = cumulus_pallet_parachain_system::ValidationData::<Runtime>::get()
+            .zip(cumulus_pallet_parachain_system::RelayStateProof::<Runtime>::get())
+            .and_then(|(data, proof)| {
+                RelayChainStateProof::new(
+                    ParachainInfo::parachain_id(),
+                    data.relay_parent_storage_root,
+                    proof,
+                )
+                .ok()
+            })
+            .and_then(|proof| {
+                proof
+                    .read_optional_entry::<Option<[u8; 32]>>(
+                        well_known_keys::CURRENT_BLOCK_RANDOMNESS,
+                    )
+                    .ok()
+                    .flatten()
+                    .flatten()
+            });
+
+        match randomness {
+            Some(randomness) => {
+                (BlakeTwo256::hash_of(&(randomness, subject)), System::block_number())
+            }
+            None => (BlakeTwo256::hash_of(&subject), 0),
+        }
+    }
+}
+
+impl frame_system::offchain::SigningTypes for Runtime {
+    type Public = <Signature as Verify>::Signer;
+    type Signature = Signature;
+}
+
+impl<LocalCall> frame_system::offchain::CreateTransactionBase<LocalCall> for Runtime
+where
+    RuntimeCall: From<LocalCall>,
+{
+    type Extrinsic = UncheckedExtrinsic;
+    type RuntimeCall = RuntimeCall;
+}
+
+/// Signed transactions of offchain workers, e.g. kumulus health reports.
+impl<LocalCall> frame_system::offchain::CreateSignedTransaction<LocalCall> for Runtime
+where
+    RuntimeCall: From<LocalCall>,
+{
+    fn create_signed_transaction<
+        C: frame_system::offchain::AppCrypto<Self::Public, Self::Signature>,
+    >(
+        call: RuntimeCall,
+        public: <Signature as Verify>::Signer,
+        account: AccountId,
+        nonce: Nonce,
+    ) -> Option<UncheckedExtrinsic> {
+        use sp_runtime::{
+            generic::{Era, SignedPayload},
+            traits::StaticLookup,
+            SaturatedConversion,
+        };
+
+        // Mortal for the largest power of two below `BlockHashCount`
+        let period = BlockHashCount::get()
+            .checked_next_power_of_two()
+            .map(|c| c / 2)
+            .unwrap_or(2) as u64;
+        let current_block = System::block_number()
+            .saturated_into::<u64>()
+            // The `System::block_number` is initialized with `n+1`, so the actual block number
+            // is `n`.
+            .saturating_sub(1);
+
+        let tx_ext: TxExtension = (
+            frame_system::CheckNonZeroSender::<Runtime>::new(),
+            frame_system::CheckSpecVersion::<Runtime>::new(),
+            frame_system::CheckTxVersion::<Runtime>::new(),
+            frame_system::CheckGenesis::<Runtime>::new(),
+            frame_system::CheckEra::<Runtime>::from(Era::mortal(period, current_block)),
+            frame_system::CheckNonce::<Runtime>::from(nonce),
+            frame_system::CheckWeight::<Runtime>::new(),
+            pallet_transaction_payment::ChargeTransactionPayment::<Runtime>::from(0),
+            cumulus_primitives_storage_weight_reclaim::StorageWeightReclaim::<Runtime>::new(),
+            frame_metadata_hash_extension::CheckMetadataHash::<Runtime>::new(false),
+        );
+
+        let raw_payload = SignedPayload::new(call, tx_ext).ok()?;
+        let signature = raw_payload.using_encoded(|payload| C::sign(payload, public))?;
+        let (call, tx_ext, _) = raw_payload.deconstruct();
+        let address = <Runtime as frame_system::Config>::Lookup::unlookup(account);
+
+        Some(UncheckedExtrinsic::new_signed(call, address, signature, tx_ext))
+    }
 }
 
 pub const UNIT: u128 = 1_000_000_000;
@@ -350,4 +740,109 @@ impl pallet_assets::Config for Runtime {
     type Extra = ();
     type RemoveItemsLimit = RemoveItemsLimit;
     type CallbackHandle = ();
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Proxy;
+    use frame_support::assert_ok;
+    use sp_runtime::{AccountId32, BuildStorage};
+
+    const OWNER: AccountId = AccountId32::new([1; 32]);
+    const DELEGATE: AccountId = AccountId32::new([2; 32]);
+    const DEST: AccountId = AccountId32::new([3; 32]);
+
+    fn new_test_ext() -> sp_io::TestExternalities {
+        let mut storage = frame_system::GenesisConfig::<Runtime>::default()
+            .build_storage()
+            .unwrap();
+        pallet_balances::GenesisConfig::<Runtime> {
+            balances: vec![(OWNER, 1_000 * MILLI_UNIT), (DELEGATE, 1_000 * MILLI_UNIT)],
+        }
+        .assimilate_storage(&mut storage)
+        .unwrap();
+
+        let mut ext = sp_io::TestExternalities::new(storage);
+        ext.execute_with(|| System::set_block_number(1));
+        ext
+    }
+
+    fn transfer() -> RuntimeCall {
+        RuntimeCall::Balances(pallet_balances::Call::transfer_allow_death {
+            dest: DEST.into(),
+            value: 10 * MILLI_UNIT,
+        })
+    }
+
+    #[test]
+    fn proxies_only_pass_whitelisted_calls() {
+        let remark = RuntimeCall::System(frame_system::Call::remark { remark: vec![] });
+        let heartbeat = RuntimeCall::Kumulus(pallet_kumulus::Call::heartbeat {});
+        let cancel = RuntimeCall::Kumulus(pallet_kumulus::Call::cancel_rental { resource_id: 0 });
+        let withdraw = RuntimeCall::Kumulus(pallet_kumulus::Call::withdraw_organisation_funds {
+            org_id: 0,
+            beneficiary: DEST,
+            amount: 1,
+        });
+        let sudo = RuntimeCall::Sudo(pallet_sudo::Call::sudo {
+            call: Box::new(remark.clone()),
+        });
+
+        assert!(ProxyType::KumulusProvider.filter(&heartbeat));
+        assert!(ProxyType::KumulusRenter.filter(&cancel));
+        for proxy_type in [ProxyType::KumulusProvider, ProxyType::KumulusRenter] {
+            for call in [&transfer(), &remark, &withdraw, &sudo] {
+                assert!(!proxy_type.filter(call), "{proxy_type:?} passed {call:?}");
+            }
+        }
+        assert!(!ProxyType::KumulusProvider.filter(&cancel));
+        assert!(!ProxyType::KumulusRenter.filter(&heartbeat));
+
+        assert!(ProxyType::NonTransfer.filter(&remark));
+        assert!(ProxyType::NonTransfer.filter(&cancel));
+        for call in [&transfer(), &withdraw, &sudo] {
+            assert!(
+                !ProxyType::NonTransfer.filter(call),
+                "NonTransfer passed {call:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn batched_calls_are_filtered_like_direct_ones() {
+        for proxy_type in [
+            ProxyType::NonTransfer,
+            ProxyType::KumulusProvider,
+            ProxyType::KumulusRenter,
+        ] {
+            new_test_ext().execute_with(|| {
+                assert_ok!(Proxy::add_proxy(
+                    RuntimeOrigin::signed(OWNER),
+                    DELEGATE.into(),
+                    proxy_type,
+                    0
+                ));
+
+                let batch = RuntimeCall::Utility(pallet_utility::Call::batch {
+                    calls: vec![transfer()],
+                });
+                assert_ok!(Proxy::proxy(
+                    RuntimeOrigin::signed(DELEGATE),
+                    OWNER.into(),
+                    None,
+                    Box::new(batch)
+                ));
+
+                System::assert_has_event(
+                    pallet_utility::Event::BatchInterrupted {
+                        index: 0,
+                        error: frame_system::Error::<Runtime>::CallFiltered.into(),
+                    }
+                    .into(),
+                );
+                assert_eq!(Balances::free_balance(DEST), 0);
+            });
+        }
+    }
+}
@@ -91,7 +91,8 @@ pub type TxExtension = (
 pub type UncheckedExtrinsic =
     generic::UncheckedExtrinsic<Address, RuntimeCall, Signature, TxExtension>;
 
-/// All migrations of the runtime, aside from the ones declared in the pallets.
+/// All single-block migrations of the runtime, aside from the ones declared in the pallets.
+/// Multi-block migrations are run by `MultiBlockMigrations`, see `configs`.
 ///
 /// This can be a tuple of types, each implementing `OnRuntimeUpgrade`.
 #[allow(unused_parens)]
@@ -274,6 +275,8 @@ mod runtime {
     pub type Timestamp = pallet_timestamp;
     #[runtime::pallet_index(3)]
     pub type ParachainInfo = parachain_info;
+    #[runtime::pallet_index(4)]
+    pub type MultiBlockMigrations = pallet_migrations;
 
     // Monetary stuff.
     #[runtime::pallet_index(10)]
@@ -307,8 +310,18 @@ mod runtime {
     #[runtime::pallet_index(33)]
     pub type MessageQueue = pallet_message_queue;
 
-    #[runtime::pallet_index(51)]
-    pub type KumulusBootstrap = pallet_kumulus_bootstrap;
+    // Accounts
+    #[runtime::pallet_index(40)]
+    pub type Identity = pallet_identity;
+    #[runtime::pallet_index(41)]
+    pub type Utility = pallet_utility;
+    #[runtime::pallet_index(42)]
+    pub type Multisig = pallet_multisig;
+    #[runtime::pallet_index(43)]
+    pub type Proxy = pallet_proxy;
+
+    #[runtime::pallet_index(50)]
+    pub type Kumulus = pallet_kumulus;
 
     #[runtime::pallet_index(52)]
     pub type Assets = pallet_assets;